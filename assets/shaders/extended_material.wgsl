@@ -72,6 +72,7 @@ fn vertex(vertex_no_morph: CustomVertexInput) -> CustomVertexOutput {
     #ifdef VERTEX_UVS_A
         out.uv = vertex_no_morph.uv;
     #endif
+    out.texture_tile = vertex_no_morph.texture_tile;
     #ifdef VERTEX_UVS_B
         out.uv_b = vertex_no_morph.uv_b;
     #endif
@@ -127,7 +128,8 @@ fn fragment(
     input.world_position = in.world_position;
     input.world_normal = in.world_normal;
 #ifdef VERTEX_UVS_A
-    input.uv = in.uv;
+    // Merged faces repeat their texture, so wrap the UVs into the texture's tile in the atlas
+    input.uv = in.texture_tile.xy + fract(in.uv) * in.texture_tile.zw;
 #endif // VERTEX_UVS_A
     input.instance_index = in.instance_index;
 
@@ -31,6 +31,8 @@ struct CustomVertexInput {
 
     @location(16) skylight: vec4<u32>,
 
+    @location(17) texture_tile: vec4<f32>,
+
 #ifdef MORPH_TARGETS
     @builtin(vertex_index) index: u32,
 #endif
@@ -61,5 +63,6 @@ struct CustomVertexOutput {
     @location(7) @interpolate(flat) visibility_range_dither: i32,
 #endif
 
-    @location(14) lighting: vec4<f32>
+    @location(14) lighting: vec4<f32>,
+    @location(15) @interpolate(flat) texture_tile: vec4<f32>
 }
\ No newline at end of file
@@ -2,9 +2,10 @@ use crate::game::entity::{GameObject};
 
 use crate::systems::networking::NetworkingSystem;
 use crate::systems::physics::PhysicsObject;
+use crate::systems::physics::interpolation::{RemoteSnapshots, ServerClock};
 use bevy::prelude::*;
 use nalgebra::{Quaternion, Vector3};
-use rc_shared::game_objects::GameObjectData;
+use rc_shared::game_objects::{GameObjectData, PlayerGameObjectData};
 use rc_shared::item::ItemStates;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::ReceivePacket;
@@ -23,19 +24,28 @@ pub fn messages_update(
     mut meshes: ResMut<Assets<Mesh>>,
     item_states: Res<ItemStates>,
     mut system: ResMut<NetworkingSystem>,
-    asset_service: Res<AssetService>
+    asset_service: Res<AssetService>,
+    mut remote_snapshots: Query<&mut RemoteSnapshots>,
+    mut clock: ResMut<ServerClock>,
+    time: Res<Time>,
 ) {
     for event in event_reader.read() {
         match &event.0 {
             Protocol::GameObjectMoved(update) => {
-                if let Some(Ok(mut transform)) = system
-                    .entity_mapping
-                    .get(&update.entity)
-                    .map(|v| physics_objects.get_mut(*v))
-                {
-                    transform.position.x = update.x;
-                    transform.position.y = update.y;
-                    transform.position.z = update.z;
+                clock.observe(update.tick, time.elapsed_seconds_f64());
+
+                let Some(entity) = system.entity_mapping.get(&update.entity) else {
+                    error!("Move event received before game_object created");
+                    continue
+                };
+
+                let position = Vector3::new(update.x, update.y, update.z);
+
+                if let Ok(mut snapshots) = remote_snapshots.get_mut(*entity) {
+                    snapshots.position.push(update.tick, position);
+                } else if let Ok(mut transform) = physics_objects.get_mut(*entity) {
+                    // The server is correcting our own player, so apply it straight away
+                    transform.position = position;
                 } else {
                     error!("Move event received before game_object created");
                 }
@@ -47,12 +57,21 @@ pub fn messages_update(
                     continue
                 };
 
+                clock.observe(update.tick, time.elapsed_seconds_f64());
+
+                let rotation = Quaternion::new(update.w, update.x, update.y, update.z);
+
+                if let Ok(mut snapshots) = remote_snapshots.get_mut(*entity) {
+                    snapshots.rotation.push(update.tick, rotation);
+                    continue
+                }
+
                 let Ok(mut physics) = physics_objects.get_mut(*entity) else {
                     warn!("Received GameObjectRotated event for unspawned game object");
                     continue
                 };
 
-                physics.rotation = Quaternion::new(update.w, update.x, update.y, update.z);
+                physics.rotation = rotation;
             }
             Protocol::SpawnGameObject(entity) => {
                 if system.entity_mapping.contains_key(&entity.id) {
@@ -71,6 +90,7 @@ pub fn messages_update(
                     GameObjectData::ItemDrop(item) => {
                         let identifier = &item.item_stack.item.identifier;
 
+                        entity_commands.insert(RemoteSnapshots::default());
                         entity_commands.insert(MaterialMeshBundle {
                             mesh: meshes.add(generate_item_mesh(identifier, &item_states)),
                             material: asset_service.translucent_texture_atlas_material.clone(),
@@ -90,6 +110,7 @@ pub fn messages_update(
                             entity_commands.insert(Player::new());
                             gravity = true;
                         } else {
+                            entity_commands.insert(RemoteSnapshots::default());
                             let entity = entity_commands.id();
                             get_player_model(
                                 &mut entity_commands,
@@ -100,10 +121,7 @@ pub fn messages_update(
                             );
                         }
 
-                        Aabb::new(
-                            Vector3::new(-0.35, 0.0, -0.35),
-                            Vector3::new(0.7, 1.85, 0.7),
-                        )
+                        PlayerGameObjectData::collider()
                     }
                     _ => unimplemented!()
                 };
@@ -12,6 +12,7 @@ use rc_networking::types::SendPacket;
 use rc_shared::aabb::Aabb;
 use rc_shared::block::BlockStates;
 use rc_shared::helpers::{from_bevy_vec3, global_to_local_position};
+use rc_shared::light::LightUpdate;
 use crate::game::entity::GameObject;
 use crate::game::interaction::MAX_INTERACTION_DISTANCE;
 use crate::systems::camera::MainCamera;
@@ -103,6 +104,7 @@ pub fn mouse_interaction_place(
 
     // Found chunk! Update block
     chunk.world.set(inner_loc, block_id);
+    chunks.light_updates.push(LightUpdate::Block(pos));
 
     // Rerender
     rerender_chunk_event.send(RerenderChunkRequest {
@@ -8,4 +8,5 @@ pub mod state;
 pub mod world;
 pub mod game_object;
 pub mod disconnect;
-pub mod game_mode;
\ No newline at end of file
+pub mod game_mode;
+pub mod pipe_items;
\ No newline at end of file
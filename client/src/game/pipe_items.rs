@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rc_networking::protocol::Protocol;
+use rc_networking::types::ReceivePacket;
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::item::ItemStates;
+use crate::game::game_object::mesh::generate_item_mesh;
+use crate::state::AppState;
+use crate::systems::asset::AssetService;
+
+/// How large items are drawn while inside a pipe
+const PIPE_ITEM_SCALE: f32 = 0.6;
+
+pub struct PipeItemPlugin;
+
+impl Plugin for PipeItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PipeItems>()
+            .add_systems(Update, (pipe_item_messages, move_pipe_items).chain())
+            .add_systems(OnEnter(AppState::MainMenu), clear_pipe_items);
+    }
+}
+
+/// The entities of the items currently travelling through pipes, by the server's item id
+#[derive(Resource, Default)]
+pub struct PipeItems {
+    entities: HashMap<u64, Entity>,
+}
+
+#[derive(Component)]
+pub struct PipeItem {
+    path: Vec<GlobalBlockPosition>,
+    speed: f32,
+    /// How many blocks along `path` the item has travelled
+    progress: f32,
+}
+
+impl PipeItem {
+    /// The centre of the pipes the item is between, blended by how far along it is
+    fn translation(&self) -> Vec3 {
+        let last = self.path.len() - 1;
+        let progress = self.progress.min(last as f32);
+
+        let from = self.path[progress as usize];
+        let to = self.path[(progress.ceil() as usize).min(last)];
+        let from = Vec3::new(from.x as f32, from.y as f32, from.z as f32);
+        let to = Vec3::new(to.x as f32, to.y as f32, to.z as f32);
+
+        from.lerp(to, progress.fract()) + Vec3::splat(0.5)
+    }
+}
+
+fn pipe_item_messages(
+    mut event_reader: EventReader<ReceivePacket>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut pipe_items: ResMut<PipeItems>,
+    item_states: Res<ItemStates>,
+    asset_service: Res<AssetService>,
+) {
+    for event in event_reader.read() {
+        match &event.0 {
+            Protocol::PipeItemSent(packet) => {
+                if packet.path.is_empty() {
+                    continue;
+                }
+
+                let item = PipeItem {
+                    path: packet.path.clone(),
+                    speed: packet.speed,
+                    progress: 0.0,
+                };
+
+                let entity = commands
+                    .spawn(MaterialMeshBundle {
+                        mesh: meshes.add(generate_item_mesh(&packet.item_identifier, &item_states)),
+                        material: asset_service.translucent_texture_atlas_material.clone(),
+                        transform: Transform::from_translation(item.translation())
+                            .with_scale(Vec3::splat(PIPE_ITEM_SCALE)),
+                        ..default()
+                    })
+                    .insert(item)
+                    .id();
+
+                if let Some(previous) = pipe_items.entities.insert(packet.id, entity) {
+                    commands.entity(previous).despawn_recursive();
+                }
+            }
+            Protocol::PipeItemRemoved(packet) => {
+                if let Some(entity) = pipe_items.entities.remove(&packet.id) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Moves items along their path. They wait at the end until the server says they've been delivered.
+fn move_pipe_items(mut query: Query<(&mut Transform, &mut PipeItem)>, time: Res<Time>) {
+    for (mut transform, mut item) in query.iter_mut() {
+        item.progress += item.speed * time.delta_seconds();
+        transform.translation = item.translation();
+    }
+}
+
+fn clear_pipe_items(mut commands: Commands, mut pipe_items: ResMut<PipeItems>) {
+    for (_, entity) in pipe_items.entities.drain() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -12,6 +12,7 @@ use rc_networking::types::SendPacket;
 use rc_shared::block::{BlockId, BlockStates};
 use rc_shared::chunk::ChunkSystemTrait;
 use rc_shared::helpers::global_to_local_position;
+use rc_shared::light::LightUpdate;
 
 enum DestroyBlockCommand {
     Skip,
@@ -70,13 +71,12 @@ pub fn destroy_block_system(
 
         // Apply block modification
         if let Some(new_block_id) = output_block_id {
+            let mut chunks = world.get_resource_mut::<ChunkSystem>().unwrap();
+
             // Fetch chunk
-            if let Some(chunk) = world
-                .get_resource_mut::<ChunkSystem>()
-                .unwrap()
-                .get_raw_chunk_mut(&chunk_loc)
-            {
+            if let Some(chunk) = chunks.get_raw_chunk_mut(&chunk_loc) {
                 chunk.set(inner_loc, new_block_id);
+                chunks.light_updates.push(LightUpdate::Block(event.position));
             } else {
                 warn!("Attempted to destroy block in unloaded chunk {:?}", event)
             }
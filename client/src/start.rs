@@ -2,6 +2,7 @@ use std::sync::OnceLock;
 use bevy_mod_billboard::prelude::BillboardPlugin;
 use crate::game::events::GameEventsPlugin;
 use crate::game::game_object::GameObjectPlugin;
+use crate::game::pipe_items::PipeItemPlugin;
 use crate::game::interaction::highlight::{
     mouse_highlight_interaction, setup_highlights, HighlightData,
 };
@@ -133,6 +134,7 @@ pub fn start() {
         .add_plugins(BlockStatesPlugin)
         .add_plugins(ItemStatesPlugin)
         .add_plugins(GameObjectPlugin)
+        .add_plugins(PipeItemPlugin)
         .add_plugins(WasmPlugin)
 
         .add_systems(Startup, create_states)
@@ -3,7 +3,7 @@ use bevy::prelude::*;
 use bevy::render::mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
 use bevy::render::render_resource::{AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError};
 use crate::systems::asset::material::translucent_chunk_extension::ChunkMaterialUniform;
-use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH};
+use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_TEXTURE_TILE};
 
 pub type ChunkMaterial = ExtendedMaterial<StandardMaterial, ChunkMaterialExtension>;
 
@@ -33,6 +33,7 @@ impl MaterialExtension for ChunkMaterialExtension {
 
         add_vertex_extension(layout, descriptor, ATTRIBUTE_LIGHTING_COLOR, 14);
         add_vertex_extension(layout, descriptor, ATTRIBUTE_SKYLIGHT_STRENGTH, 16);
+        add_vertex_extension(layout, descriptor, ATTRIBUTE_TEXTURE_TILE, 17);
 
         Ok(())
     }
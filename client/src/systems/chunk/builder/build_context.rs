@@ -1,20 +1,14 @@
 use std::collections::HashMap;
 use fnv::{FnvBuildHasher, FnvHashMap};
-use nalgebra::{Vector2, Vector3};
+use nalgebra::Vector3;
 use serde::{Deserialize, Serialize};
 use rc_shared::block::BlockStates;
 use rc_shared::chunk::LightingColor;
-use rc_shared::CHUNK_SIZE;
 use crate::systems::chunk::nearby_cache::NearbyChunkCache;
 use crate::systems::chunk::nearby_chunk_map::NearbyChunkMap;
-use crate::systems::chunk::nearby_column_cache::NearbyChunkColumnCache;
 
 #[derive(Serialize, Deserialize)]
 pub struct ChunkBuildContext {
-    // Location of all lights in the surrounding chunks
-    pub lights: Vec<(Vector3<i32>, [u8; 4])>,
-    // Location of all points of sunlight in the surrounding chunks
-    pub sunlight_points: Vec<Vector3<i32>>,
     // Translucency of all blocks in the surrounding chunks.
     // TODO: Convert this into a more compressed format?
     pub translucency_map: NearbyChunkMap<bool>,
@@ -28,19 +22,15 @@ pub struct ChunkBuildContextNeighborBlockData {
     pub is_transparent: bool
 }
 
-// Stores any context a chunk may need to build lighting. Used so that chunk can be chucked at another thread
+// Stores any context a chunk may need to build its mesh. Used so that chunk can be chucked at another thread
 impl ChunkBuildContext {
     pub fn new(
         states: &BlockStates,
         cache: &NearbyChunkCache,
-        column_cache: &NearbyChunkColumnCache
     ) -> ChunkBuildContext {
 
-        assert_eq!(Vector2::new(cache.position().x, cache.position().z), column_cache.position());
-
         let chunk_pos = cache.position();
 
-        let mut lights = Vec::new();
         let mut translucency_map: NearbyChunkMap<bool> = NearbyChunkMap::new_empty(chunk_pos);
         let mut surrounding_data = FnvHashMap::default();
 
@@ -58,64 +48,18 @@ impl ChunkBuildContext {
 
             *entry.data = visual_block.translucent;
 
-            // Store all lights in surrounding chunks
-            if visual_block.emission[3] != 0 {
-                lights.push((
-                    entry.world_position,
-                    visual_block.emission,
-                ));
-            }
-
             // Store blocks that touch this chunk, for lighting and culling
             if is_neighbor_block(entry.chunk_position - chunk_pos, entry.block_position) {
                 surrounding_data.insert(entry.world_position,
                     ChunkBuildContextNeighborBlockData {
-                        light: LightingColor::default(),
+                        light: chunk.light.get(entry.block_position).color(),
                         is_transparent: visual_block.translucent,
                     }
                 );
             }
         });
 
-        // Add skylight to array
-        let mut sunlight_points = vec![];
-        let rebuild_chunk_pos = cache.position();
-        let y_range = ((rebuild_chunk_pos.y - 1) * CHUNK_SIZE as i32)..((rebuild_chunk_pos.y + 2) * CHUNK_SIZE as i32);
-
-        // Loop through all surrounding blocks
-        for chunk_x in (rebuild_chunk_pos.x - 1)..=(rebuild_chunk_pos.x + 1) {
-            for chunk_z in (rebuild_chunk_pos.z - 1)..=(rebuild_chunk_pos.z + 1) {
-
-                let Some(chunk) = column_cache.get_chunk(Vector2::new(chunk_x, chunk_z)) else {
-                  continue
-                };
-
-                for x in 0..CHUNK_SIZE {
-                    for z in 0..CHUNK_SIZE {
-
-                        // Fetch the y value of the skylight, and add a light if we need it
-                        let Some(y_value) = chunk.skylight_level[x][z] else {
-                            continue
-                        };
-
-                        // If light isn't applicable
-                        if y_range.contains(&y_value) {
-                            let pos = Vector3::new(
-                                (chunk_x * CHUNK_SIZE as i32) + x as i32,
-                                y_value,
-                                (chunk_z * CHUNK_SIZE as i32) + z as i32
-                            );
-
-                            sunlight_points.push(pos);
-                        }
-                    }
-                }
-            }
-        }
-
         ChunkBuildContext {
-            lights,
-            sunlight_points,
             translucency_map,
             surrounding_data,
         }
@@ -154,15 +98,19 @@ fn is_neighbor_block(relative_chunk_pos: Vector3<i32>, block_pos: Vector3<usize>
 #[cfg(test)]
 mod tests {
     use fnv::FnvHashMap;
-    use nalgebra::{Vector2, Vector3};
-    use rc_shared::block::BlockStates;
-    use rc_shared::block::types::VisualBlock;
+    use nalgebra::Vector3;
+    use rc_shared::block::{test_block_states, BlockId, BlockStates};
     use rc_shared::chunk::ChunkDataStorage;
+    use rc_shared::light::{update_light, LightUpdate};
     use rc_shared::viewable_direction::ViewableDirectionBitMap;
     use crate::systems::chunk::builder::build_context::{ChunkBuildContext, is_neighbor_block};
     use crate::systems::chunk::data::ChunkData;
     use crate::systems::chunk::nearby_cache::NearbyChunkCache;
-    use crate::systems::chunk::nearby_column_cache::NearbyChunkColumnCache;
+    use crate::systems::chunk::ChunkSystem;
+
+    fn block_id(states: &BlockStates, identifier: &str) -> BlockId {
+        states.get_by_identifier(identifier).unwrap().1.get_id()
+    }
 
     #[test]
     fn neighbor_test_cases() {
@@ -180,27 +128,8 @@ mod tests {
     #[test]
     fn context_test_cases() {
 
-        let mut states = BlockStates::new();
-
-        states.states.push(VisualBlock {
-            translucent: true,
-            full: false,
-            draw_betweens: false,
-            faces: vec![],
-            collision_boxes: vec![],
-            bounding_boxes: vec![],
-            emission: [0; 4],
-        });
-
-        states.states.push(VisualBlock {
-            translucent: false,
-            full: true,
-            draw_betweens: false,
-            faces: vec![],
-            collision_boxes: vec![],
-            bounding_boxes: vec![],
-            emission: [0; 4],
-        });
+        let states = test_block_states();
+        let stone = block_id(states, "mcv3::block::Stone");
 
         let mut chunks = FnvHashMap::default();
 
@@ -213,8 +142,8 @@ mod tests {
 
         let mut data = [[[0; 16]; 16]; 16];
 
-        data[0][0][0] = 1;
-        data[0][1][0] = 1;
+        data[0][0][0] = stone;
+        data[0][1][0] = stone;
 
         chunks.insert(Vector3::new(0, 1, 0), ChunkData::new_handleless(
             ChunkDataStorage::Data(Box::new(data)),
@@ -223,7 +152,7 @@ mod tests {
 
         let mut data = [[[0; 16]; 16]; 16];
 
-        data[15][0][0] = 1;
+        data[15][0][0] = stone;
 
         chunks.insert(Vector3::new(-1, 0, 0), ChunkData::new_handleless(
             ChunkDataStorage::Data(Box::new(data)),
@@ -231,46 +160,22 @@ mod tests {
         ));
 
         let chunk_cache = NearbyChunkCache::from_map(&chunks, Vector3::new(0, 0, 0));
-        let column_cache = NearbyChunkColumnCache::empty(Vector2::new(0, 0));
-
         let context = ChunkBuildContext::new(
-            &states,
+            states,
             &chunk_cache,
-            &column_cache
         );
 
-        assert!(context.surrounding_data.get(&Vector3::new(0, 16, 0)).is_some());
-        assert!(context.surrounding_data.get(&Vector3::new(0, 17, 0)).is_none());
-        assert!(context.surrounding_data.get(&Vector3::new(-1, 0, 0)).is_some());
-        assert!(context.surrounding_data.get(&Vector3::new(0, 0, 0)).is_none());
+        assert!(context.surrounding_data.contains_key(&Vector3::new(0, 16, 0)));
+        assert!(!context.surrounding_data.contains_key(&Vector3::new(0, 17, 0)));
+        assert!(context.surrounding_data.contains_key(&Vector3::new(-1, 0, 0)));
+        assert!(!context.surrounding_data.contains_key(&Vector3::new(0, 0, 0)));
     }
 
     #[test]
     fn context_viewable_cases() {
 
-        let mut states = BlockStates::new();
-
-        states.states.push(VisualBlock {
-            identifier: "mcv3::Air".to_string(),
-            translucent: true,
-            full: false,
-            draw_betweens: false,
-            faces: vec![],
-            collision_boxes: vec![],
-            bounding_boxes: vec![],
-            emission: [0; 4],
-        });
-
-        states.states.push(VisualBlock {
-            identifier: "mcv3::Stone".to_string(),
-            translucent: false,
-            full: true,
-            draw_betweens: false,
-            faces: vec![],
-            collision_boxes: vec![],
-            bounding_boxes: vec![],
-            emission: [0; 4],
-        });
+        let states = test_block_states();
+        let stone = block_id(states, "mcv3::block::Stone");
 
         let mut chunks = FnvHashMap::default();
 
@@ -283,8 +188,8 @@ mod tests {
 
         let mut data = [[[0; 16]; 16]; 16];
 
-        data[0][0][0] = 1;
-        data[0][1][0] = 1;
+        data[0][0][0] = stone;
+        data[0][1][0] = stone;
 
         chunks.insert(Vector3::new(0, 1, 4), ChunkData::new_handleless(
             ChunkDataStorage::Data(Box::new(data)),
@@ -293,7 +198,7 @@ mod tests {
 
         let mut data = [[[0; 16]; 16]; 16];
 
-        data[15][0][0] = 1;
+        data[15][0][0] = stone;
 
         chunks.insert(Vector3::new(-1, 0, 4), ChunkData::new_handleless(
             ChunkDataStorage::Data(Box::new(data)),
@@ -301,12 +206,9 @@ mod tests {
         ));
 
         let chunk_cache = NearbyChunkCache::from_map(&chunks, Vector3::new(0, 0, 4));
-        let column_cache = NearbyChunkColumnCache::empty(Vector2::new(0, 0));
-
         let context = ChunkBuildContext::new(
-            &states,
+            states,
             &chunk_cache,
-            &column_cache
         );
 
         //println!("{:?}", context.surrounding_data);
@@ -314,7 +216,7 @@ mod tests {
         let main_chunk = chunks.get(&Vector3::new(0,0,4)).unwrap();
 
         let visibility_map = main_chunk.generate_viewable_map(
-            &states,
+            states,
             &context,
             false
         );
@@ -323,5 +225,47 @@ mod tests {
         assert!(visibility_map[1][15][0].has_flag(ViewableDirectionBitMap::Top));
     }
 
+    #[test]
+    fn context_light_filter_cases() {
+        let states = test_block_states();
+        let water = block_id(states, "mcv3::block::Water");
+
+        // A lamp by the edge of chunk 0,0,0, lighting water and air the same distance away in the edge blocks
+        let mut data = [[[0; 16]; 16]; 16];
+
+        data[14][8][8] = block_id(states, "mcv3::block::Lamp");
+        data[15][9][8] = water;
+
+        let mut chunks = ChunkSystem::new();
+
+        chunks.chunks.insert(Vector3::new(0, 0, 0), ChunkData::new_handleless(
+            ChunkDataStorage::Data(Box::new(data)),
+            Vector3::new(0, 0, 0)
+        ));
+
+        chunks.chunks.insert(Vector3::new(1, 0, 0), ChunkData::new_handleless(
+            ChunkDataStorage::Data(Box::new([[[0; 16]; 16]; 16])),
+            Vector3::new(1, 0, 0)
+        ));
 
-}
\ No newline at end of file
+        update_light(&mut chunks, states, &[
+            LightUpdate::Chunk(Vector3::new(0, 0, 0)),
+            LightUpdate::Chunk(Vector3::new(1, 0, 0)),
+        ]);
+
+        let chunk_cache = NearbyChunkCache::from_map(&chunks.chunks, Vector3::new(1, 0, 0));
+        let context = ChunkBuildContext::new(
+            states,
+            &chunk_cache,
+        );
+
+        let in_air = &context.surrounding_data.get(&Vector3::new(15, 7, 8)).unwrap().light;
+        let in_water = &context.surrounding_data.get(&Vector3::new(15, 9, 8)).unwrap().light;
+
+        assert!(in_air.r > 0);
+        assert!(in_water.r < in_air.r);
+
+        // Water lets less red than blue through
+        assert!(in_air.r - in_water.r > in_air.b - in_water.b);
+    }
+}
@@ -6,7 +6,6 @@ use rc_shared::block::BlockStates;
 use rc_shared::chunk::ChunkDataStorage;
 use crate::systems::chunk::builder::entry::MeshBuildEntry;
 use crate::systems::chunk::builder::generate_mesh::UpdateChunkMesh;
-use crate::systems::chunk::builder::lighting::LightingUpdateData;
 use crate::systems::chunk::builder::{RerenderChunkRequest, RerenderChunkFlagContext};
 use crate::systems::chunk::builder::thread::{ChunkBuilderScheduler, ChunkBuilderSchedulerTrait};
 use crate::systems::chunk::builder::thread::executor::ChunkBuilderExecutor;
@@ -18,7 +17,7 @@ pub struct MeshBuilderContext {
     // A priority list of chunks to build
     pub chunks: BinaryHeap<MeshBuildEntry>,
 
-    pub processing_chunk_handles: Vec<Task<(UpdateChunkMesh, LightingUpdateData)>>,
+    pub processing_chunk_handles: Vec<Task<UpdateChunkMesh>>,
 
     pub scheduler: ChunkBuilderScheduler
 }
@@ -6,9 +6,11 @@ use serde::{Deserialize, Serialize};
 use rc_shared::block::BlockStates;
 use rc_shared::chunk::LightingColor;
 use rc_shared::helpers::global_to_local_position;
-use rc_shared::viewable_direction::{ViewableDirection, BLOCK_SIDES};
+use rc_shared::viewable_direction::{AxisAlignedDirection, ViewableDirection, BLOCK_SIDES};
 use rc_shared::CHUNK_SIZE;
 use crate::systems::chunk::builder::build_context::ChunkBuildContext;
+use crate::systems::chunk::builder::greedy::GreedyMesher;
+use crate::systems::chunk::occlusion::ChunkConnectivity;
 use crate::utils::mesh::draw_kit::DrawKit;
 
 #[derive(Component, Serialize, Deserialize)]
@@ -17,6 +19,7 @@ pub struct UpdateChunkMesh {
     pub opaque: DrawKit,
     pub translucent: DrawKit,
     pub viewable_map: Option<[[[ViewableDirection; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
+    pub connectivity: ChunkConnectivity,
 }
 
 impl ChunkData {
@@ -26,11 +29,23 @@ impl ChunkData {
         edge_faces: bool,
         context: &ChunkBuildContext,
     ) -> UpdateChunkMesh {
+        self.build_mesh_with(block_states, edge_faces, context, true)
+    }
+
+    /// Builds the chunk's mesh, merging the faces of solid blocks into larger faces when `greedy` is set
+    fn build_mesh_with(
+        &self,
+        block_states: &BlockStates,
+        edge_faces: bool,
+        context: &ChunkBuildContext,
+        greedy: bool,
+    ) -> UpdateChunkMesh {
 
         let viewable = self.generate_viewable_map(block_states, context, edge_faces);
 
         let mut opaque = DrawKit::new();
         let mut translucent = DrawKit::new().with_wind_strength();
+        let mut greedy_mesher = GreedyMesher::new();
 
         // Create the buffers to add the mesh data into
         let chunk = &self.world;
@@ -47,7 +62,7 @@ impl ChunkData {
                     if block_id != 0 && viewable != 0 {
                         let block = block_states.get_block_from_id(block_id);
 
-                        let mut light_color = [self.light_levels[x][y][z]; 6];
+                        let mut light_color = [self.light.get(pos).color(); 6];
 
                         for (i, side) in BLOCK_SIDES.iter().enumerate() {
                             let world_position = Vector3::new(x, y, z).cast::<i32>()
@@ -57,7 +72,7 @@ impl ChunkData {
                             let (chunk_pos, local_pos) = global_to_local_position(world_position);
 
                             if chunk_pos == self.position {
-                                light_color[i] = self.light_levels[local_pos.x][local_pos.y][local_pos.z];
+                                light_color[i] = self.light.get(local_pos).color();
                                 continue;
                             }
 
@@ -69,27 +84,143 @@ impl ChunkData {
                         }
 
                         let visual_block = block.draw();
-                        visual_block.draw(
-                            Vector3::new(x as f32, y as f32, z as f32),
-                            ViewableDirection(viewable),
-                            light_color,
-                            if visual_block.translucent {
-                                &mut translucent
+
+                        if visual_block.translucent {
+                            visual_block.draw(
+                                Vector3::new(x as f32, y as f32, z as f32),
+                                ViewableDirection(viewable),
+                                light_color,
+                                &mut translucent,
+                            );
+                            continue;
+                        }
+
+                        for (i, face) in visual_block.faces.iter().enumerate() {
+                            if !ViewableDirection(viewable).has_flag(face.direction) && face.edge {
+                                // Not visible from that direction and marked as an edge face, so cull
+                                continue;
+                            }
+
+                            let color = light_color[AxisAlignedDirection::from(face.direction) as usize];
+
+                            if greedy && GreedyMesher::can_merge(visual_block, face) {
+                                greedy_mesher.add(pos, block_id, i, face, color);
                             } else {
-                                &mut opaque
-                            },
-                        );
+                                opaque.draw_face(Vector3::new(x as f32, y as f32, z as f32), face, color);
+                            }
+                        }
                     }
                 }
             }
         }
 
+        greedy_mesher.draw(block_states, &mut opaque);
+
         // Check top faces
         UpdateChunkMesh {
             chunk: self.position,
             opaque,
             translucent,
             viewable_map: Some(viewable),
+            connectivity: ChunkConnectivity::from_blocks(&self.world, block_states),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use web_time::Instant;
+    use rc_shared::chunk::ChunkDataStorage;
+    use rc_shared::CHUNK_SIZE;
+    use rc_shared::block::test_block_states;
+    use crate::systems::chunk::builder::build_context::ChunkBuildContext;
+    use crate::systems::chunk::data::ChunkData;
+    use crate::systems::chunk::nearby_cache::NearbyChunkCache;
+    use crate::systems::chunk::ChunkSystem;
+
+    /// The number of opaque faces drawn for a chunk with nothing around it
+    fn opaque_faces(world: ChunkDataStorage, greedy: bool) -> usize {
+        let states = test_block_states();
+        let position = Vector3::new(0, 0, 0);
+
+        let mut chunks = ChunkSystem::new();
+        chunks.chunks.insert(position, ChunkData::new_handleless(world, position));
+
+        let cache = NearbyChunkCache::from_service(&chunks, position);
+        let context = ChunkBuildContext::new(states, &cache);
+
+        chunks.chunks[&position].build_mesh_with(states, false, &context, greedy).opaque.positions.len() / 4
+    }
+
+    #[test]
+    fn test_greedy_meshing_merges_faces() {
+        let states = test_block_states();
+        let stone = states.get_by_identifier("mcv3::block::Stone").unwrap().1.get_id();
+
+        // A single layer of stone across the chunk
+        let mut data = [[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
+        for column in data.iter_mut() {
+            column[3] = [stone; CHUNK_SIZE];
         }
+
+        // Every block's top and bottom. Sides along the chunk's edge aren't drawn as there's nothing next to it
+        assert_eq!(opaque_faces(ChunkDataStorage::Data(Box::new(data)), false), 2 * CHUNK_SIZE * CHUNK_SIZE);
+        // The top and bottom each as a single face
+        assert_eq!(opaque_faces(ChunkDataStorage::Data(Box::new(data)), true), 2);
+    }
+
+    /// Meshes a few hundred chunks both ways, so is left out of normal test runs.
+    /// Run with `cargo test benchmark_greedy_meshing -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn benchmark_greedy_meshing() {
+        let states = test_block_states();
+
+        let stone = states.get_by_identifier("mcv3::block::Stone").unwrap().1.get_id();
+        let grass = states.get_by_identifier("mcv3::block::Grass").unwrap().1.get_id();
+
+        // Flat terrain of stone under a layer of grass
+        let mut chunks = ChunkSystem::new();
+        for x in -4..=4 {
+            for y in -1..=1 {
+                for z in -4..=4 {
+                    let mut data = [[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
+                    if y < 0 {
+                        data = [[[stone; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
+                    } else if y == 0 {
+                        for column in data.iter_mut() {
+                            column[..7].fill([stone; CHUNK_SIZE]);
+                            column[7] = [grass; CHUNK_SIZE];
+                        }
+                    }
+
+                    let chunk = ChunkData::new_handleless(ChunkDataStorage::Data(Box::new(data)), Vector3::new(x, y, z));
+                    chunks.chunks.insert(chunk.position, chunk);
+                }
+            }
+        }
+
+        let mut vertices = [0; 2];
+        for (i, greedy) in [false, true].into_iter().enumerate() {
+            let start = Instant::now();
+
+            for chunk in chunks.chunks.values() {
+                let cache = NearbyChunkCache::from_service(&chunks, chunk.position);
+                let context = ChunkBuildContext::new(states, &cache);
+
+                let mesh = chunk.build_mesh_with(states, false, &context, greedy);
+                vertices[i] += mesh.opaque.positions.len() + mesh.translucent.positions.len();
+            }
+
+            println!(
+                "Greedy: {}, {} vertices, took {}ms per chunk",
+                greedy,
+                vertices[i],
+                start.elapsed().as_nanos() as f32 / 1000000.0 / chunks.chunks.len() as f32
+            );
+        }
+
+        assert!(vertices[1] * 16 < vertices[0]);
     }
 }
@@ -0,0 +1,244 @@
+use nalgebra::Vector3;
+use rc_shared::block::face::Face;
+use rc_shared::block::types::VisualBlock;
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::chunk::{LightingColor, LocalBlockPosition};
+use rc_shared::viewable_direction::{AxisAlignedDirection, BLOCK_SIDES};
+use rc_shared::CHUNK_SIZE;
+use crate::utils::mesh::draw_kit::DrawKit;
+
+const LAYER_SIZE: usize = CHUNK_SIZE * CHUNK_SIZE;
+const DIRECTION_SIZE: usize = CHUNK_SIZE * LAYER_SIZE;
+
+/// A face that looks the same as any other with the same block, face and light
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct GreedyFace {
+    block_id: BlockId,
+    face: usize,
+    color: LightingColor,
+}
+
+/// Collects the faces of opaque full blocks, then draws coplanar faces that look the same as one
+/// larger face with its texture repeated across it
+pub struct GreedyMesher {
+    /// Indexed by the face's direction, then its block's position along that direction, then
+    /// across the other two axes in order
+    faces: Vec<Option<GreedyFace>>,
+}
+
+impl GreedyMesher {
+    pub fn new() -> GreedyMesher {
+        GreedyMesher {
+            faces: vec![None; BLOCK_SIDES.len() * DIRECTION_SIZE],
+        }
+    }
+
+    /// Whether a face can be drawn merged with the faces next to it. Anything that isn't a plain
+    /// square on the side of a solid block is drawn on its own.
+    pub fn can_merge(visual_block: &VisualBlock, face: &Face) -> bool {
+        !visual_block.translucent
+            && visual_block.full
+            && face.edge
+            && face.wind_strengths.is_none()
+            && face_edges(face).is_some()
+    }
+
+    pub fn add(
+        &mut self,
+        pos: LocalBlockPosition,
+        block_id: BlockId,
+        face_index: usize,
+        face: &Face,
+        color: LightingColor,
+    ) {
+        let direction = AxisAlignedDirection::from(face.direction) as usize;
+        let (layer, u, v) = to_plane(direction, pos);
+
+        self.faces[index(direction, layer, u, v)] = Some(GreedyFace {
+            block_id,
+            face: face_index,
+            color,
+        });
+    }
+
+    /// Draws each collected face into `kit`, growing faces as far as they can go across their
+    /// plane then down it
+    pub fn draw(mut self, block_states: &BlockStates, kit: &mut DrawKit) {
+        for direction in 0..BLOCK_SIDES.len() {
+            for layer in 0..CHUNK_SIZE {
+                for u in 0..CHUNK_SIZE {
+                    for v in 0..CHUNK_SIZE {
+                        let Some(face) = self.faces[index(direction, layer, u, v)] else {
+                            continue;
+                        };
+
+                        let matches = |faces: &[Option<GreedyFace>], u: usize, v: usize| {
+                            faces[index(direction, layer, u, v)] == Some(face)
+                        };
+
+                        let mut width = 1;
+                        while v + width < CHUNK_SIZE && matches(&self.faces, u, v + width) {
+                            width += 1;
+                        }
+
+                        let mut height = 1;
+                        while u + height < CHUNK_SIZE
+                            && (v..v + width).all(|v| matches(&self.faces, u + height, v))
+                        {
+                            height += 1;
+                        }
+
+                        for u in u..u + height {
+                            for v in v..v + width {
+                                self.faces[index(direction, layer, u, v)] = None;
+                            }
+                        }
+
+                        let min = from_plane(direction, layer, u, v);
+                        let max = from_plane(direction, layer, u + height - 1, v + width - 1);
+                        draw_merged(block_states, face, min, max, kit);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draws a face stretched over every block from `min` to `max`
+fn draw_merged(
+    block_states: &BlockStates,
+    greedy_face: GreedyFace,
+    min: LocalBlockPosition,
+    max: LocalBlockPosition,
+    kit: &mut DrawKit,
+) {
+    let block = block_states.get_block_from_id(greedy_face.block_id);
+    let face = &block.draw().faces[greedy_face.face];
+    let (across, down) = face_edges(face).unwrap();
+
+    // The face is drawn from the block its top left corner is in, so it stretches forwards along both of its edges
+    let mut origin = min;
+    for axis in 0..3 {
+        if across[axis] < 0 || down[axis] < 0 {
+            origin[axis] = max[axis];
+        }
+    }
+
+    let blocks = (max - min).add_scalar(1).cast::<f32>();
+    let across_blocks = across.abs().cast::<f32>().dot(&blocks);
+    let down_blocks = down.abs().cast::<f32>().dot(&blocks);
+
+    let merged = Face {
+        top_right: face.top_left + across.cast::<f32>() * across_blocks,
+        bottom_left: face.top_left + down.cast::<f32>() * down_blocks,
+        ..face.clone()
+    };
+
+    kit.draw_repeated_face(origin.cast::<f32>(), &merged, greedy_face.color, [down_blocks, across_blocks]);
+}
+
+/// The edges of a face from its top left corner to its top right and bottom left corners, if
+/// they're both one block long and along an axis
+fn face_edges(face: &Face) -> Option<(Vector3<i32>, Vector3<i32>)> {
+    let unit = |edge: Vector3<f32>| {
+        let rounded = edge.map(|v| v.round() as i32);
+        let is_unit = rounded.cast::<f32>() == edge && rounded.abs().sum() == 1;
+
+        is_unit.then_some(rounded)
+    };
+
+    Some((unit(face.top_right - face.top_left)?, unit(face.bottom_left - face.top_left)?))
+}
+
+/// The axis a direction faces along, then the two axes across it
+fn axes(direction: usize) -> (usize, usize, usize) {
+    match BLOCK_SIDES[direction].iamax() {
+        0 => (0, 1, 2),
+        1 => (1, 0, 2),
+        _ => (2, 0, 1),
+    }
+}
+
+fn to_plane(direction: usize, pos: LocalBlockPosition) -> (usize, usize, usize) {
+    let (normal, u, v) = axes(direction);
+    (pos[normal], pos[u], pos[v])
+}
+
+fn from_plane(direction: usize, layer: usize, u: usize, v: usize) -> LocalBlockPosition {
+    let (normal, u_axis, v_axis) = axes(direction);
+
+    let mut pos = Vector3::zeros();
+    pos[normal] = layer;
+    pos[u_axis] = u;
+    pos[v_axis] = v;
+    pos
+}
+
+#[inline]
+fn index(direction: usize, layer: usize, u: usize, v: usize) -> usize {
+    direction * DIRECTION_SIZE + layer * LAYER_SIZE + u * CHUNK_SIZE + v
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use rc_shared::chunk::LightingColor;
+    use rc_shared::viewable_direction::ViewableDirectionBitMap;
+    use crate::systems::chunk::builder::greedy::GreedyMesher;
+    use crate::utils::mesh::draw_kit::DrawKit;
+    use rc_shared::block::test_block_states;
+
+    /// The corners of the box around the first face drawn
+    fn bounds(kit: &DrawKit) -> ([f32; 3], [f32; 3]) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for position in &kit.positions[..4] {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+        }
+        (min, max)
+    }
+
+    #[test]
+    fn test_merges_faces_in_a_rectangle() {
+        let states = test_block_states();
+
+        let stone = states.get_by_identifier("mcv3::block::Stone").unwrap().1;
+        let faces = &stone.draw().faces;
+
+        for (direction, expected) in [
+            (ViewableDirectionBitMap::Top, ([0.0, 2.0, 0.0], [3.0, 2.0, 2.0])),
+            (ViewableDirectionBitMap::Front, ([0.0, 0.0, 0.0], [3.0, 2.0, 0.0])),
+        ] {
+            let (i, face) = faces.iter().enumerate().find(|(_, face)| face.direction == direction).unwrap();
+            let mut mesher = GreedyMesher::new();
+
+            // A 3 by 2 rectangle of faces, with a differently lit face that isn't merged
+            for x in 0..3 {
+                for other in 0..2 {
+                    let pos = if direction == ViewableDirectionBitMap::Top {
+                        Vector3::new(x, 1, other)
+                    } else {
+                        Vector3::new(x, other, 0)
+                    };
+                    mesher.add(pos, stone.get_id(), i, face, LightingColor::default());
+                }
+            }
+            mesher.add(Vector3::new(5, 5, 5), stone.get_id(), i, face, LightingColor::full());
+
+            let mut kit = DrawKit::new();
+            mesher.draw(states, &mut kit);
+
+            assert_eq!(kit.positions.len(), 8);
+            assert_eq!(bounds(&kit), expected);
+
+            // The texture repeats once per block in each direction
+            let mut repeats = kit.uv_coordinates[..4].iter().flatten().copied().collect::<Vec<_>>();
+            repeats.sort_by(f32::total_cmp);
+            assert_eq!(repeats.last(), Some(&3.0));
+            assert!(repeats.contains(&2.0));
+        }
+    }
+}
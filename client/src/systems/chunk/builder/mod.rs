@@ -1,10 +1,9 @@
 mod entry;
 mod generate_mesh;
-mod lighting;
+mod greedy;
 pub mod build_context;
 pub mod thread;
 pub mod builder;
-mod skylight;
 
 use crate::systems::chunk::builder::entry::PLAYER_POS;
 use crate::systems::chunk::nearby_cache::NearbyChunkCache;
@@ -12,7 +11,7 @@ use crate::systems::chunk::ChunkSystem;
 use bevy::prelude::*;
 use bevy::render::mesh::MeshVertexAttribute;
 use bevy::render::render_resource::VertexFormat;
-use nalgebra::{Vector2, Vector3};
+use nalgebra::Vector3;
 use rc_shared::block::BlockStates;
 use rc_shared::helpers::from_bevy_vec3;
 use std::sync::atomic::Ordering;
@@ -22,7 +21,6 @@ use crate::systems::chunk::builder::builder::MeshBuilderContext;
 use crate::systems::chunk::builder::thread::executor::ChunkBuilderJob;
 use crate::systems::chunk::flags::ChunkFlagsBitMap;
 use crate::systems::chunk::builder::thread::ChunkBuilderSchedulerTrait;
-use crate::systems::chunk::nearby_column_cache::NearbyChunkColumnCache;
 
 pub const ATTRIBUTE_LIGHTING_COLOR: MeshVertexAttribute =
     MeshVertexAttribute::new("Lighting", 988540917, VertexFormat::Float32x4);
@@ -30,6 +28,9 @@ pub const ATTRIBUTE_SKYLIGHT_STRENGTH: MeshVertexAttribute =
     MeshVertexAttribute::new("Skylight", 988540919, VertexFormat::Uint8x4);
 pub const ATTRIBUTE_WIND_STRENGTH: MeshVertexAttribute =
     MeshVertexAttribute::new("WindStrength", 988520913, VertexFormat::Float32);
+/// The part of the texture atlas a face's texture is in, as its minimum u and v then its width and height
+pub const ATTRIBUTE_TEXTURE_TILE: MeshVertexAttribute =
+    MeshVertexAttribute::new("TextureTile", 988540923, VertexFormat::Float32x4);
 
 #[derive(Event)]
 pub struct RerenderChunkRequest {
@@ -86,9 +87,7 @@ pub fn mesh_scheduler(
             let chunk = chunk.clone();
 
             let cache = NearbyChunkCache::from_service(&chunks, chunk.position);
-            let column_cache = NearbyChunkColumnCache::from_service(&chunks, Vector2::new(chunk.position.x, chunk.position.z));
-
-            let context = ChunkBuildContext::new(&block_states, &cache, &column_cache);
+            let context = ChunkBuildContext::new(&block_states, &cache);
 
             builder_data.scheduler.schedule(ChunkBuilderJob {
                 chunk,
@@ -119,8 +118,8 @@ pub fn mesh_updater(
                 .apply_mesh(meshes.get_mut(&chunk.handles.as_ref().unwrap().opaque_mesh).unwrap());
             update.mesh.translucent
                 .apply_mesh(meshes.get_mut(&chunk.handles.as_ref().unwrap().translucent_mesh).unwrap());
+            chunk.connectivity = update.mesh.connectivity;
 
-            chunk.light_levels = update.lighting.data;
             chunk.flags.add_flag(ChunkFlagsBitMap::Ready);
         }
 
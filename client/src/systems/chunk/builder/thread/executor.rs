@@ -3,7 +3,6 @@ use serde::{Deserialize, Serialize};
 use rc_shared::block::BlockStates;
 use crate::systems::chunk::builder::build_context::ChunkBuildContext;
 use crate::systems::chunk::builder::generate_mesh::UpdateChunkMesh;
-use crate::systems::chunk::builder::lighting::LightingUpdateData;
 use crate::systems::chunk::data::ChunkData;
 
 pub struct ChunkBuilderExecutor {
@@ -26,26 +25,16 @@ impl ChunkBuilderExecutor {
         };
 
         let ChunkBuilderJob {
-            mut chunk,
-            mut context
+            chunk,
+            context
         } = job;
 
-        let mut lighting = chunk.build_lighting(&mut context);
-
-        chunk.build_skylighting(
-            &mut context,
-            &mut lighting.data
-        );
-
-        chunk.light_levels = lighting.data;
-
         // Generate mesh & gpu buffers
         let mesh = chunk.build_mesh(&self.block_states, false, &context);
 
         Some(ChunkBuilderUpdate {
             position: chunk.position,
             mesh,
-            lighting
         })
     }
 }
@@ -60,5 +49,4 @@ pub struct ChunkBuilderJob {
 pub struct ChunkBuilderUpdate {
     pub position: Vector3<i32>,
     pub mesh: UpdateChunkMesh,
-    pub lighting: LightingUpdateData
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::ReceivePacket;
+use rc_shared::CHUNK_SIZE;
+use rc_shared::light::LightUpdate;
 use crate::systems::chunk::ChunkSystem;
 
 pub fn receive_column_updates(
@@ -12,8 +14,26 @@ pub fn receive_column_updates(
             continue
         };
 
-        data.chunk_columns.insert(update.position, update.data.clone());
+        let old = data.chunk_columns.insert(update.position, update.data.clone()).unwrap_or_default();
 
-        // TODO: Rebuild chunks?
+        // Relight the columns of blocks whose sky height moved
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let (before, after) = (old.skylight_level[x][z], update.data.skylight_level[x][z]);
+                if before == after {
+                    continue;
+                }
+
+                let Some(y) = before.into_iter().chain(after).min() else {
+                    continue;
+                };
+
+                data.light_updates.push(LightUpdate::Sky {
+                    x: update.position.x * CHUNK_SIZE as i32 + x as i32,
+                    z: update.position.y * CHUNK_SIZE as i32 + z as i32,
+                    y,
+                });
+            }
+        }
     }
-}
\ No newline at end of file
+}
@@ -2,10 +2,12 @@ use bevy::ecs::component::Component;
 use bevy::prelude::{Entity, Handle, Mesh};
 use nalgebra::Vector3;
 use serde::{Deserialize, Serialize};
-use rc_shared::chunk::{ChunkDataStorage, LightingColor, RawLightingData};
+use rc_shared::chunk::ChunkDataStorage;
+use rc_shared::light::ChunkLight;
 use rc_shared::viewable_direction::ViewableDirection;
 use rc_shared::CHUNK_SIZE;
 use crate::systems::chunk::flags::ChunkFlags;
+use crate::systems::chunk::occlusion::ChunkConnectivity;
 
 pub mod viewable;
 
@@ -18,8 +20,11 @@ pub struct ChunkData {
     // TODO: Investigate not storing this
     pub viewable_map: Option<[[[ViewableDirection; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
 
-    // Stores the lighting intensity and color map
-    pub light_levels: RawLightingData,
+    // Stores the lighting intensity and color map, kept up to date by the light engine
+    pub light: ChunkLight,
+
+    // Which of the chunk's sides can be seen through it, worked out alongside its mesh
+    pub connectivity: ChunkConnectivity,
 
     // Always set except for during tests
     #[serde(skip)]
@@ -42,7 +47,8 @@ impl ChunkData {
             world: data,
             viewable_map: None,
             position,
-            light_levels: [[[LightingColor::full(); CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE],
+            light: ChunkLight::default(),
+            connectivity: ChunkConnectivity::default(),
             handles: Some(ChunkHandleData {
                 entity,
                 opaque_entity,
@@ -62,7 +68,8 @@ impl ChunkData {
             world,
             viewable_map: None,
             position,
-            light_levels: [[[LightingColor::full(); CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE],
+            light: ChunkLight::default(),
+            connectivity: ChunkConnectivity::default(),
             handles: None,
             flags: Default::default(),
         }
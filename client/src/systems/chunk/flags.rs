@@ -11,7 +11,9 @@ pub enum ChunkFlagsBitMap {
     /// Chunk contains no blocks
     Empty = 0b00000010,
     /// Chunk has had mesh built if it needed it
-    Ready = 0b00000100
+    Ready = 0b00000100,
+    /// Chunk can't be seen from the camera, so is hidden
+    Occluded = 0b00001000
 }
 
 impl Debug for ChunkFlags {
@@ -0,0 +1,110 @@
+use bevy::log::debug;
+use bevy::prelude::{EventWriter, Res, ResMut};
+use nalgebra::{Vector2, Vector3};
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::helpers::global_to_local_position;
+use rc_shared::light::{update_light, LightLevel, LightWorld};
+use std::mem;
+use web_time::Instant;
+use crate::systems::chunk::builder::{RerenderChunkFlagContext, RerenderChunkRequest};
+use crate::systems::chunk::ChunkSystem;
+
+impl LightWorld for ChunkSystem {
+    fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<BlockId> {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        self.chunks.get(&chunk_pos).map(|chunk| chunk.world.get(local_pos))
+    }
+
+    fn sky_height(&self, x: i32, z: i32) -> Option<i32> {
+        let (chunk_pos, local_pos) = global_to_local_position(Vector3::new(x, 0, z));
+
+        self.chunk_columns
+            .get(&Vector2::new(chunk_pos.x, chunk_pos.z))?
+            .skylight_level[local_pos.x][local_pos.z]
+    }
+
+    fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel> {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        self.chunks.get(&chunk_pos).map(|chunk| chunk.light.get(local_pos))
+    }
+
+    fn set_light(&mut self, pos: GlobalBlockPosition, light: LightLevel) {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+            chunk.light.set(local_pos, light);
+        }
+    }
+}
+
+/// Relights the world around chunks and blocks that changed, rebuilding the chunks whose light changed
+pub fn update_chunk_light(
+    mut chunks: ResMut<ChunkSystem>,
+    block_states: Res<BlockStates>,
+    mut rerender_chunks: EventWriter<RerenderChunkRequest>,
+) {
+    if chunks.light_updates.is_empty() {
+        return;
+    }
+
+    let start = Instant::now();
+    let updates = mem::take(&mut chunks.light_updates);
+    let changed = update_light(&mut *chunks, &block_states, &updates);
+
+    debug!(
+        "Took {}ns to relight {} changes, affecting {} chunks",
+        start.elapsed().as_nanos(),
+        updates.len(),
+        changed.len()
+    );
+
+    // Chunks draw the light of the blocks just outside them too
+    for chunk in changed {
+        rerender_chunks.send(RerenderChunkRequest {
+            chunk,
+            context: RerenderChunkFlagContext::Adjacent,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use web_time::Instant;
+    use rc_shared::light::{update_light, LightUpdate};
+    use rc_shared::block::test_block_states;
+    use crate::systems::chunk::data::ChunkData;
+    use crate::systems::chunk::static_world_data::StaticWorldData;
+    use crate::systems::chunk::ChunkSystem;
+
+    /// Needs a captured world in `chunk_lighting_benchmark.mpk`, so is left out of normal test runs.
+    /// Run with `cargo test benchmark_chunk_lighting -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn benchmark_chunk_lighting() {
+
+        let file_data = fs::read("chunk_lighting_benchmark.mpk").unwrap();
+        let world_data = rmp_serde::from_slice::<StaticWorldData>(file_data.as_slice()).unwrap();
+
+        let states = test_block_states();
+
+        for _ in 0..10 {
+            let mut chunks = ChunkSystem::new();
+
+            for chunk_data in &world_data.data {
+                let chunk = ChunkData::new_handleless(chunk_data.data.clone(), chunk_data.position);
+                chunks.chunks.insert(chunk.position, chunk);
+            }
+
+            let updates = chunks.chunks.keys().map(|pos| LightUpdate::Chunk(*pos)).collect::<Vec<_>>();
+
+            let start = Instant::now();
+            update_light(&mut chunks, states, &updates);
+
+            println!("Took {}ms per chunk", start.elapsed().as_nanos() as f32 / 1000000.0 / updates.len() as f32);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::primitives::Aabb;
+use bevy::render::render_asset::RenderAssetUsages;
+use nalgebra::{Vector2, Vector3};
+use rc_shared::block::face::Face;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::LightingColor;
+use rc_shared::chunk_summary::ChunkSummary;
+use rc_shared::light::{LightLevel, MAX_SKYLIGHT};
+use rc_shared::viewable_direction::{AxisAlignedDirection, ViewableDirectionBitMap, BLOCK_SIDES};
+use rc_shared::CHUNK_SIZE;
+use crate::systems::asset::AssetService;
+use crate::systems::chunk::flags::ChunkFlagsBitMap;
+use crate::systems::chunk::ChunkSystem;
+use crate::utils::mesh::draw_kit::DrawKit;
+
+/// Summaries are small, but a whole ring of them can arrive at once when we cross a chunk border
+const LOD_MESHES_BUILT_PER_FRAME: usize = 32;
+
+/// A chunk column too far away to load, drawn coarsely from a summary of its surface instead
+pub struct LodColumn {
+    /// The finest scale asked for so far
+    pub requested_scale: u8,
+    pub summary: Option<ChunkSummary>,
+    pub entity: Option<Entity>,
+    /// Whether its mesh needs building again, as its summary or a neighbour's has arrived
+    pub dirty: bool,
+}
+
+impl LodColumn {
+    pub fn new(requested_scale: u8) -> LodColumn {
+        LodColumn {
+            requested_scale,
+            summary: None,
+            entity: None,
+            dirty: false,
+        }
+    }
+}
+
+impl ChunkSystem {
+    /// Stores a summary the server sent, unless we have stopped wanting it or already have a finer one
+    pub fn receive_summary(&mut self, position: Vector2<i32>, summary: ChunkSummary) {
+        let Some(column) = self.lod_columns.get_mut(&position) else {
+            return;
+        };
+
+        if column.summary.as_ref().is_some_and(|current| current.scale < summary.scale) {
+            return;
+        }
+
+        column.summary = Some(summary);
+
+        // Neighbours' sides reach down to this column's surface
+        for offset in [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(-1, 0), Vector2::new(0, 1), Vector2::new(0, -1)] {
+            if let Some(column) = self.lod_columns.get_mut(&(position + offset)) {
+                column.dirty = true;
+            }
+        }
+    }
+
+    pub fn unload_lod_column(&mut self, position: Vector2<i32>, commands: &mut Commands) {
+        if let Some(entity) = self.lod_columns.remove(&position).and_then(|column| column.entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    /// The height of the surface at a block column, relative to the chunk column at `position`
+    fn summary_height(&self, position: Vector2<i32>, x: i32, z: i32) -> Option<i32> {
+        let size = CHUNK_SIZE as i32;
+        let column = position + Vector2::new(x.div_euclid(size), z.div_euclid(size));
+        let summary = self.lod_columns.get(&column)?.summary.as_ref()?;
+
+        Some(summary.height_at(x.rem_euclid(size) as usize, z.rem_euclid(size) as usize))
+    }
+
+    /// Whether the chunks holding a column's surface are all loaded and drawn, so it doesn't need drawing coarsely
+    fn is_covered(&self, position: Vector2<i32>, summary: &ChunkSummary) -> bool {
+        let (min, max) = summary.height_range();
+        let size = CHUNK_SIZE as i32;
+
+        (min.div_euclid(size)..=max.div_euclid(size)).all(|y| {
+            self.chunks
+                .get(&Vector3::new(position.x, y, position.y))
+                .is_some_and(|chunk| chunk.flags.has_flag(ChunkFlagsBitMap::Ready))
+        })
+    }
+}
+
+/// Draws the surface of a chunk column from its summary. Each cell's top block is stretched across the cell,
+/// with its sides reaching down to the cells around it.
+pub fn draw_lod_column(chunks: &ChunkSystem, position: Vector2<i32>, block_states: &BlockStates) -> DrawKit {
+    let mut kit = DrawKit::new();
+
+    let Some(summary) = chunks.lod_columns.get(&position).and_then(|column| column.summary.as_ref()) else {
+        return kit;
+    };
+
+    let scale = summary.scale as i32;
+    let color = LightLevel([0, 0, 0, MAX_SKYLIGHT]).color();
+
+    for cell_x in 0..summary.cells() {
+        for cell_z in 0..summary.cells() {
+            let (height, block_id) = summary.get(cell_x, cell_z);
+            let (x, z) = (cell_x as i32 * scale, cell_z as i32 * scale);
+
+            let block = block_states.get_block_from_id(block_id);
+            let visual_block = block.draw();
+
+            for face in &visual_block.faces {
+                let direction = BLOCK_SIDES[AxisAlignedDirection::from(face.direction) as usize];
+
+                if face.direction == ViewableDirectionBitMap::Top {
+                    let origin = Vector3::new(x, height, z).cast::<f32>();
+                    draw_stretched_face(&mut kit, origin, face, Vector3::new(scale, 1, scale), color);
+                    continue;
+                }
+
+                if direction.y != 0 {
+                    continue;
+                }
+
+                // The lowest neighbouring block column along this side, or a chunk down where there's no neighbour yet
+                let neighbour_height = (0..scale)
+                    .map(|along| {
+                        let neighbour_x = if direction.x < 0 { x - 1 } else if direction.x > 0 { x + scale } else { x + along };
+                        let neighbour_z = if direction.z < 0 { z - 1 } else if direction.z > 0 { z + scale } else { z + along };
+
+                        chunks
+                            .summary_height(position, neighbour_x, neighbour_z)
+                            .unwrap_or(height - CHUNK_SIZE as i32)
+                    })
+                    .min()
+                    .unwrap();
+
+                let depth = height - neighbour_height;
+                if depth <= 0 {
+                    continue;
+                }
+
+                let origin = Vector3::new(x, height + 1 - depth, z).cast::<f32>();
+                draw_stretched_face(&mut kit, origin, face, Vector3::new(scale, depth, scale), color);
+            }
+        }
+    }
+
+    kit
+}
+
+/// Draws a face as if its block were stretched to `size`, repeating its texture rather than stretching it
+fn draw_stretched_face(kit: &mut DrawKit, origin: Vector3<f32>, face: &Face, size: Vector3<i32>, color: LightingColor) {
+    let size = size.cast::<f32>();
+
+    let stretched = Face {
+        top_left: face.top_left.component_mul(&size),
+        top_right: face.top_right.component_mul(&size),
+        bottom_left: face.bottom_left.component_mul(&size),
+        ..face.clone()
+    };
+
+    let across = (stretched.top_right - stretched.top_left).norm() / (face.top_right - face.top_left).norm();
+    let down = (stretched.bottom_left - stretched.top_left).norm() / (face.bottom_left - face.top_left).norm();
+
+    kit.draw_repeated_face(origin, &stretched, color, [down, across]);
+}
+
+/// Builds the meshes of chunk columns whose summaries have changed
+pub fn build_lod_meshes(
+    mut system: ResMut<ChunkSystem>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_service: Res<AssetService>,
+    block_states: Res<BlockStates>,
+) {
+    let dirty = system
+        .lod_columns
+        .iter()
+        .filter(|(_, column)| column.dirty && column.summary.is_some())
+        .map(|(position, _)| *position)
+        .take(LOD_MESHES_BUILT_PER_FRAME)
+        .collect::<Vec<Vector2<i32>>>();
+
+    for position in dirty {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all());
+        draw_lod_column(&system, position, &block_states).apply_mesh(&mut mesh);
+        let mesh = meshes.add(mesh);
+
+        let column = system.lod_columns.get_mut(&position).unwrap();
+        column.dirty = false;
+
+        match column.entity {
+            // Its bounds are calculated again for the new mesh
+            Some(entity) => {
+                commands.entity(entity).insert(mesh).remove::<Aabb>();
+            }
+            None => {
+                column.entity = Some(
+                    commands
+                        .spawn(asset_service.opaque_texture_atlas_material.clone())
+                        .insert(Transform::from_translation(Vec3::new(
+                            (position.x * CHUNK_SIZE as i32) as f32,
+                            0.0,
+                            (position.y * CHUNK_SIZE as i32) as f32,
+                        )))
+                        .insert(GlobalTransform::default())
+                        .insert(VisibilityBundle::default())
+                        .insert(mesh)
+                        .id(),
+                );
+            }
+        }
+    }
+}
+
+/// Hides the coarse surface of chunk columns once their chunks have been drawn, and shows it again when they unload
+pub fn update_lod_visibility(system: Res<ChunkSystem>, mut visibilities: Query<&mut Visibility>) {
+    for (position, column) in &system.lod_columns {
+        let (Some(entity), Some(summary)) = (column.entity, &column.summary) else {
+            continue;
+        };
+
+        let Ok(mut visibility) = visibilities.get_mut(entity) else {
+            continue;
+        };
+
+        let expected = if system.is_covered(*position, summary) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        // Only write when it changes, so Bevy doesn't see every column as changed each frame
+        if *visibility != expected {
+            *visibility = expected;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use rc_shared::chunk_summary::ChunkSummary;
+    use crate::systems::chunk::lod::{draw_lod_column, LodColumn};
+    use crate::systems::chunk::ChunkSystem;
+    use rc_shared::block::test_block_states;
+
+    /// The number of faces drawn, and the lowest and highest points drawn
+    fn draw(chunks: &ChunkSystem, position: Vector2<i32>) -> (usize, f32, f32) {
+        let kit = draw_lod_column(chunks, position, test_block_states());
+        let heights = kit.positions.iter().map(|position| position[1]);
+
+        (kit.positions.len() / 4, heights.clone().fold(f32::MAX, f32::min), heights.fold(f32::MIN, f32::max))
+    }
+
+    fn column(summary: ChunkSummary) -> LodColumn {
+        LodColumn {
+            summary: Some(summary),
+            ..LodColumn::new(2)
+        }
+    }
+
+    #[test]
+    fn test_draws_cells_and_their_sides() {
+        let states = test_block_states();
+        let stone = states.get_by_identifier("mcv3::block::Stone").unwrap().1.get_id();
+
+        // Flat ground at y = 4 with a pillar 3 blocks higher in one cell
+        let summary = ChunkSummary::from_surface(2, |x, z| if (x, z) == (6, 6) { (7, stone) } else { (4, stone) });
+
+        let mut chunks = ChunkSystem::new();
+        chunks.lod_columns.insert(Vector2::new(0, 0), column(summary.clone()));
+
+        // A top for each of the 8 by 8 cells, the pillar's sides, and the sides of the column's edge cells
+        // dropping a chunk down as there's nothing next to them yet
+        assert_eq!(draw(&chunks, Vector2::new(0, 0)), (64 + 4 + 32, 4.0 - 16.0 + 1.0, 8.0));
+
+        // With neighbours on every side at the same height, only the pillar has sides
+        for offset in [Vector2::new(1, 0), Vector2::new(-1, 0), Vector2::new(0, 1), Vector2::new(0, -1)] {
+            chunks.lod_columns.insert(offset, column(summary.clone()));
+        }
+        assert_eq!(draw(&chunks, Vector2::new(0, 0)), (64 + 4, 5.0, 8.0));
+    }
+}
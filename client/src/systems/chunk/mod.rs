@@ -1,22 +1,28 @@
 use crate::systems::asset::AssetService;
-use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_WIND_STRENGTH, ChunkRebuiltEvent, mesh_scheduler, mesh_updater, RerenderChunkRequest};
+use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_TEXTURE_TILE, ATTRIBUTE_WIND_STRENGTH, ChunkRebuiltEvent, mesh_scheduler, mesh_updater, RerenderChunkRequest};
 use crate::systems::chunk::data::ChunkData;
-use crate::systems::chunk::request::request_chunks;
+use crate::systems::chunk::request::{request_chunk_summaries, request_chunks, unload_chunks};
 use bevy::prelude::*;
 use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
 use bevy::render::primitives::Aabb;
 use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::VisibilitySystems;
 use fnv::{FnvBuildHasher, FnvHashMap};
 use nalgebra::{Vector2, Vector3};
-use rc_shared::chunk::{ChunkDataStorage, ChunkSystemTrait};
+use rc_shared::block::entity::BlockEntityData;
+use rc_shared::chunk::{ChunkDataStorage, ChunkSystemTrait, GlobalBlockPosition};
 use rc_shared::CHUNK_SIZE;
 use std::collections::HashMap;
 use rc_shared::chunk_column::ChunkColumnData;
+use rc_shared::light::LightUpdate;
 use crate::state::AppState;
 use crate::systems::asset::parsing::message_pack::MessagePackAssetLoader;
 use crate::systems::chunk::builder::builder::setup_mesh_builder_context;
 use crate::systems::chunk::column::receive_column_updates;
 use crate::systems::chunk::flags::ChunkFlagsBitMap;
+use crate::systems::chunk::light::update_chunk_light;
+use crate::systems::chunk::lod::{build_lod_meshes, update_lod_visibility, LodColumn};
+use crate::systems::chunk::occlusion::cull_occluded_chunks;
 use crate::systems::chunk::static_world_data::{save_surroundings_system, StaticWorldData};
 
 pub mod builder;
@@ -30,7 +36,9 @@ pub mod flags;
 mod edge;
 mod condensed_spacial_data;
 mod column;
-mod nearby_column_cache;
+mod light;
+mod lod;
+mod occlusion;
 
 pub struct ChunkPlugin;
 
@@ -38,12 +46,20 @@ impl Plugin for ChunkPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ChunkSystem::new())
             .add_systems(OnExit(AppState::Loading), setup_mesh_builder_context)
-            .add_systems(Update, (mesh_scheduler, mesh_updater).run_if(in_state(AppState::MainMenu)))
-            .add_systems(Update, (mesh_scheduler, mesh_updater).run_if(in_state(AppState::Connecting)))
-            .add_systems(Update, (mesh_scheduler, mesh_updater).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (update_chunk_light, (mesh_scheduler, mesh_updater)).chain().run_if(in_state(AppState::MainMenu)))
+            .add_systems(Update, (update_chunk_light, (mesh_scheduler, mesh_updater)).chain().run_if(in_state(AppState::Connecting)))
+            .add_systems(Update, (update_chunk_light, (mesh_scheduler, mesh_updater)).chain().run_if(in_state(AppState::InGame)))
             .add_event::<RerenderChunkRequest>()
             .add_event::<ChunkRebuiltEvent>()
-            .add_systems(Update, request_chunks)
+            .add_systems(Update, (request_chunks, unload_chunks, request_chunk_summaries))
+            .add_systems(Update, (build_lod_meshes, update_lod_visibility).chain())
+            // Runs once the camera's frustum is known for this frame, before visibility is passed down to meshes
+            .add_systems(
+                PostUpdate,
+                cull_occluded_chunks
+                    .after(VisibilitySystems::UpdateFrusta)
+                    .before(VisibilitySystems::VisibilityPropagate),
+            )
             // Static world data
             .init_asset::<StaticWorldData>()
             .init_asset_loader::<MessagePackAssetLoader<StaticWorldData>>()
@@ -58,7 +74,17 @@ pub struct ChunkSystem {
     pub chunk_columns: HashMap<Vector2<i32>, ChunkColumnData, FnvBuildHasher>,
 
     /// A list of all chunks that have rerender requests outstanding
-    pub requested_chunks: Vec<Vector3<i32>>
+    pub requested_chunks: Vec<Vector3<i32>>,
+
+    /// The block entities of loaded chunks. These are sent separately to chunks, so are kept
+    /// when a chunk is replaced.
+    pub block_entities: HashMap<GlobalBlockPosition, BlockEntityData, FnvBuildHasher>,
+
+    /// Changes to the world that it hasn't been relit for yet
+    pub light_updates: Vec<LightUpdate>,
+
+    /// Chunk columns drawn from summaries, as they are too far away to load
+    pub lod_columns: HashMap<Vector2<i32>, LodColumn, FnvBuildHasher>,
 }
 
 impl ChunkSystem {
@@ -66,7 +92,10 @@ impl ChunkSystem {
         ChunkSystem {
             chunks: FnvHashMap::default(),
             chunk_columns: FnvHashMap::default(),
-            requested_chunks: vec![]
+            requested_chunks: vec![],
+            block_entities: FnvHashMap::default(),
+            light_updates: vec![],
+            lod_columns: FnvHashMap::default(),
         }
     }
 
@@ -86,6 +115,7 @@ impl ChunkSystem {
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![] as Vec<[f32; 2]>);
         mesh.insert_attribute(ATTRIBUTE_LIGHTING_COLOR, VertexAttributeValues::Float32x4(vec![]));
         mesh.insert_attribute(ATTRIBUTE_SKYLIGHT_STRENGTH, VertexAttributeValues::Uint8x4(vec![]));
+        mesh.insert_attribute(ATTRIBUTE_TEXTURE_TILE, VertexAttributeValues::Float32x4(vec![]));
 
         let opaque = meshes.add(mesh.clone());
 
@@ -142,6 +172,7 @@ impl ChunkSystem {
         );
 
         self.chunks.insert(position, chunk);
+        self.light_updates.push(LightUpdate::Chunk(position));
 
         // Recompute onedge for all surrounding chunks
         for x in (position.x - 1)..=(position.x + 1) {
@@ -154,6 +185,15 @@ impl ChunkSystem {
     }
 
     pub fn unload_all_chunks(&mut self, commands: &mut Commands) {
+        self.block_entities.clear();
+        self.light_updates.clear();
+
+        for (_, column) in self.lod_columns.drain() {
+            if let Some(entity) = column.entity {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
         for (_, chunk) in self.chunks.drain() {
             if let Some(handles) = chunk.handles {
                 commands.entity(handles.entity).despawn_recursive();
@@ -0,0 +1,297 @@
+use std::collections::VecDeque;
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy::render::primitives::{Aabb, Frustum};
+use fnv::{FnvHashMap, FnvHashSet};
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::chunk::{ChunkDataStorage, ChunkPosition};
+use rc_shared::helpers::{from_bevy_vec3, global_f32_to_local_position};
+use rc_shared::viewable_direction::BLOCK_SIDES;
+use rc_shared::CHUNK_SIZE;
+use crate::systems::camera::MainCamera;
+use crate::systems::chunk::flags::ChunkFlagsBitMap;
+use crate::systems::chunk::ChunkSystem;
+
+/// Which sides of a chunk can be seen from which others, through the blocks that don't fill their space.
+/// Sides are numbered in the order of `BLOCK_SIDES`, and bit `from * 6 + to` is set when `from` can see `to`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkConnectivity(u64);
+
+impl Default for ChunkConnectivity {
+    /// Chunks that haven't been looked through yet could be empty, so must not hide anything behind them
+    fn default() -> Self {
+        ChunkConnectivity::ALL
+    }
+}
+
+impl ChunkConnectivity {
+    pub const ALL: ChunkConnectivity = ChunkConnectivity((1 << (6 * 6)) - 1);
+    pub const NONE: ChunkConnectivity = ChunkConnectivity(0);
+
+    /// Flood fills each pocket of see-through blocks, connecting every side the pocket touches
+    pub fn from_blocks(world: &ChunkDataStorage, block_states: &BlockStates) -> ChunkConnectivity {
+        if *world == ChunkDataStorage::Empty {
+            return ChunkConnectivity::ALL;
+        }
+
+        let mut see_through = FnvHashMap::<BlockId, bool>::default();
+        let mut is_see_through = |pos: Vector3<usize>| {
+            *see_through.entry(world.get(pos)).or_insert_with_key(|id| {
+                let block = block_states.get_block_from_id(*id);
+                let visual_block = block.draw();
+                visual_block.translucent || !visual_block.full
+            })
+        };
+
+        let index = |pos: Vector3<usize>| (pos.x * CHUNK_SIZE + pos.y) * CHUNK_SIZE + pos.z;
+
+        let mut connectivity = ChunkConnectivity::NONE;
+        let mut visited = vec![false; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE];
+        let mut stack = Vec::new();
+
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    let start = Vector3::new(x, y, z);
+                    if visited[index(start)] || !is_see_through(start) {
+                        continue;
+                    }
+
+                    visited[index(start)] = true;
+                    stack.push(start);
+
+                    // The sides the pocket reaches, as a bit for each
+                    let mut sides = 0u8;
+
+                    while let Some(pos) = stack.pop() {
+                        for (side, direction) in BLOCK_SIDES.iter().enumerate() {
+                            let next = pos.cast::<i32>() + direction;
+
+                            if next.iter().any(|v| *v < 0 || *v >= CHUNK_SIZE as i32) {
+                                sides |= 1 << side;
+                                continue;
+                            }
+
+                            let next = next.map(|v| v as usize);
+                            if !visited[index(next)] && is_see_through(next) {
+                                visited[index(next)] = true;
+                                stack.push(next);
+                            }
+                        }
+                    }
+
+                    for from in 0..BLOCK_SIDES.len() {
+                        for to in 0..BLOCK_SIDES.len() {
+                            if sides & (1 << from) != 0 && sides & (1 << to) != 0 {
+                                connectivity.0 |= 1 << (from * 6 + to);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        connectivity
+    }
+
+    pub fn connects(&self, from: usize, to: usize) -> bool {
+        self.0 & (1 << (from * 6 + to)) != 0
+    }
+}
+
+/// The side facing the other way, as `BLOCK_SIDES` pairs each side with its opposite
+fn opposite(side: usize) -> usize {
+    side ^ 1
+}
+
+/// Finds the chunks that could be seen from the camera's chunk by walking outwards through the sides that can
+/// see each other. Chunks are only walked into if they are loaded and `in_view`, and never back towards the
+/// camera, so the walk stays in front of it.
+pub fn visible_chunks(
+    chunks: &ChunkSystem,
+    camera_chunk: ChunkPosition,
+    in_view: impl Fn(ChunkPosition) -> bool,
+) -> FnvHashSet<ChunkPosition> {
+    let mut visible = FnvHashSet::default();
+    visible.insert(camera_chunk);
+
+    // Each chunk with the side it was entered from, and every direction walked to reach it
+    let mut queue = VecDeque::new();
+
+    // The camera could be anywhere in its chunk, so can look out of every side
+    for (side, direction) in BLOCK_SIDES.iter().enumerate() {
+        let next = camera_chunk + direction;
+
+        if chunks.chunks.contains_key(&next) && in_view(next) && visible.insert(next) {
+            queue.push_back((next, opposite(side), 1u8 << side));
+        }
+    }
+
+    while let Some((position, entered, walked)) = queue.pop_front() {
+        let connectivity = chunks.chunks[&position].connectivity;
+
+        for (side, direction) in BLOCK_SIDES.iter().enumerate() {
+            // Turning back towards the camera can't reveal anything it can't already see
+            if walked & (1 << opposite(side)) != 0 || !connectivity.connects(entered, side) {
+                continue;
+            }
+
+            let next = position + direction;
+
+            if chunks.chunks.contains_key(&next) && in_view(next) && visible.insert(next) {
+                queue.push_back((next, opposite(side), walked | (1 << side)));
+            }
+        }
+    }
+
+    visible
+}
+
+/// Hides chunks the camera can't see into, such as those buried underground or behind hills
+pub fn cull_occluded_chunks(
+    mut system: ResMut<ChunkSystem>,
+    camera: Query<(&GlobalTransform, &Frustum), With<MainCamera>>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    let Ok((transform, frustum)) = camera.get_single() else {
+        return;
+    };
+
+    let (camera_chunk, _) = global_f32_to_local_position(from_bevy_vec3(transform.translation()));
+
+    // Without the camera's chunk there's nothing to start looking from, so show everything
+    let visible = system.chunks.contains_key(&camera_chunk).then(|| {
+        visible_chunks(&system, camera_chunk, |position| {
+            let min = Vec3::new(position.x as f32, position.y as f32, position.z as f32) * CHUNK_SIZE as f32;
+            let aabb = Aabb::from_min_max(min, min + Vec3::splat(CHUNK_SIZE as f32));
+
+            frustum.intersects_obb(&aabb, &Affine3A::IDENTITY, true, true)
+        })
+    });
+
+    for chunk in system.chunks.values_mut() {
+        let Some(handles) = &chunk.handles else {
+            continue;
+        };
+
+        let occluded = visible.as_ref().is_some_and(|visible| !visible.contains(&chunk.position));
+        if chunk.flags.has_flag(ChunkFlagsBitMap::Occluded) == occluded {
+            continue;
+        }
+
+        let Ok(mut visibility) = visibilities.get_mut(handles.entity) else {
+            continue;
+        };
+
+        if occluded {
+            chunk.flags.add_flag(ChunkFlagsBitMap::Occluded);
+            *visibility = Visibility::Hidden;
+        } else {
+            chunk.flags.remove_flag(ChunkFlagsBitMap::Occluded);
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use rc_shared::chunk::ChunkDataStorage;
+    use rc_shared::CHUNK_SIZE;
+    use crate::systems::chunk::data::ChunkData;
+    use crate::systems::chunk::occlusion::{visible_chunks, ChunkConnectivity};
+    use crate::systems::chunk::ChunkSystem;
+    use rc_shared::block::test_block_states;
+
+    /// Up, down, -x, +x, -z, +z
+    const UP: usize = 0;
+    const DOWN: usize = 1;
+    const WEST: usize = 2;
+    const EAST: usize = 3;
+
+    fn stone_chunk(hollow: impl Fn(usize, usize, usize) -> bool) -> ChunkDataStorage {
+        let states = test_block_states();
+        let stone = states.get_by_identifier("mcv3::block::Stone").unwrap().1.get_id();
+
+        let mut data = Box::new([[[stone; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]);
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    if hollow(x, y, z) {
+                        data[x][y][z] = 0;
+                    }
+                }
+            }
+        }
+
+        ChunkDataStorage::Data(data)
+    }
+
+    #[test]
+    fn test_connectivity_follows_tunnels() {
+        let states = test_block_states();
+
+        let solid = ChunkConnectivity::from_blocks(&stone_chunk(|_, _, _| false), states);
+        assert_eq!(solid, ChunkConnectivity::NONE);
+
+        assert_eq!(ChunkConnectivity::from_blocks(&ChunkDataStorage::Empty, states), ChunkConnectivity::ALL);
+
+        // A tunnel along x, and a separate shaft down from the top
+        let tunnels = ChunkConnectivity::from_blocks(
+            &stone_chunk(|x, y, z| (y == 4 && z == 4) || (x == 10 && z == 10 && y > 8)),
+            states,
+        );
+
+        assert!(tunnels.connects(WEST, EAST));
+        assert!(tunnels.connects(EAST, WEST));
+        assert!(!tunnels.connects(WEST, UP));
+        assert!(!tunnels.connects(UP, DOWN));
+        // The shaft only touches one side, so leads nowhere
+        assert!(!tunnels.connects(UP, EAST));
+    }
+
+    #[test]
+    fn test_buried_chunks_are_hidden() {
+        let states = test_block_states();
+        let mut chunks = ChunkSystem::new();
+
+        // Air above y = 0 and solid stone below, with a shaft down through the ground under the camera
+        for x in -3..=3 {
+            for y in -3..=1 {
+                for z in -3..=3 {
+                    let position = Vector3::new(x, y, z);
+                    let world = if y >= 0 {
+                        ChunkDataStorage::Empty
+                    } else if position == Vector3::new(0, -1, 0) {
+                        stone_chunk(|x, _, z| x == 8 && z == 8)
+                    } else {
+                        stone_chunk(|_, _, _| false)
+                    };
+
+                    let mut chunk = ChunkData::new_handleless(world, position);
+                    chunk.connectivity = ChunkConnectivity::from_blocks(&chunk.world, states);
+                    chunks.chunks.insert(position, chunk);
+                }
+            }
+        }
+
+        let visible = visible_chunks(&chunks, Vector3::new(0, 0, 0), |_| true);
+
+        // Everything above ground, and the top layer of the ground which is seen from above
+        assert!(visible.contains(&Vector3::new(3, 1, -3)));
+        assert!(visible.contains(&Vector3::new(-2, -1, 2)));
+
+        // Solid ground hides what's under it, apart from where the shaft leads
+        assert!(!visible.contains(&Vector3::new(1, -2, 0)));
+        assert!(!visible.contains(&Vector3::new(-2, -3, 2)));
+        assert!(visible.contains(&Vector3::new(0, -2, 0)));
+        assert!(!visible.contains(&Vector3::new(0, -3, 0)));
+
+        // Nothing outside the view is walked through
+        let in_front = visible_chunks(&chunks, Vector3::new(0, 0, 0), |position| position.x >= 0);
+        assert!(!in_front.contains(&Vector3::new(-1, 0, 0)));
+        assert!(in_front.contains(&Vector3::new(2, 0, 0)));
+    }
+}
@@ -1,13 +1,41 @@
 use crate::game::player::Player;
+use crate::systems::chunk::lod::LodColumn;
 use crate::systems::chunk::ChunkSystem;
 use crate::systems::physics::PhysicsObject;
-use bevy::prelude::{EventWriter, Query, ResMut, With};
-use nalgebra::Vector3;
+use bevy::prelude::{Commands, EventWriter, Query, ResMut, With};
+use nalgebra::{Vector2, Vector3};
 use rc_shared::constants::UserId;
 use rc_networking::protocol::serverbound::request_chunk::RequestChunk;
+use rc_networking::protocol::serverbound::request_chunk_summary::RequestChunkSummary;
+use rc_networking::protocol::serverbound::unload_chunk::UnloadChunk;
+use rc_networking::protocol::serverbound::unload_chunk_summary::UnloadChunkSummary;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::SendPacket;
-use rc_shared::helpers::global_f32_to_local_position;
+use rc_shared::helpers::{global_f32_to_local_position, global_to_local_position};
+use std::collections::HashSet;
+
+#[cfg(target_arch = "wasm32")]
+const RENDER_DISTANCE: i32 = 6;
+#[cfg(not(target_arch = "wasm32"))]
+const RENDER_DISTANCE: i32 = 8;
+
+/// How far away chunk columns are drawn coarsely from summaries of their surface, past where chunks are loaded
+#[cfg(target_arch = "wasm32")]
+const LOD_DISTANCE: i32 = 32;
+#[cfg(not(target_arch = "wasm32"))]
+const LOD_DISTANCE: i32 = 48;
+
+/// How far past the render distance chunks are kept, so walking back and forth over a chunk border doesn't reload them
+const UNLOAD_HYSTERESIS: i32 = 2;
+
+/// The scale to summarise a chunk column at, from how many chunks away it is
+fn lod_scale(distance: f32) -> u8 {
+    if distance <= (RENDER_DISTANCE * 2) as f32 {
+        2
+    } else {
+        4
+    }
+}
 
 /// Requests chunks when we move between chunks
 pub fn request_chunks(
@@ -27,10 +55,7 @@ pub fn request_chunks(
         return;
     }
 
-    #[cfg(target_arch = "wasm32")]
-    let render_distance = 6;
-    #[cfg(not(target_arch = "wasm32"))]
-    let render_distance = 8;
+    let render_distance = RENDER_DISTANCE;
 
     // Load new chunks
     for x in -render_distance..render_distance {
@@ -65,3 +90,137 @@ pub fn request_chunks(
         }
     }
 }
+
+/// Unloads chunks that are far outside of render distance when we move between chunks
+pub fn unload_chunks(
+    player: Query<&PhysicsObject, With<Player>>,
+    mut system: ResMut<ChunkSystem>,
+    mut commands: Commands,
+    mut unload_requests: EventWriter<SendPacket>,
+) {
+    let Ok(object) = player.get_single() else {
+        return
+    };
+
+    let (current_chunk, _) = global_f32_to_local_position(object.position);
+    let (previous_chunk, _) = global_f32_to_local_position(object.previous_position);
+
+    if current_chunk == previous_chunk {
+        return;
+    }
+
+    let unload_distance = (RENDER_DISTANCE + UNLOAD_HYSTERESIS) as f32;
+    let is_far = |chunk: &Vector3<i32>| (chunk - current_chunk).cast::<f32>().magnitude() > unload_distance;
+
+    // Requests are forgotten too, so the chunks are requested again when we come back
+    let far_chunks = system
+        .chunks
+        .keys()
+        .chain(system.requested_chunks.iter())
+        .filter(|chunk| is_far(chunk))
+        .copied()
+        .collect::<HashSet<Vector3<i32>>>();
+
+    if far_chunks.is_empty() {
+        return;
+    }
+
+    system.requested_chunks.retain(|chunk| !far_chunks.contains(chunk));
+    system
+        .block_entities
+        .retain(|pos, _| !far_chunks.contains(&global_to_local_position(*pos).0));
+
+    for chunk in far_chunks {
+        system.unload_chunk(chunk, &mut commands);
+
+        unload_requests.send(SendPacket(
+            Protocol::UnloadChunk(UnloadChunk::new(chunk.x, chunk.y, chunk.z)),
+            UserId(0),
+        ));
+    }
+
+    // Drop far away columns once they have no chunks left in them
+    let loaded_columns = system
+        .chunks
+        .keys()
+        .map(|chunk| Vector2::new(chunk.x, chunk.z))
+        .collect::<HashSet<Vector2<i32>>>();
+    let current_column = Vector2::new(current_chunk.x, current_chunk.z);
+
+    system.chunk_columns.retain(|column, _| {
+        loaded_columns.contains(column)
+            || (column - current_column).cast::<f32>().magnitude() <= unload_distance
+    });
+}
+
+/// Requests summaries of the chunk columns around us when we move between columns, asking again for a finer
+/// one as we get closer, and drops those too far away
+pub fn request_chunk_summaries(
+    player: Query<&PhysicsObject, With<Player>>,
+    mut system: ResMut<ChunkSystem>,
+    mut commands: Commands,
+    mut summary_requests: EventWriter<SendPacket>,
+) {
+    let Ok(object) = player.get_single() else {
+        return
+    };
+
+    let (current_chunk, _) = global_f32_to_local_position(object.position);
+    let (previous_chunk, _) = global_f32_to_local_position(object.previous_position);
+
+    let current_column = Vector2::new(current_chunk.x, current_chunk.z);
+    if current_column == Vector2::new(previous_chunk.x, previous_chunk.z) {
+        return;
+    }
+
+    let distance = |column: &Vector2<i32>| (column - current_column).cast::<f32>().magnitude();
+
+    let far_columns = system
+        .lod_columns
+        .keys()
+        .filter(|column| distance(column) > (LOD_DISTANCE + UNLOAD_HYSTERESIS) as f32)
+        .copied()
+        .collect::<Vec<Vector2<i32>>>();
+
+    for column in far_columns {
+        system.unload_lod_column(column, &mut commands);
+
+        summary_requests.send(SendPacket(
+            Protocol::UnloadChunkSummary(UnloadChunkSummary::new(column.x, column.y)),
+            UserId(0),
+        ));
+    }
+
+    let mut requests = Vec::new();
+    for x in -LOD_DISTANCE..=LOD_DISTANCE {
+        for z in -LOD_DISTANCE..=LOD_DISTANCE {
+            let column = current_column + Vector2::new(x, z);
+            if distance(&column) > LOD_DISTANCE as f32 {
+                continue;
+            }
+
+            let scale = lod_scale(distance(&column));
+            if system.lod_columns.get(&column).is_some_and(|lod| lod.requested_scale <= scale) {
+                continue;
+            }
+
+            requests.push((column, scale));
+        }
+    }
+
+    // The server answers in order, so the nearest are drawn first
+    requests.sort_by(|(a, _), (b, _)| distance(a).total_cmp(&distance(b)));
+
+    for (column, scale) in requests {
+        system
+            .lod_columns
+            .entry(column)
+            .or_insert_with(|| LodColumn::new(scale))
+            .requested_scale = scale;
+
+        summary_requests.send(SendPacket(
+            Protocol::RequestChunkSummary(RequestChunkSummary::new(column.x, column.y, scale)),
+            UserId(0),
+        ));
+    }
+}
@@ -6,13 +6,11 @@ use crate::systems::physics::PhysicsObject;
 use bevy::prelude::*;
 use nalgebra::Vector3;
 use rc_shared::block::BlockStates;
+use rc_shared::physics::{JUMP_VELOCITY, MOVEMENT_SPEED_POSITION, MOVEMENT_SPEED_VELOCITY, SPRINTING_MULTIPLIER, SWIMMING_MULTIPLIER, SWIM_VELOCITY};
 use crate::systems::camera::MainCamera;
 use crate::systems::debugging::DebuggingInfo;
 use crate::systems::ui::console::ConsoleData;
 
-const MOVEMENT_SPEED_POSITION: f32 = 2.4;
-const MOVEMENT_SPEED_VELOCITY: f32 = 15.0;
-
 pub fn update_input_movement(
     service: Res<InputSystem>,
     mut player: Query<(&mut PhysicsObject, &Player)>,
@@ -39,22 +37,28 @@ pub fn update_input_movement(
     let forward = Vector3::new(forward.x, 0.0, forward.z);
     let right = forward.cross(&Vector3::new(0.0, 1.0, 0.0));
 
-    let flying_multiplier = if player_physics.touching_ground {
+    let swimming = player_physics.submersion > 0.0;
+
+    let flying_multiplier = if swimming {
+        SWIMMING_MULTIPLIER
+    } else if player_physics.touching_ground {
         1.0
     } else {
         0.4
     };
 
     let sprinting_multiplier = if player.is_sprinting && player_physics.touching_ground {
-        1.5
+        SPRINTING_MULTIPLIER
     } else {
         1.0
     };
 
     let mut proposed_delta = Vector3::zeros();
 
-    if keys.pressed(KeyCode::Space) && player_physics.touching_ground {
-        player_physics.velocity.y = 9.0;
+    if keys.pressed(KeyCode::Space) && swimming {
+        player_physics.velocity.y = player_physics.velocity.y.max(SWIM_VELOCITY);
+    } else if keys.pressed(KeyCode::Space) && player_physics.touching_ground {
+        player_physics.velocity.y = JUMP_VELOCITY;
     }
     if keys.pressed(KeyCode::KeyW) {
         // W is being held down
@@ -3,6 +3,7 @@ use crate::systems::chunk::builder::{RerenderChunkRequest, RerenderChunkFlagCont
 use crate::systems::chunk::ChunkSystem;
 use bevy::prelude::*;
 use bevy::utils::hashbrown::HashMap;
+use std::mem;
 use nalgebra::Vector3;
 use rc_shared::constants::UserId;
 use rc_networking::protocol::clientbound::chunk_update::{
@@ -11,13 +12,26 @@ use rc_networking::protocol::clientbound::chunk_update::{
 use rc_networking::protocol::serverbound::acknowledge_chunk::AcknowledgeChunk;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::{ReceivePacket, SendPacket};
+use rc_shared::block::BlockStates;
+use rc_shared::block::palette::BlockIdRemap;
 use rc_shared::chunk::ChunkDataStorage;
 use rc_shared::helpers::global_to_local_position;
+use rc_shared::light::LightUpdate;
 use rc_shared::CHUNK_SIZE;
 
-#[derive(Default)]
+/// Chunk data received from the server that can't be used yet. Block ids differ between servers,
+/// so this is reset whenever a new connection is made.
+#[derive(Resource, Default)]
 pub struct ChunkSync {
     partial_chunks: HashMap<u64, Vec<PartialChunkUpdate>>,
+    /// Translates the server's block ids into ours, unknown until the server sends its palette
+    block_ids: Option<BlockIdRemap>,
+    /// Packets containing blocks that arrived before the server's palette, applied once it arrives
+    waiting_for_palette: Vec<Protocol>,
+}
+
+pub fn reset_chunk_sync(mut chunk_cache: ResMut<ChunkSync>) {
+    *chunk_cache = ChunkSync::default();
 }
 
 pub fn network_chunk_sync(
@@ -27,21 +41,51 @@ pub fn network_chunk_sync(
     mut asset_service: Res<AssetService>,
     mut chunk_service: ResMut<ChunkSystem>,
     mut rerender_chunks: EventWriter<RerenderChunkRequest>,
-    mut chunk_cache: Local<ChunkSync>,
+    mut chunk_cache: ResMut<ChunkSync>,
     mut send_response: EventWriter<SendPacket>,
+    block_states: Res<BlockStates>,
 ) {
+    let mut packets = vec![];
+
     for event in event_reader.read() {
         match &event.0 {
-            Protocol::FullChunkUpdate(update) => {
+            Protocol::BlockPalette(palette) => {
+                chunk_cache.block_ids = Some(BlockIdRemap::new(palette, &block_states.palette()));
+
+                // Packets that were waiting arrived before any received this frame
+                let mut waiting = mem::take(&mut chunk_cache.waiting_for_palette);
+                waiting.append(&mut packets);
+                packets = waiting;
+            }
+            Protocol::FullChunkUpdate(_)
+            | Protocol::PartialChunkUpdate(_)
+            | Protocol::BlockUpdate(_)
+            | Protocol::BlockEntityUpdate(_)
+            | Protocol::ChunkSummaryUpdate(_)
+            | Protocol::UnloadAllChunks(_) => packets.push(event.0.clone()),
+            _ => {}
+        }
+    }
+
+    let Some(block_ids) = chunk_cache.block_ids.clone() else {
+        chunk_cache.waiting_for_palette.append(&mut packets);
+        return;
+    };
+
+    for packet in packets {
+        match packet {
+            Protocol::FullChunkUpdate(mut update) => {
                 let location = Vector3::new(update.x, update.y, update.z);
 
                 if chunk_service.chunks.contains_key(&location) {
                     chunk_service.unload_chunk(location, &mut commands);
                 }
 
+                update.data.remap(&block_ids);
+
                 chunk_service.create_chunk(
                     location,
-                    update.data.clone(),
+                    update.data,
                     &mut commands,
                     &asset_service,
                     &mut meshes,
@@ -59,31 +103,34 @@ pub fn network_chunk_sync(
                 ));
             }
             Protocol::PartialChunkUpdate(update) => {
-                if !chunk_cache.partial_chunks.contains_key(&update.id) {
-                    let partial_chunks = vec![update.clone()];
-                    chunk_cache.partial_chunks.insert(update.id, partial_chunks);
+                let update_id = update.id;
+                let location = Vector3::new(update.x, update.y, update.z);
+
+                if !chunk_cache.partial_chunks.contains_key(&update_id) {
+                    let partial_chunks = vec![update];
+                    chunk_cache.partial_chunks.insert(update_id, partial_chunks);
                     continue;
                 }
 
                 chunk_cache
                     .partial_chunks
-                    .get_mut(&update.id)
+                    .get_mut(&update_id)
                     .unwrap()
-                    .push(update.clone());
+                    .push(update);
                 // Check if its full
                 let needed_chunks = ((CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as f32
                     / PARTIAL_CHUNK_UPDATE_SIZE as f32)
                     .ceil() as usize;
 
-                if chunk_cache.partial_chunks.get(&update.id).unwrap().len() != needed_chunks {
+                if chunk_cache.partial_chunks.get(&update_id).unwrap().len() != needed_chunks {
                     continue;
                 }
 
-                let location = Vector3::new(update.x, update.y, update.z);
-
-                if let Some(chunk) = FullChunkUpdate::from_partial(
-                    chunk_cache.partial_chunks.remove(&update.id).unwrap(),
+                if let Some(mut chunk) = FullChunkUpdate::from_partial(
+                    chunk_cache.partial_chunks.remove(&update_id).unwrap(),
                 ) {
+                    chunk.data.remap(&block_ids);
+
                     chunk_service.create_chunk(
                         location,
                         chunk.data,
@@ -102,6 +149,7 @@ pub fn network_chunk_sync(
             }
             Protocol::BlockUpdate(update) => {
                 let location = Vector3::new(update.x, update.y, update.z);
+                let block_id = block_ids.get(update.id);
 
                 // Locate chunk
                 let (chunk_loc, inner_loc) = global_to_local_position(location);
@@ -109,7 +157,8 @@ pub fn network_chunk_sync(
                 // Try find chunk
                 if let Some(chunk) = chunk_service.chunks.get_mut(&chunk_loc) {
                     // Found chunk! Update block
-                    chunk.world.set(inner_loc, update.id);
+                    chunk.world.set(inner_loc, block_id);
+                    chunk_service.light_updates.push(LightUpdate::Block(location));
 
                     // Rerender
                     rerender_chunks.send(RerenderChunkRequest {
@@ -121,7 +170,7 @@ pub fn network_chunk_sync(
                     let mut chunk = [[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
 
                     // Set block
-                    chunk[inner_loc.x][inner_loc.y][inner_loc.z] = update.id;
+                    chunk[inner_loc.x][inner_loc.y][inner_loc.z] = block_id;
 
                     // Create chunk
                     chunk_service.create_chunk(
@@ -138,6 +187,18 @@ pub fn network_chunk_sync(
                     });
                 }
             }
+            Protocol::BlockEntityUpdate(update) => {
+                let location = Vector3::new(update.x, update.y, update.z);
+
+                match update.entity {
+                    Some(entity) => chunk_service.block_entities.insert(location, entity),
+                    None => chunk_service.block_entities.remove(&location),
+                };
+            }
+            Protocol::ChunkSummaryUpdate(mut update) => {
+                update.summary.remap(&block_ids);
+                chunk_service.receive_summary(update.position, update.summary);
+            }
             Protocol::UnloadAllChunks(_) => {
                 chunk_service.unload_all_chunks(&mut commands);
             }
@@ -1,4 +1,4 @@
-use crate::systems::networking::chunk::network_chunk_sync;
+use crate::systems::networking::chunk::{network_chunk_sync, reset_chunk_sync, ChunkSync};
 use crate::systems::networking::location_sync::{
     LastNetworkRotationSync, LastNetworkTranslationSync, network_location_sync,
 };
@@ -9,6 +9,7 @@ use rc_shared::constants::{GameObjectId, UserId};
 use std::collections::HashMap;
 use nalgebra::{Quaternion, Vector3};
 use crate::authentication::GameAuthentication;
+use crate::state::AppState;
 
 mod chunk;
 mod location_sync;
@@ -21,7 +22,9 @@ impl Plugin for NetworkingPlugin {
         app.add_plugins(NetworkingClientPlugin)
             .add_systems(Update, messages_update)
             .add_systems(Update, network_location_sync)
+            .init_resource::<ChunkSync>()
             .add_systems(Update, network_chunk_sync)
+            .add_systems(OnEnter(AppState::Connecting), reset_chunk_sync)
             .insert_resource(LastNetworkTranslationSync(Vector3::default()))
             .insert_resource(LastNetworkRotationSync(Quat::default()));
 
@@ -0,0 +1,180 @@
+use crate::systems::physics::PhysicsObject;
+use bevy::prelude::*;
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+use rc_shared::PHYSICS_SYNC_RATE_SECONDS;
+use std::collections::VecDeque;
+
+/// How many ticks in the past remote game objects are drawn, so there is usually a later snapshot to interpolate towards
+const INTERPOLATION_DELAY_TICKS: f64 = 2.0;
+
+/// How many ticks past the latest snapshot a game object keeps moving before it eases back to it
+const MAX_EXTRAPOLATION_TICKS: f64 = 3.0;
+
+/// How far the estimated server tick can run ahead of received packets before it is corrected
+const MAX_CLOCK_DRIFT_TICKS: f64 = 4.0;
+
+/// The most snapshots stored per game object, in case the render time stops advancing
+const MAX_SNAPSHOTS: usize = 32;
+
+/// Estimates the server's current tick from the ticks stamped on received packets
+#[derive(Resource, Default)]
+pub struct ServerClock {
+    anchor: Option<(u64, f64)>,
+}
+
+impl ServerClock {
+    /// Records that a packet stamped with `tick` was received at `now` seconds
+    pub fn observe(&mut self, tick: u64, now: f64) {
+        let drifted = self
+            .estimated_tick(now)
+            .map_or(true, |estimate| tick as f64 > estimate || estimate - tick as f64 > MAX_CLOCK_DRIFT_TICKS);
+
+        if drifted {
+            self.anchor = Some((tick, now));
+        }
+    }
+
+    /// The tick the server is likely on at `now` seconds, as seen through the latest packets
+    pub fn estimated_tick(&self, now: f64) -> Option<f64> {
+        self.anchor
+            .map(|(tick, received)| tick as f64 + (now - received) / PHYSICS_SYNC_RATE_SECONDS)
+    }
+}
+
+pub trait Interpolate: Copy {
+    /// Blends from `self` at t = 0 to `to` at t = 1. Values of t above 1 extrapolate where supported
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Interpolate for Quaternion<f32> {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        let from = UnitQuaternion::new_normalize(*self);
+        let to = UnitQuaternion::new_normalize(*to);
+
+        from.slerp(&to, t.clamp(0.0, 1.0)).into_inner()
+    }
+}
+
+/// States of a value received from the server, ordered by the tick they were recorded on
+#[derive(Default)]
+pub struct SnapshotBuffer<T> {
+    snapshots: VecDeque<(u64, T)>,
+}
+
+impl<T: Interpolate> SnapshotBuffer<T> {
+    pub fn push(&mut self, tick: u64, value: T) {
+        let index = self.snapshots.partition_point(|(t, _)| *t <= tick);
+
+        // Replace duplicates, as later packets for the same tick are more up to date
+        if index > 0 && self.snapshots[index - 1].0 == tick {
+            self.snapshots[index - 1].1 = value;
+        } else {
+            self.snapshots.insert(index, (tick, value));
+        }
+
+        if self.snapshots.len() > MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Calculates the value at `tick`, then forgets snapshots that are no longer needed to do so
+    pub fn sample(&mut self, tick: f64) -> Option<T> {
+        while self.snapshots.len() > 2 && self.snapshots[1].0 as f64 <= tick {
+            self.snapshots.pop_front();
+        }
+
+        let (first_tick, first) = *self.snapshots.front()?;
+
+        if self.snapshots.len() == 1 || tick <= first_tick as f64 {
+            return Some(first);
+        }
+
+        let (second_tick, second) = self.snapshots[1];
+        let gap = (second_tick - first_tick) as f64;
+        let mut progress = tick - first_tick as f64;
+
+        // Past the latest snapshot, keep moving for a while then ease back so stopped objects settle where they stopped
+        let overshoot = tick - second_tick as f64;
+        if overshoot > MAX_EXTRAPOLATION_TICKS {
+            progress = gap + (2.0 * MAX_EXTRAPOLATION_TICKS - overshoot).max(0.0);
+        }
+
+        Some(first.interpolate(&second, (progress / gap) as f32))
+    }
+}
+
+/// Buffered server state for a game object controlled by the server, drawn slightly in the past
+#[derive(Component, Default)]
+pub struct RemoteSnapshots {
+    pub position: SnapshotBuffer<Vector3<f32>>,
+    pub rotation: SnapshotBuffer<Quaternion<f32>>,
+}
+
+/// Moves remote game objects to their buffered state at the current render time
+pub fn interpolate_snapshots(
+    mut query: Query<(&mut PhysicsObject, &mut RemoteSnapshots)>,
+    clock: Res<ServerClock>,
+    time: Res<Time>,
+) {
+    let Some(tick) = clock.estimated_tick(time.elapsed_seconds_f64()) else {
+        return;
+    };
+    let render_tick = tick - INTERPOLATION_DELAY_TICKS;
+
+    for (mut object, mut snapshots) in query.iter_mut() {
+        if let Some(position) = snapshots.position.sample(render_tick) {
+            object.position = position;
+            object.previous_position = position;
+        }
+        if let Some(rotation) = snapshots.rotation.sample(render_tick) {
+            object.rotation = rotation;
+            object.previous_rotation = rotation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::physics::interpolation::{ServerClock, SnapshotBuffer};
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_sample_snapshots() {
+        let mut buffer = SnapshotBuffer::default();
+        buffer.push(10, Vector3::new(0.0, 0.0, 0.0));
+        buffer.push(14, Vector3::new(4.0, 0.0, 0.0));
+        buffer.push(12, Vector3::new(2.0, 0.0, 0.0));
+
+        assert_eq!(buffer.sample(9.0), Some(Vector3::new(0.0, 0.0, 0.0)));
+        assert_eq!(buffer.sample(11.0), Some(Vector3::new(1.0, 0.0, 0.0)));
+        assert_eq!(buffer.sample(13.5), Some(Vector3::new(3.5, 0.0, 0.0)));
+
+        // Extrapolates for a bounded time, then settles back on the latest snapshot
+        assert_eq!(buffer.sample(16.0), Some(Vector3::new(6.0, 0.0, 0.0)));
+        assert_eq!(buffer.sample(19.0), Some(Vector3::new(5.0, 0.0, 0.0)));
+        assert_eq!(buffer.sample(30.0), Some(Vector3::new(4.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_server_clock() {
+        let mut clock = ServerClock::default();
+        assert_eq!(clock.estimated_tick(0.0), None);
+
+        clock.observe(100, 1.0);
+        assert!((clock.estimated_tick(1.5).unwrap() - 110.0).abs() < 0.001);
+
+        // Packets arriving a little late don't pull the clock backwards
+        clock.observe(108, 1.5);
+        assert!((clock.estimated_tick(1.5).unwrap() - 110.0).abs() < 0.001);
+
+        // Packets from ahead of the estimate move it forwards
+        clock.observe(120, 1.5);
+        assert_eq!(clock.estimated_tick(1.5), Some(120.0));
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::app::PreUpdate;
 use crate::state::AppState;
 use crate::systems::chunk::ChunkSystem;
+use crate::systems::physics::interpolation::{interpolate_snapshots, ServerClock};
 use crate::systems::physics::simulate::physics_tick;
 use crate::systems::physics::sync::{physics_location_sync, physics_rotation_sync, update_last_position};
 use bevy::ecs::component::Component;
@@ -10,6 +11,7 @@ use nalgebra::{Quaternion, Vector3};
 use rc_shared::aabb::Aabb;
 use rc_shared::block::BlockStates;
 
+pub mod interpolation;
 pub mod raycasts;
 mod simulate;
 mod sync;
@@ -24,7 +26,8 @@ impl Plugin for PhysicsPlugin {
             physics_tick
                 .run_if(in_state(AppState::InGame)),
         )
-        .add_systems(Update, (physics_location_sync, physics_rotation_sync))
+        .init_resource::<ServerClock>()
+        .add_systems(Update, (interpolate_snapshots, (physics_location_sync, physics_rotation_sync)).chain())
         .add_systems(FixedPreUpdate, update_last_position);
     }
 }
@@ -40,6 +43,8 @@ pub struct PhysicsObject {
     pub collider: Aabb,
     pub gravity: bool,
     pub touching_ground: bool,
+    /// How much of the object is under water, from 0 to 1
+    pub submersion: f32,
 }
 
 impl PhysicsObject {
@@ -54,6 +59,7 @@ impl PhysicsObject {
             collider,
             gravity: false,
             touching_ground: false,
+            submersion: 0.0,
         }
     }
 
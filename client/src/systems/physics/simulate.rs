@@ -4,14 +4,12 @@ use crate::systems::ui::debugging::DebuggingUIData;
 use bevy::prelude::*;
 use nalgebra::Vector3;
 use rc_shared::block::BlockStates;
+use rc_shared::physics::{AIR_FRICTION, FLUID_BUOYANCY, FLUID_FRICTION, GRAVITY_STRENGTH, MAX_HORIZONTAL_VELOCITY};
 use std::ops::Deref;
 
 const MAX_TOUCHING_GROUND_DIST: f32 = 0.05;
-const GRAVITY_STRENGTH: f32 = 30.0;
 
 const GROUND_FRICTION: f32 = 8.0;
-const AIR_FRICTION: f32 = 0.6;
-const MAX_HORIZONTAL_VELOCITY: f32 = 5.0;
 
 pub fn physics_tick(
     mut query: Query<&mut PhysicsObject>,
@@ -26,8 +24,15 @@ pub fn physics_tick(
     for mut object in query.iter_mut() {
         let current_position = object.position.clone();
 
+        object.submersion = object
+            .collider
+            .offset(object.position)
+            .fluid_submersion(chunks.deref(), &block_states);
+
         if object.gravity {
-            object.velocity.y -= GRAVITY_STRENGTH * time.delta_seconds();
+            // Water pushes back against gravity the deeper the object is
+            let buoyancy = FLUID_BUOYANCY * object.submersion;
+            object.velocity.y -= GRAVITY_STRENGTH * (1.0 - buoyancy) * time.delta_seconds();
         }
 
         let proposed_delta = object.velocity * time.delta_seconds();
@@ -62,6 +67,8 @@ pub fn physics_tick(
 
         if object.touching_ground {
             object.velocity *= 1.0 - (GROUND_FRICTION * time.delta_seconds());
+        } else if object.submersion > 0.0 {
+            object.velocity *= 1.0 - (FLUID_FRICTION * time.delta_seconds());
         } else {
             object.velocity *= 1.0 - (AIR_FRICTION * time.delta_seconds());
         }
@@ -8,7 +8,7 @@ use rc_shared::item::ItemStates;
 use crate::game::game_object::mesh::generate_item_mesh;
 use crate::game::inventory::Inventory;
 use crate::systems::asset::AssetService;
-use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_WIND_STRENGTH};
+use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_TEXTURE_TILE, ATTRIBUTE_WIND_STRENGTH};
 
 /// Used by the view model camera and the player's equipped item.
 /// The light sources belong to both layers
@@ -48,6 +48,7 @@ pub fn setup_equipped_item(
     empty_mesh.insert_attribute(ATTRIBUTE_WIND_STRENGTH, VertexAttributeValues::Float32(vec![]));
     empty_mesh.insert_attribute(ATTRIBUTE_LIGHTING_COLOR, VertexAttributeValues::Float32x4(vec![]));
     empty_mesh.insert_attribute(ATTRIBUTE_SKYLIGHT_STRENGTH, VertexAttributeValues::Uint8x4(vec![]));
+    empty_mesh.insert_attribute(ATTRIBUTE_TEXTURE_TILE, VertexAttributeValues::Float32x4(vec![]));
 
     // Spawn the player's right arm.
     let mesh_entity = commands.spawn((
@@ -92,6 +93,7 @@ pub fn update_equipped_item_mesh(
         mesh.insert_attribute(ATTRIBUTE_WIND_STRENGTH, VertexAttributeValues::Float32(vec![]));
         mesh.insert_attribute(ATTRIBUTE_LIGHTING_COLOR, VertexAttributeValues::Float32x4(vec![]));
         mesh.insert_attribute(ATTRIBUTE_SKYLIGHT_STRENGTH, VertexAttributeValues::Uint8x4(vec![]));
+        mesh.insert_attribute(ATTRIBUTE_TEXTURE_TILE, VertexAttributeValues::Float32x4(vec![]));
 
         mesh
     };
@@ -1,4 +1,4 @@
-use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_WIND_STRENGTH};
+use crate::systems::chunk::builder::{ATTRIBUTE_LIGHTING_COLOR, ATTRIBUTE_SKYLIGHT_STRENGTH, ATTRIBUTE_TEXTURE_TILE, ATTRIBUTE_WIND_STRENGTH};
 use bevy::prelude::Mesh;
 use bevy::render::mesh::{Indices, VertexAttributeValues};
 use nalgebra::Vector3;
@@ -14,6 +14,7 @@ pub struct DrawKit {
     pub indices: Vec<u32>,
     pub normals: Vec<[f32; 3]>,
     pub uv_coordinates: Vec<[f32; 2]>,
+    pub texture_tiles: Vec<[f32; 4]>,
     pub lighting: Vec<[f32; 4]>,
     pub skylight_strengths: Vec<[u8; 4]>,
     pub wind_strength: Option<Vec<f32>>,
@@ -27,6 +28,7 @@ impl DrawKit {
             indices: Vec::with_capacity(1000),
             normals: Vec::with_capacity(1000),
             uv_coordinates: Vec::with_capacity(1000),
+            texture_tiles: Vec::with_capacity(1000),
             lighting: Vec::with_capacity(1000),
             skylight_strengths: Vec::with_capacity(1000),
             wind_strength: None,
@@ -43,6 +45,18 @@ impl DrawKit {
         position: Vector3<f32>,
         face: &Face,
         color: LightingColor
+    ) {
+        self.draw_repeated_face(position, face, color, [1.0, 1.0]);
+    }
+
+    /// Draws a face with its texture repeated `repeat` times, first along its top left to bottom left
+    /// edge then along its top left to top right edge
+    pub fn draw_repeated_face(
+        &mut self,
+        position: Vector3<f32>,
+        face: &Face,
+        color: LightingColor,
+        repeat: [f32; 2],
     ) {
         let center = (face.top_right + face.bottom_left) / 2.0;
 
@@ -70,15 +84,17 @@ impl DrawKit {
             ]);
         }
 
-        // TODO: Push multiple at a time
-        self.uv_coordinates
-            .push([face.texture.u_min, face.texture.v_max]);
-        self.uv_coordinates
-            .push([face.texture.u_min, face.texture.v_min]);
-        self.uv_coordinates
-            .push([face.texture.u_max, face.texture.v_max]);
-        self.uv_coordinates
-            .push([face.texture.u_max, face.texture.v_min]);
+        // UVs count textures across the face, and are wrapped into the texture's place in the atlas by the shader
+        let [u, v] = repeat;
+        self.uv_coordinates.extend([[0.0, v], [0.0, 0.0], [u, v], [u, 0.0]]);
+
+        let tile = [
+            face.texture.u_min,
+            face.texture.v_min,
+            face.texture.u_max - face.texture.u_min,
+            face.texture.v_max - face.texture.v_min,
+        ];
+        self.texture_tiles.extend([tile; 4]);
 
         self.indices.push(indices_index + 1);
         self.indices.push(indices_index + 0);
@@ -109,6 +125,10 @@ impl DrawKit {
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uv_coordinates);
+        mesh.insert_attribute(
+            ATTRIBUTE_TEXTURE_TILE,
+            VertexAttributeValues::Float32x4(self.texture_tiles),
+        );
 
         mesh.insert_attribute(
             ATTRIBUTE_LIGHTING_COLOR,
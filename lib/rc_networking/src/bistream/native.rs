@@ -92,4 +92,11 @@ impl BiStream {
     pub fn send(&self, message: Protocol) -> Result<(), SendError<Protocol>> {
         self.out_send.send(message)
     }
-}
\ No newline at end of file
+
+    /// Stops sending, resolving once every packet already queued has been written
+    pub async fn finish(self) {
+        drop(self.out_send);
+        self.in_handle.abort();
+        let _ = self.out_handle.await;
+    }
+}
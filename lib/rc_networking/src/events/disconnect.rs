@@ -5,3 +5,10 @@ use rc_shared::constants::UserId;
 pub struct NetworkDisconnectionEvent {
     pub client: UserId,
 }
+
+/// Closes a client's connection from the server, telling them why
+#[derive(Event)]
+pub struct DisconnectClient {
+    pub client: UserId,
+    pub reason: String,
+}
@@ -41,6 +41,9 @@ fn get_channel(protocol: &Protocol) -> Channel {
         | Protocol::GameObjectRotated(_) => Channel::Unreliable,
 
         Protocol::BlockUpdate(_)
+        | Protocol::BlockEntityUpdate(_)
+        | Protocol::PipeItemSent(_)
+        | Protocol::PipeItemRemoved(_)
         | Protocol::Disconnect(_)
         | Protocol::ChatSent(_)
         | Protocol::PlayerChat(_)
@@ -48,11 +51,15 @@ fn get_channel(protocol: &Protocol) -> Channel {
         | Protocol::SpawnGameObject(_)
         | Protocol::UpdateLoading(_)
         | Protocol::RequestChunk(_)
+        | Protocol::RequestChunkSummary(_)
+        | Protocol::UnloadChunkSummary(_)
+        | Protocol::UnloadChunk(_)
         | Protocol::ServerState(_)
         | Protocol::UpdateInventorySlot(_)
         | Protocol::UpdateInventory(_)
         | Protocol::Authorization(_)
         | Protocol::AuthorizationAccepted
+        | Protocol::BlockPalette(_)
         | Protocol::PlaceBlock(_)
         | Protocol::DestroyBlock(_)
         | Protocol::ChangeHotbarSlot(_)
@@ -61,6 +68,8 @@ fn get_channel(protocol: &Protocol) -> Channel {
         | Protocol::UnloadAllChunks(_)
         | Protocol::AcknowledgeChunk(_) => Channel::Reliable,
 
-        Protocol::FullChunkUpdate(_) | Protocol::PartialChunkUpdate(_) => Channel::Chunk,
+        Protocol::FullChunkUpdate(_)
+        | Protocol::PartialChunkUpdate(_)
+        | Protocol::ChunkSummaryUpdate(_) => Channel::Chunk,
     }
 }
@@ -0,0 +1,22 @@
+use serde::{Serialize, Deserialize};
+use rc_shared::block::entity::BlockEntityData;
+
+/// The block entity at a position, or that the block there no longer has one
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BlockEntityUpdate {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub entity: Option<BlockEntityData>,
+}
+
+impl BlockEntityUpdate {
+    pub fn new(x: i32, y: i32, z: i32, entity: Option<BlockEntityData>) -> BlockEntityUpdate {
+        BlockEntityUpdate {
+            x,
+            y,
+            z,
+            entity
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use nalgebra::Vector2;
+use serde::{Serialize, Deserialize};
+use rc_shared::chunk_summary::ChunkSummary;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[repr(C)]
+pub struct ChunkSummaryUpdate {
+    pub position: Vector2<i32>,
+    pub summary: ChunkSummary
+}
+
+impl ChunkSummaryUpdate {
+    pub fn new(position: Vector2<i32>, summary: ChunkSummary) -> ChunkSummaryUpdate {
+        ChunkSummaryUpdate {
+            position,
+            summary
+        }
+    }
+}
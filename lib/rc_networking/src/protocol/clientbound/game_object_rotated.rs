@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 #[repr(C)]
 pub struct GameObjectRotated {
     pub entity: GameObjectId,
+    /// The server tick this state was recorded on
+    pub tick: u64,
     pub x: f32,
     pub y: f32,
     pub z: f32,
@@ -1,4 +1,5 @@
 pub mod block_update;
+pub mod block_entity_update;
 pub mod chat;
 pub mod chunk_update;
 pub mod despawn_game_object;
@@ -11,4 +12,6 @@ pub mod update_inventory_slot;
 pub mod update_inventory;
 pub mod game_mode_update;
 pub mod chunk_column_update;
-pub mod unload_all_chunks;
\ No newline at end of file
+pub mod unload_all_chunks;
+pub mod pipe_item;
+pub mod chunk_summary_update;
\ No newline at end of file
@@ -0,0 +1,20 @@
+use nalgebra::Vector3;
+use serde::{Serialize, Deserialize};
+
+/// An item has started travelling through pipes. Clients move it along `path` themselves, so
+/// nothing more is sent until it arrives.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PipeItemSent {
+    pub id: u64,
+    pub item_identifier: String,
+    /// The pipes the item passes through, in order
+    pub path: Vec<Vector3<i32>>,
+    /// Blocks per second
+    pub speed: f32,
+}
+
+/// An item has left the pipes, either arriving or falling out of a broken pipe
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone)]
+pub struct PipeItemRemoved {
+    pub id: u64,
+}
@@ -1,4 +1,6 @@
 use crate::protocol::clientbound::block_update::BlockUpdate;
+use crate::protocol::clientbound::block_entity_update::BlockEntityUpdate;
+use crate::protocol::clientbound::pipe_item::{PipeItemRemoved, PipeItemSent};
 use crate::protocol::clientbound::chat::ChatSent;
 use crate::protocol::clientbound::chunk_update::{FullChunkUpdate, PartialChunkUpdate};
 use crate::protocol::clientbound::despawn_game_object::DespawnGameObject;
@@ -11,10 +13,15 @@ use crate::protocol::serverbound::acknowledge_chunk::AcknowledgeChunk;
 use crate::protocol::serverbound::player_move::PlayerMove;
 use crate::protocol::serverbound::player_rotate::PlayerRotate;
 use crate::protocol::serverbound::request_chunk::RequestChunk;
+use crate::protocol::serverbound::request_chunk_summary::RequestChunkSummary;
+use crate::protocol::serverbound::unload_chunk::UnloadChunk;
+use crate::protocol::serverbound::unload_chunk_summary::UnloadChunkSummary;
 use self::clientbound::update_inventory::UpdateInventory;
 use self::clientbound::update_inventory_slot::UpdateInventorySlot;
 use serde::{Deserialize, Serialize};
+use rc_shared::block::palette::BlockPalette;
 use crate::protocol::clientbound::chunk_column_update::ChunkColumnUpdate;
+use crate::protocol::clientbound::chunk_summary_update::ChunkSummaryUpdate;
 use crate::protocol::clientbound::game_mode_update::GameModeUpdate;
 use crate::protocol::clientbound::unload_all_chunks::UnloadAllChunks;
 use crate::protocol::serverbound::change_hotbar_slot::ChangeHotbarSlot;
@@ -33,12 +40,17 @@ pub const ALPN: &[u8] = b"h3";
 pub enum Protocol {
     Authorization(String),
     AuthorizationAccepted,
+    /// The server's block ids, sent before anything containing them so clients can translate them into their own
+    BlockPalette(BlockPalette),
     PlayerMove(PlayerMove),
     GameObjectMoved(GameObjectMoved),
     PlayerRotate(PlayerRotate),
     GameObjectRotated(GameObjectRotated),
     DespawnGameObject(DespawnGameObject),
     BlockUpdate(BlockUpdate),
+    BlockEntityUpdate(BlockEntityUpdate),
+    PipeItemSent(PipeItemSent),
+    PipeItemRemoved(PipeItemRemoved),
     ChatSent(ChatSent),
     ServerState(ServerState),
     PlayerChat(PlayerChat),
@@ -57,5 +69,9 @@ pub enum Protocol {
     UpdateInventorySlot(UpdateInventorySlot),
     UpdateInventory(UpdateInventory),
     Disconnect(String),
-    UnloadAllChunks(UnloadAllChunks)
+    UnloadAllChunks(UnloadAllChunks),
+    UnloadChunk(UnloadChunk),
+    RequestChunkSummary(RequestChunkSummary),
+    UnloadChunkSummary(UnloadChunkSummary),
+    ChunkSummaryUpdate(ChunkSummaryUpdate)
 }
@@ -6,3 +6,6 @@ pub mod player_chat;
 pub mod place_block;
 pub mod change_hotbar_slot;
 pub mod destroy_block;
+pub mod unload_chunk;
+pub mod request_chunk_summary;
+pub mod unload_chunk_summary;
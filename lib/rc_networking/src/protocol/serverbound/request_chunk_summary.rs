@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Asks for a summary of a chunk column too far away to load, with each cell covering `scale` blocks along each side
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone)]
+#[repr(C)]
+pub struct RequestChunkSummary {
+    pub x: i32,
+    pub z: i32,
+    pub scale: u8,
+}
+
+impl RequestChunkSummary {
+    pub fn new(x: i32, z: i32, scale: u8) -> RequestChunkSummary {
+        RequestChunkSummary { x, z, scale }
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Tells the server the client has dropped a chunk, so it stops sending updates for it
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone)]
+#[repr(C)]
+pub struct UnloadChunk {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl UnloadChunk {
+    pub fn new(x: i32, y: i32, z: i32) -> UnloadChunk {
+        UnloadChunk { x, y, z }
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Tells the server the client has dropped a chunk column's summary, so it stops sending it if still queued
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone)]
+#[repr(C)]
+pub struct UnloadChunkSummary {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl UnloadChunkSummary {
+    pub fn new(x: i32, z: i32) -> UnloadChunkSummary {
+        UnloadChunkSummary { x, z }
+    }
+}
@@ -2,10 +2,10 @@ use rc_shared::config;
 use dotenvy_macro::dotenv;
 use rc_shared::constants::UserId;
 use crate::events::connection::NetworkConnectionEvent;
-use crate::events::disconnect::NetworkDisconnectionEvent;
+use crate::events::disconnect::{DisconnectClient, NetworkDisconnectionEvent};
 
 use crate::server::systems::{
-    open_new_conn, read_packets_system, update_system, write_packets_system,
+    disconnect_system, open_new_conn, read_packets_system, update_system, write_packets_system,
 };
 use crate::server::user_connection::UserConnection;
 use crate::types::{ReceivePacket, SendPacket};
@@ -34,9 +34,10 @@ impl Plugin for QuinnServerPlugin {
             .add_event::<SendPacket>()
             .add_event::<NetworkConnectionEvent>()
             .add_event::<NetworkDisconnectionEvent>()
+            .add_event::<DisconnectClient>()
             .add_systems(
                 Update,
-                (update_system, read_packets_system, write_packets_system),
+                (update_system, read_packets_system, write_packets_system, disconnect_system.after(write_packets_system)),
             );
 
         app.init_resource::<NetworkingServerConfig>();
@@ -3,7 +3,7 @@ use bevy::prelude::error;
 use crate::bistream::{BiStream, recv_protocol, send_protocol};
 use rc_shared::constants::UserId;
 use crate::events::connection::NetworkConnectionEvent;
-use crate::events::disconnect::NetworkDisconnectionEvent;
+use crate::events::disconnect::{DisconnectClient, NetworkDisconnectionEvent};
 use crate::server::user_connection::UserConnection;
 use crate::server::NetworkingServer;
 use crate::types::{ReceivePacket, SendPacket};
@@ -239,3 +239,29 @@ pub fn write_packets_system(
         }
     });
 }
+
+/// Closes the connections of clients the server has disconnected, once the reason has been sent
+pub fn disconnect_system(
+    mut server: ResMut<NetworkingServer>,
+    mut requests: EventReader<DisconnectClient>,
+    mut disconnection_event: EventWriter<NetworkDisconnectionEvent>,
+) {
+    for request in requests.read() {
+        let Some(UserConnection { mut connection, reliable, .. }) = server.connections.remove(&request.client) else {
+            continue;
+        };
+
+        if let Err(e) = reliable.send(Protocol::Disconnect(request.reason.clone())) {
+            warn!("Failed to send disconnect reason to {:?}: {:?}", request.client, e);
+        }
+
+        let reason = request.reason.clone();
+
+        server.runtime.spawn(async move {
+            reliable.finish().await;
+            connection.close(0, &reason);
+        });
+
+        disconnection_event.send(NetworkDisconnectionEvent { client: request.client });
+    }
+}
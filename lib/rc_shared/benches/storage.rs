@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use nalgebra::Vector3;
+use rc_shared::chunk::ChunkDataStorage;
+use crate::chunk::get_chunk;
+
+mod chunk;
+
+// Compares reading and writing blocks in the raw and paletted chunk storages
+fn bench_storage(c: &mut Criterion) {
+    let raw = ChunkDataStorage::Data(Box::new(get_chunk()));
+
+    let mut paletted = raw.clone();
+    paletted.optimise();
+
+    assert!(matches!(paletted, ChunkDataStorage::Palette(_)));
+
+    let mut group = c.benchmark_group("chunk_storage_get");
+
+    for (name, storage) in [("data", &raw), ("palette", &paletted)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut total = 0;
+                for x in 0..16 {
+                    for y in 0..16 {
+                        for z in 0..16 {
+                            total += storage.get(Vector3::new(x, y, z));
+                        }
+                    }
+                }
+                std::hint::black_box(total)
+            });
+        });
+    }
+
+    group.finish();
+
+    let mut group = c.benchmark_group("chunk_storage_set");
+
+    for (name, storage) in [("data", &raw), ("palette", &paletted)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut storage = storage.clone();
+                for x in 0..16 {
+                    for y in 0..16 {
+                        for z in 0..16 {
+                            storage.set(Vector3::new(x, y, z), ((x + y + z) % 8) as u32);
+                        }
+                    }
+                }
+                std::hint::black_box(storage)
+            });
+        });
+    }
+
+    group.finish();
+
+    c.bench_function("chunk_storage_optimise", |b| {
+        b.iter(|| {
+            let mut storage = raw.clone();
+            storage.optimise();
+            std::hint::black_box(storage)
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_storage,
+);
+criterion_main!(benches);
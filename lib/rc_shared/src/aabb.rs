@@ -1,6 +1,7 @@
 use bevy::color::palettes::basic::RED;
 use bevy::prelude::{Gizmos, Transform};
 use crate::block::BlockStates;
+use crate::block::blocks::water::WaterBlock;
 use crate::chunk::ChunkSystemTrait;
 use crate::helpers::{global_to_local_position, to_bevy_vec3};
 use nalgebra::Vector3;
@@ -157,6 +158,40 @@ impl Aabb {
         matches
     }
 
+    /// How much of this `Aabb`'s height is under water, from 0 when dry to 1 when fully submerged
+    pub fn fluid_submersion(&self, chunks: &dyn ChunkSystemTrait, blocks: &BlockStates) -> f32 {
+        let water_at = |pos: Vector3<i32>| {
+            let (chunk_pos, block_pos) = global_to_local_position(pos);
+            let block_id = chunks.get_raw_chunk(&chunk_pos)?.get(block_pos);
+            WaterBlock::from_block(&blocks.get_block_from_id(block_id))
+        };
+
+        let bottom = self.bottom_left.y;
+        let top = self.bottom_left.y + self.size.y;
+        let mut surface = bottom;
+
+        for x in f32::floor(self.bottom_left.x) as i32..f32::ceil(self.bottom_left.x + self.size.x) as i32 {
+            for y in f32::floor(bottom) as i32..f32::ceil(top) as i32 {
+                for z in f32::floor(self.bottom_left.z) as i32..f32::ceil(self.bottom_left.z + self.size.z) as i32 {
+                    let Some(water) = water_at(Vector3::new(x, y, z)) else {
+                        continue;
+                    };
+
+                    // Water with more water above it is filled to the top
+                    let height = if water_at(Vector3::new(x, y + 1, z)).is_some() {
+                        1.0
+                    } else {
+                        water.height()
+                    };
+
+                    surface = max(surface, y as f32 + height);
+                }
+            }
+        }
+
+        (min(surface, top) - bottom) / self.size.y
+    }
+
     /// Returns the maximum movement allowed before collision with another `Aabb`
     pub fn try_move(&self, mut movement: Vector3<f32>, other: &Aabb) -> Vector3<f32> {
         if movement.x > 0.0 {
@@ -17,6 +17,7 @@ impl BlockImpl for AirBlock {
                 collision_boxes: vec![],
                 bounding_boxes: vec![],
                 emission: [0; 4],
+                light_filter: [0; 4],
             }
         ]
     }
@@ -0,0 +1,64 @@
+use nalgebra::Vector3;
+use crate::aabb::Aabb;
+use crate::atlas::{TEXTURE_ATLAS, TextureAtlasIndex};
+use crate::block::BlockId;
+use crate::block::entity::{BlockEntityData, InventoryEntity};
+use crate::block::types::{VisualBlock, LootTableEntry};
+use crate::block::blocks::{BlockImpl, get_full_block_faces};
+
+/// How many item stacks a chest holds
+pub const CHEST_SLOTS: usize = 27;
+
+pub struct ChestBlock;
+
+impl BlockImpl for ChestBlock {
+    const IDENTIFIER: &'static str = "mcv3::block::Chest";
+
+    fn get_variants() -> Vec<VisualBlock> {
+        let texture_y = *TEXTURE_ATLAS.get().index.get("game/wood_top").unwrap_or(&TextureAtlasIndex::default());
+
+        let mut faces = get_full_block_faces("game/wood_log");
+        faces[0].texture = texture_y;
+        faces[1].texture = texture_y;
+
+        vec![
+            VisualBlock {
+                translucent: false,
+                full: true,
+                draw_betweens: false,
+                faces,
+                collision_boxes: vec![
+                    Aabb::new(
+                        Vector3::new(0.0, 0.0, 0.0),
+                        Vector3::new(1.0, 1.0, 1.0),
+                    )
+                ],
+                bounding_boxes: vec![
+                    Aabb::new(
+                        Vector3::new(0.0, 0.0, 0.0),
+                        Vector3::new(1.0, 1.0, 1.0),
+                    )
+                ],
+                emission: [0; 4],
+                light_filter: [0; 4],
+            }
+        ]
+    }
+
+    fn parse_block_state(id: BlockId) -> Self {
+        Self
+    }
+
+    fn get_loot(&self) -> Vec<LootTableEntry> {
+        vec![
+            LootTableEntry {
+                chance: 1.0,
+                item_identifier: "mcv3::ChestItem".to_string(),
+            }
+        ]
+    }
+
+    fn create_entity() -> Option<BlockEntityData> {
+        Some(BlockEntityData::new(&InventoryEntity::new(CHEST_SLOTS)))
+    }
+}
@@ -5,7 +5,12 @@ use crate::block::BlockId;
 use crate::block::face::Face;
 use crate::block::types::{VisualBlock, LootTableEntry};
 use crate::block::blocks::{BlockImpl};
+use crate::block::blocks::dirt::DirtBlock;
+use crate::block::tick::BlockTickContext;
+use crate::chunk::GlobalBlockPosition;
+use crate::light::PLANT_GROWTH_LIGHT;
 use crate::viewable_direction::ViewableDirectionBitMap;
+use rand::Rng;
 
 pub struct GrassBlock;
 
@@ -97,6 +102,7 @@ impl BlockImpl for GrassBlock {
                     )
                 ],
                 emission: [0; 4],
+                light_filter: [0; 4],
             }
         ]
     }
@@ -113,4 +119,38 @@ impl BlockImpl for GrassBlock {
             }
         ]
     }
+
+    const RANDOM_TICKS: bool = true;
+
+    /// Dies when covered, otherwise spreads to a nearby dirt block that isn't
+    fn on_random_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        let Some(dirt) = world.block_id_of(DirtBlock::IDENTIFIER) else {
+            return;
+        };
+
+        if is_covered(world, pos) {
+            world.set_block(pos, dirt);
+            return;
+        }
+
+        let rng = world.rng();
+        let target = pos + Vector3::new(rng.gen_range(-1..=1), rng.gen_range(-3..=1), rng.gen_range(-1..=1));
+
+        if world.get_block_id(target) == Some(dirt)
+            && !is_covered(world, target)
+            && world.is_lit(target + Vector3::new(0, 1, 0), PLANT_GROWTH_LIGHT)
+        {
+            world.set_block(target, world.block_id_of(Self::IDENTIFIER).unwrap());
+        }
+    }
+}
+
+/// Whether the block above `pos` is solid, blocking out the light grass needs
+fn is_covered(world: &dyn BlockTickContext, pos: GlobalBlockPosition) -> bool {
+    world
+        .get_block(pos + Vector3::new(0, 1, 0))
+        .is_some_and(|block| {
+            let visual = block.draw();
+            visual.full && !visual.translucent
+        })
 }
\ No newline at end of file
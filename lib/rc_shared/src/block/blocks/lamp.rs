@@ -97,6 +97,7 @@ impl BlockImpl for LampBlock {
                     )
                 ],
                 emission: [255, 180, 80, 16],
+                light_filter: [0; 4],
             }
         ]
     }
@@ -5,7 +5,20 @@ use crate::block::BlockId;
 use crate::block::face::Face;
 use crate::block::types::VisualBlock;
 use crate::block::blocks::{BlockImpl, get_full_block_faces};
-use crate::viewable_direction::ViewableDirectionBitMap;
+use crate::block::blocks::wood_log::WoodLogBlock;
+use crate::block::tick::BlockTickContext;
+use crate::block::types::LootTableEntry;
+use crate::chunk::GlobalBlockPosition;
+use crate::viewable_direction::{BLOCK_SIDES, ViewableDirectionBitMap};
+use rand::Rng;
+use std::collections::{HashSet, VecDeque};
+
+/// How many leaves away a log can be while still holding them up
+const LOG_DISTANCE: usize = 4;
+
+/// The fewest and most ticks leaves wait after a change nearby before checking if they should decay.
+/// The delay is random so cut trees lose their leaves gradually.
+const DECAY_DELAY: std::ops::Range<u64> = 20..100;
 
 pub struct LeavesBlock;
 
@@ -95,6 +108,7 @@ impl BlockImpl for LeavesBlock {
                     )
                 ],
                 emission: [0; 4],
+                light_filter: [2, 1, 2, 2],
             }
         ]
     }
@@ -102,4 +116,58 @@ impl BlockImpl for LeavesBlock {
     fn parse_block_state(id: BlockId) -> Self {
         Self
     }
+
+    fn get_loot(&self) -> Vec<LootTableEntry> {
+        vec![
+            LootTableEntry {
+                chance: 0.1,
+                item_identifier: "mcv3::SaplingItem".to_string(),
+            }
+        ]
+    }
+
+    const RANDOM_TICKS: bool = true;
+
+    fn on_random_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        decay(pos, world);
+    }
+
+    fn on_scheduled_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        decay(pos, world);
+    }
+
+    fn on_poke(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        let delay = world.rng().gen_range(DECAY_DELAY);
+        world.schedule_tick(pos, delay);
+    }
+}
+
+/// Removes the leaves at `pos` if there's no log within `LOG_DISTANCE` through other leaves
+fn decay(pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+    let mut visited = HashSet::from([pos]);
+    let mut queue = VecDeque::from([(pos, 0)]);
+
+    while let Some((current, distance)) = queue.pop_front() {
+        for side in &BLOCK_SIDES {
+            let next = current + side;
+            if !visited.insert(next) {
+                continue;
+            }
+
+            let Some(block) = world.get_block(next) else {
+                // Unloaded chunks might have a log in them
+                return;
+            };
+
+            if block.get_identifier() == WoodLogBlock::IDENTIFIER {
+                return;
+            }
+
+            if block.get_identifier() == LeavesBlock::IDENTIFIER && distance + 1 < LOG_DISTANCE {
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    world.destroy_block(pos);
 }
\ No newline at end of file
@@ -74,6 +74,7 @@ impl BlockImpl for LongGrassBlock {
                     )
                 ],
                 emission: [0; 4],
+                light_filter: [0; 4],
             }
         ]
     }
@@ -10,21 +10,45 @@ pub(crate) mod sand;
 pub(crate) mod plaster;
 pub(crate) mod water;
 pub(crate) mod pipe;
+pub(crate) mod ruby_ore;
+pub(crate) mod chest;
+pub(crate) mod sapling;
 
 use nalgebra::Vector3;
 use crate::atlas::{TEXTURE_ATLAS, TextureAtlasIndex};
 use crate::block::BlockId;
+use crate::block::entity::BlockEntityData;
 use crate::block::face::Face;
+use crate::block::properties::BlockProperty;
+use crate::block::tick::BlockTickContext;
+use crate::chunk::GlobalBlockPosition;
 use crate::block::types::{VisualBlock, LootTableEntry};
 use crate::viewable_direction::ViewableDirectionBitMap;
 
 pub trait BlockImpl {
     const IDENTIFIER: &'static str;
+    /// The properties making up the block's state. When there are any, the block has one variant
+    /// for every combination of their values, in the order given by `BlockState`.
+    const PROPERTIES: &'static [BlockProperty] = &[];
     fn get_variants() -> Vec<VisualBlock>;
     fn parse_block_state(id: BlockId) -> Self;
     fn draw(&self) -> VisualBlock { Self::get_variants().pop().unwrap() }
     fn on_destroy(&self) {}
     fn get_loot(&self) -> Vec<LootTableEntry> { vec![] }
+    /// The block entity given to the block when it's placed, which is dropped when it's destroyed
+    fn create_entity() -> Option<BlockEntityData> { None }
+    /// Whether `on_random_tick` does anything, so the server can skip the block cheaply
+    const RANDOM_TICKS: bool = false;
+    /// Whether the block is a fluid, which can't be broken
+    const FLUID: bool = false;
+    /// Whether placing a block where this one is replaces it
+    const REPLACEABLE: bool = Self::FLUID;
+    /// Called for blocks picked at random from loaded chunks, for slow changes like plants growing
+    fn on_random_tick(&self, _pos: GlobalBlockPosition, _world: &mut dyn BlockTickContext) {}
+    /// Called when a tick scheduled with `BlockTickContext::schedule_tick` is due
+    fn on_scheduled_tick(&self, _pos: GlobalBlockPosition, _world: &mut dyn BlockTickContext) {}
+    /// Called when a block next to this one changes
+    fn on_poke(&self, _pos: GlobalBlockPosition, _world: &mut dyn BlockTickContext) {}
 }
 
 fn get_full_block_faces(
@@ -3,48 +3,67 @@ use crate::aabb::Aabb;
 use crate::atlas::{TEXTURE_ATLAS, TextureAtlasIndex};
 use crate::block::BlockId;
 use crate::block::face::Face;
+use crate::block::properties::{BlockProperty, BlockState, SIDE_PROPERTIES, variant_count};
 use crate::block::types::VisualBlock;
 use crate::block::blocks::{BlockImpl};
 use crate::viewable_direction::ViewableDirectionBitMap;
 
-pub struct PipeBlock;
+/// A pipe, connected to its neighbours on the sides given by its properties
+pub struct PipeBlock {
+    sides: [bool; 6],
+}
 
 impl BlockImpl for PipeBlock {
     const IDENTIFIER: &'static str = "mcv3::block::Pipe";
+    const PROPERTIES: &'static [BlockProperty] = &[
+        BlockProperty::Bool(SIDE_PROPERTIES[0]),
+        BlockProperty::Bool(SIDE_PROPERTIES[1]),
+        BlockProperty::Bool(SIDE_PROPERTIES[2]),
+        BlockProperty::Bool(SIDE_PROPERTIES[3]),
+        BlockProperty::Bool(SIDE_PROPERTIES[4]),
+        BlockProperty::Bool(SIDE_PROPERTIES[5]),
+    ];
 
     fn get_variants() -> Vec<VisualBlock> {
-        pipe()
-            .into_iter()
-            .map(|face| {
-                VisualBlock {
-                    translucent: true,
-                    full: true,
-                    draw_betweens: false,
-                    faces: face,
-                    collision_boxes: vec![
-                        Aabb::new(
-                            Vector3::new(0.25, 0.25, 0.0),
-                            Vector3::new(0.5, 0.5, 1.0),
-                        )
-                    ],
-                    bounding_boxes: vec![
-                        Aabb::new(
-                            Vector3::new(0.25, 0.25, 0.0),
-                            Vector3::new(0.5, 0.5, 1.0),
-                        )
-                    ],
-                    emission: [0; 4],
-                }
-            })
+        (0..variant_count(Self::PROPERTIES))
+            .map(|id| Self::parse_block_state(id).draw())
             .collect::<Vec<VisualBlock>>()
     }
 
     fn parse_block_state(id: BlockId) -> Self {
-        Self
+        let state = BlockState::new(Self::PROPERTIES, id);
+
+        Self {
+            sides: SIDE_PROPERTIES.map(|side| state.get_bool(side)),
+        }
+    }
+
+    fn draw(&self) -> VisualBlock {
+        VisualBlock {
+            translucent: true,
+            full: true,
+            draw_betweens: false,
+            faces: pipe(self.sides),
+            collision_boxes: vec![
+                Aabb::new(
+                    Vector3::new(0.25, 0.25, 0.0),
+                    Vector3::new(0.5, 0.5, 1.0),
+                )
+            ],
+            bounding_boxes: vec![
+                Aabb::new(
+                    Vector3::new(0.25, 0.25, 0.0),
+                    Vector3::new(0.5, 0.5, 1.0),
+                )
+            ],
+            emission: [0; 4],
+            light_filter: [0; 4],
+        }
     }
 }
 
-pub fn pipe() -> Vec<Vec<Face>> {
+/// The faces of a pipe connected on `sides_active`, indexed like `BLOCK_SIDES`
+pub fn pipe(sides_active: [bool; 6]) -> Vec<Face> {
     let texture = *TEXTURE_ATLAS.get().index.get("game/pipe_end").unwrap_or(&TextureAtlasIndex::default());
 
     let center_piece = vec![
@@ -150,34 +169,16 @@ pub fn pipe() -> Vec<Vec<Face>> {
         },
     ];
 
-    let mut states = Vec::new();
-
-    // Toggle each side
-    for i in 0..(2_i32.pow(6)) {
-        let sides_active = [
-            i & 0b100000 > 0,
-            i & 0b010000 > 0,
-            i & 0b001000 > 0,
-            i & 0b000100 > 0,
-            i & 0b000010 > 0,
-            i & 0b000001 > 0,
-        ];
-
-        let mut working_copy = center_piece.clone();
+    let mut faces = center_piece;
+    let sides = data(texture);
 
-        for x in 0..6 {
-            if sides_active[x] == false {
-                continue;
-            }
-
-            working_copy
-                .append(&mut data(texture).get_mut(x).unwrap().to_vec());
+    for x in 0..6 {
+        if sides_active[x] {
+            faces.extend_from_slice(&sides[x]);
         }
-
-        states.push(working_copy);
     }
 
-    states
+    faces
 }
 
 fn data<'a>(texture: TextureAtlasIndex) -> Vec<[Face; 8]> {
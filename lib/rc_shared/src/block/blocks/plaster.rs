@@ -29,6 +29,7 @@ impl BlockImpl for PlasterBlock {
                     )
                 ],
                 emission: [0; 4],
+                light_filter: [0; 4],
             }
         ]
     }
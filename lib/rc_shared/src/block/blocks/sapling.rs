@@ -0,0 +1,153 @@
+use nalgebra::Vector3;
+use crate::aabb::Aabb;
+use crate::atlas::{TEXTURE_ATLAS, TextureAtlasIndex};
+use crate::block::BlockId;
+use crate::block::face::Face;
+use crate::block::blocks::BlockImpl;
+use crate::block::blocks::dirt::DirtBlock;
+use crate::block::blocks::grass::GrassBlock;
+use crate::block::blocks::leaves::LeavesBlock;
+use crate::block::blocks::wood_log::WoodLogBlock;
+use crate::block::properties::{BlockProperty, BlockState, PropertyValue};
+use crate::block::tick::BlockTickContext;
+use crate::block::types::{VisualBlock, LootTableEntry};
+use crate::chunk::GlobalBlockPosition;
+use crate::light::PLANT_GROWTH_LIGHT;
+use crate::viewable_direction::ViewableDirectionBitMap;
+
+/// How many blocks of log a grown tree has
+const TRUNK_HEIGHT: i32 = 4;
+
+/// A young tree, which grows through its stages on random ticks while lit before becoming a full tree
+pub struct SaplingBlock {
+    stage: i32,
+}
+
+impl BlockImpl for SaplingBlock {
+    const IDENTIFIER: &'static str = "mcv3::block::Sapling";
+    const PROPERTIES: &'static [BlockProperty] = &[BlockProperty::Int("stage", 0, 1)];
+
+    fn get_variants() -> Vec<VisualBlock> {
+        (0..2).map(|stage| SaplingBlock { stage }.draw()).collect()
+    }
+
+    fn parse_block_state(id: BlockId) -> Self {
+        let stage = match BlockState::new(Self::PROPERTIES, id).get("stage") {
+            Some(PropertyValue::Int(stage)) => stage,
+            _ => 0,
+        };
+
+        Self { stage }
+    }
+
+    fn draw(&self) -> VisualBlock {
+        let texture = *TEXTURE_ATLAS.get().index.get("game/tree_leaves").unwrap_or(&TextureAtlasIndex::default());
+
+        // Two crossed planes, like long grass
+        let plane = |top_left: Vector3<f32>, top_right: Vector3<f32>, bottom_left: Vector3<f32>| Face {
+            top_left,
+            top_right,
+            bottom_left,
+            edge: false,
+            direction: ViewableDirectionBitMap::Top,
+            wind_strengths: Some([0.0, 0.5, 0.5, 0.0]),
+            normal: ViewableDirectionBitMap::Top.to_normal(),
+            texture
+        };
+
+        VisualBlock {
+            translucent: true,
+            full: false,
+            draw_betweens: true,
+            faces: vec![
+                plane(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Vector3::new(1.0, 0.0, 1.0)),
+                plane(Vector3::new(1.0, 0.0, 1.0), Vector3::new(1.0, 1.0, 1.0), Vector3::new(0.0, 0.0, 0.0)),
+                plane(Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 1.0, 1.0), Vector3::new(1.0, 0.0, 0.0)),
+                plane(Vector3::new(1.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)),
+            ],
+            collision_boxes: vec![],
+            bounding_boxes: vec![
+                Aabb::new(
+                    Vector3::new(0.2, 0.0, 0.2),
+                    Vector3::new(0.6, 0.8, 0.6),
+                )
+            ],
+            emission: [0; 4],
+            light_filter: [0; 4],
+        }
+    }
+
+    fn get_loot(&self) -> Vec<LootTableEntry> {
+        vec![
+            LootTableEntry {
+                chance: 1.0,
+                item_identifier: "mcv3::SaplingItem".to_string(),
+            }
+        ]
+    }
+
+    const RANDOM_TICKS: bool = true;
+
+    fn on_random_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        if !world.is_lit(pos, PLANT_GROWTH_LIGHT) {
+            return;
+        }
+
+        if self.stage == 0 {
+            let grown = world.get_block(pos).and_then(|block| block.with_property("stage", 1));
+            if let Some(grown) = grown {
+                world.set_block(pos, grown.get_id());
+            }
+            return;
+        }
+
+        grow_tree(pos, world);
+    }
+
+    /// Saplings only stay planted on soil
+    fn on_poke(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        let below = pos - Vector3::new(0, 1, 0);
+
+        if world.get_block_id(below).is_some()
+            && !world.is_block(below, DirtBlock::IDENTIFIER)
+            && !world.is_block(below, GrassBlock::IDENTIFIER)
+        {
+            world.destroy_block(pos);
+        }
+    }
+}
+
+/// Replaces the sapling at `pos` with a tree, if there's room for its trunk
+fn grow_tree(pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+    let air = 0;
+    let (Some(log), Some(leaves)) = (
+        world.block_id_of(WoodLogBlock::IDENTIFIER),
+        world.block_id_of(LeavesBlock::IDENTIFIER),
+    ) else {
+        return;
+    };
+
+    if (1..=TRUNK_HEIGHT).any(|y| world.get_block_id(pos + Vector3::new(0, y, 0)) != Some(air)) {
+        return;
+    }
+
+    for y in 0..TRUNK_HEIGHT {
+        world.set_block(pos + Vector3::new(0, y, 0), log);
+    }
+
+    // Two layers of leaves around the top of the trunk, with one more on top
+    let mut canopy = vec![Vector3::new(0, TRUNK_HEIGHT + 1, 0)];
+    for y in [TRUNK_HEIGHT - 1, TRUNK_HEIGHT] {
+        for x in -1..=1 {
+            for z in -1..=1 {
+                canopy.push(Vector3::new(x, y, z));
+            }
+        }
+    }
+
+    for offset in canopy {
+        if world.get_block_id(pos + offset) == Some(air) {
+            world.set_block(pos + offset, leaves);
+        }
+    }
+}
@@ -29,6 +29,7 @@ impl BlockImpl for StoneBlock {
                     )
                 ],
                 emission: [0; 4],
+                light_filter: [0; 4],
             }
         ]
     }
@@ -4,98 +4,265 @@ use crate::aabb::Aabb;
 use crate::atlas::TEXTURE_ATLAS;
 use crate::block::BlockId;
 use crate::block::face::Face;
+use crate::block::properties::{BlockProperty, BlockState, PropertyValue};
+use crate::block::tick::BlockTickContext;
 use crate::block::types::VisualBlock;
-use crate::block::blocks::{BlockImpl, get_full_block_faces};
-use crate::viewable_direction::ViewableDirectionBitMap;
+use crate::block::blocks::BlockImpl;
+use crate::block::WorldBlock;
+use crate::chunk::GlobalBlockPosition;
+use crate::viewable_direction::{BLOCK_SIDES, ViewableDirectionBitMap};
 
-pub struct WaterBlock;
+/// The weakest flowing water. Water spreads sideways one level weaker each block, so this is how
+/// far it reaches from a source.
+pub const MAX_WATER_LEVEL: i32 = 7;
+
+/// The height of a full block of water
+const FULL_HEIGHT: f32 = 0.9;
+
+/// Ticks between water moving one block
+const FLOW_DELAY: u64 = 5;
+
+/// Level 0 is a source block, which stays put. Higher levels are water flowing away from a source,
+/// getting shallower as they go. Falling water is full height, and spreads as if it were a source.
+pub struct WaterBlock {
+    level: i32,
+    falling: bool,
+}
+
+impl WaterBlock {
+    pub fn from_block(block: &WorldBlock) -> Option<WaterBlock> {
+        if block.get_identifier() != Self::IDENTIFIER {
+            return None;
+        }
+
+        Some(Self::parse_block_state(block.get_state().variant()))
+    }
+
+    pub fn is_source(&self) -> bool {
+        self.level == 0 && !self.falling
+    }
+
+    /// How high the water's surface is within the block
+    pub fn height(&self) -> f32 {
+        if self.falling {
+            FULL_HEIGHT
+        } else {
+            FULL_HEIGHT * (MAX_WATER_LEVEL + 1 - self.level) as f32 / (MAX_WATER_LEVEL + 1) as f32
+        }
+    }
+
+    /// The level water flowing out of this block sideways has
+    fn spread_level(&self) -> i32 {
+        if self.falling {
+            1
+        } else {
+            self.level + 1
+        }
+    }
+
+    fn block_id(world: &dyn BlockTickContext, level: i32, falling: bool) -> Option<BlockId> {
+        let state = BlockState::default(Self::PROPERTIES)
+            .with("level", level)?
+            .with("falling", falling)?;
+
+        Some(world.block_id_of(Self::IDENTIFIER)? + state.variant())
+    }
+
+    /// The water at `pos`, if there is any
+    fn at(world: &dyn BlockTickContext, pos: GlobalBlockPosition) -> Option<WaterBlock> {
+        WaterBlock::from_block(&world.get_block(pos)?)
+    }
+
+    /// What flowing water at `pos` should be, given the water around it. None means it has nothing
+    /// feeding it so should dry up.
+    fn expected(world: &dyn BlockTickContext, pos: GlobalBlockPosition) -> Option<WaterBlock> {
+        if Self::at(world, pos + Vector3::new(0, 1, 0)).is_some() {
+            return Some(WaterBlock { level: 1, falling: true });
+        }
+
+        BLOCK_SIDES[2..]
+            .iter()
+            .filter_map(|side| Self::at(world, pos + side))
+            .map(|neighbour| neighbour.spread_level())
+            .filter(|level| *level <= MAX_WATER_LEVEL)
+            .min()
+            .map(|level| WaterBlock { level, falling: false })
+    }
+
+    /// Fills `pos` with water, if it's empty or has weaker water in it
+    fn flow_into(world: &mut dyn BlockTickContext, pos: GlobalBlockPosition, level: i32, falling: bool) {
+        let replaceable = match world.get_block_id(pos) {
+            Some(0) => true,
+            Some(_) => Self::at(world, pos).is_some_and(|water| !water.is_source() && !water.falling && water.level > level),
+            None => false,
+        };
+
+        if !replaceable {
+            return;
+        }
+
+        if let Some(id) = Self::block_id(world, level, falling) {
+            world.set_block(pos, id);
+            world.schedule_tick(pos, FLOW_DELAY);
+        }
+    }
+}
 
 impl BlockImpl for WaterBlock {
     const IDENTIFIER: &'static str = "mcv3::block::Water";
+    const PROPERTIES: &'static [BlockProperty] = &[
+        BlockProperty::Int("level", 0, MAX_WATER_LEVEL),
+        BlockProperty::Bool("falling"),
+    ];
+    const FLUID: bool = true;
 
     fn get_variants() -> Vec<VisualBlock> {
+        (0..=MAX_WATER_LEVEL)
+            .flat_map(|level| [false, true].map(|falling| WaterBlock { level, falling }.draw()))
+            .collect()
+    }
+
+    fn parse_block_state(id: BlockId) -> Self {
+        let state = BlockState::new(Self::PROPERTIES, id);
+
+        let level = match state.get("level") {
+            Some(PropertyValue::Int(level)) => level,
+            _ => 0,
+        };
+
+        Self {
+            level,
+            falling: state.get_bool("falling"),
+        }
+    }
+
+    fn draw(&self) -> VisualBlock {
         let texture = *TEXTURE_ATLAS.get().index.get("game/water").unwrap_or(&TextureAtlasIndex::default());
+        let height = self.height();
 
-        vec![
-            VisualBlock {
-                translucent: true,
-                full: false,
-                draw_betweens: false,
-                faces: vec![
-                    Face {
-                        top_left: Vector3::new(0.0, 0.9, 0.0),
-                        top_right: Vector3::new(1.0, 0.9, 0.0),
-                        bottom_left: Vector3::new(0.0, 0.9, 1.0),
-                        edge: false,
-                        direction: ViewableDirectionBitMap::Top,
-                        wind_strengths: None,
-                        normal: ViewableDirectionBitMap::Top.to_normal(),
-                        texture
-                    },
-                    Face {
-                        top_left: Vector3::new(0.0, 0.0, 0.0),
-                        top_right: Vector3::new(0.0, 0.0, 1.0),
-                        bottom_left: Vector3::new(1.0, 0.0, 0.0),
-                        edge: false,
-                        direction: ViewableDirectionBitMap::Bottom,
-                        wind_strengths: None,
-                        normal: ViewableDirectionBitMap::Bottom.to_normal(),
-                        texture
-                    },
-                    Face {
-                        top_left: Vector3::new(0.0, 0.0, 0.0),
-                        top_right: Vector3::new(0.0, 0.9, 0.0),
-                        bottom_left: Vector3::new(0.0, 0.0, 1.0),
-                        edge: false,
-                        direction: ViewableDirectionBitMap::Left,
-                        wind_strengths: None,
-                        normal: ViewableDirectionBitMap::Left.to_normal(),
-                        texture
-                    },
-                    Face {
-                        top_left: Vector3::new(1.0, 0.0, 1.0),
-                        top_right: Vector3::new(1.0, 0.9, 1.0),
-                        bottom_left: Vector3::new(1.0, 0.0, 0.0),
-                        edge: false,
-                        direction: ViewableDirectionBitMap::Right,
-                        wind_strengths: None,
-                        normal: ViewableDirectionBitMap::Right.to_normal(),
-                        texture
-                    },
-                    Face {
-                        top_left: Vector3::new(1.0, 0.0, 0.0),
-                        top_right: Vector3::new(1.0, 0.9, 1.0),
-                        bottom_left: Vector3::new(0.0, 0.0, 0.0),
-                        edge: false,
-                        direction: ViewableDirectionBitMap::Front,
-                        wind_strengths: None,
-                        normal: ViewableDirectionBitMap::Front.to_normal(),
-                        texture
-                    },
-                    Face {
-                        top_left: Vector3::new(0.0, 0.0, 1.0),
-                        top_right: Vector3::new(0.0, 0.9, 1.0),
-                        bottom_left: Vector3::new(1.0, 0.0, 1.0),
-                        edge: false,
-                        direction: ViewableDirectionBitMap::Back,
-                        wind_strengths: None,
-                        normal: ViewableDirectionBitMap::Back.to_normal(),
-                        texture
-                    },
-                ],
-                collision_boxes: vec![],
-                bounding_boxes: vec![
-                    Aabb::new(
-                        Vector3::new(0.0, 0.0, 0.0),
-                        Vector3::new(1.0, 0.9, 1.0),
-                    )
-                ],
-                emission: [0; 4],
-            }
-        ]
+        VisualBlock {
+            translucent: true,
+            full: false,
+            draw_betweens: false,
+            faces: vec![
+                Face {
+                    top_left: Vector3::new(0.0, height, 0.0),
+                    top_right: Vector3::new(1.0, height, 0.0),
+                    bottom_left: Vector3::new(0.0, height, 1.0),
+                    edge: false,
+                    direction: ViewableDirectionBitMap::Top,
+                    wind_strengths: None,
+                    normal: ViewableDirectionBitMap::Top.to_normal(),
+                    texture
+                },
+                Face {
+                    top_left: Vector3::new(0.0, 0.0, 0.0),
+                    top_right: Vector3::new(0.0, 0.0, 1.0),
+                    bottom_left: Vector3::new(1.0, 0.0, 0.0),
+                    edge: false,
+                    direction: ViewableDirectionBitMap::Bottom,
+                    wind_strengths: None,
+                    normal: ViewableDirectionBitMap::Bottom.to_normal(),
+                    texture
+                },
+                Face {
+                    top_left: Vector3::new(0.0, 0.0, 0.0),
+                    top_right: Vector3::new(0.0, height, 0.0),
+                    bottom_left: Vector3::new(0.0, 0.0, 1.0),
+                    edge: false,
+                    direction: ViewableDirectionBitMap::Left,
+                    wind_strengths: None,
+                    normal: ViewableDirectionBitMap::Left.to_normal(),
+                    texture
+                },
+                Face {
+                    top_left: Vector3::new(1.0, 0.0, 1.0),
+                    top_right: Vector3::new(1.0, height, 1.0),
+                    bottom_left: Vector3::new(1.0, 0.0, 0.0),
+                    edge: false,
+                    direction: ViewableDirectionBitMap::Right,
+                    wind_strengths: None,
+                    normal: ViewableDirectionBitMap::Right.to_normal(),
+                    texture
+                },
+                Face {
+                    top_left: Vector3::new(1.0, 0.0, 0.0),
+                    top_right: Vector3::new(1.0, height, 0.0),
+                    bottom_left: Vector3::new(0.0, 0.0, 0.0),
+                    edge: false,
+                    direction: ViewableDirectionBitMap::Front,
+                    wind_strengths: None,
+                    normal: ViewableDirectionBitMap::Front.to_normal(),
+                    texture
+                },
+                Face {
+                    top_left: Vector3::new(0.0, 0.0, 1.0),
+                    top_right: Vector3::new(0.0, height, 1.0),
+                    bottom_left: Vector3::new(1.0, 0.0, 1.0),
+                    edge: false,
+                    direction: ViewableDirectionBitMap::Back,
+                    wind_strengths: None,
+                    normal: ViewableDirectionBitMap::Back.to_normal(),
+                    texture
+                },
+            ],
+            collision_boxes: vec![],
+            bounding_boxes: vec![
+                Aabb::new(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(1.0, height, 1.0),
+                )
+            ],
+            emission: [0; 4],
+            // Red is absorbed first, so deep water is dark and blue
+            light_filter: [3, 2, 1, 2],
+        }
     }
 
-    fn parse_block_state(id: BlockId) -> Self {
-        Self
+    fn on_poke(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        world.schedule_tick(pos, FLOW_DELAY);
+    }
+
+    /// Flowing water first settles to the level its neighbours give it, drying up if nothing feeds
+    /// it. Water then flows down if it can, otherwise out to the sides.
+    fn on_scheduled_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        if !self.is_source() {
+            match Self::expected(world, pos) {
+                None => {
+                    world.set_block(pos, 0);
+                    return;
+                }
+                Some(expected) if expected.level != self.level || expected.falling != self.falling => {
+                    if let Some(id) = Self::block_id(world, expected.level, expected.falling) {
+                        world.set_block(pos, id);
+                        world.schedule_tick(pos, FLOW_DELAY);
+                    }
+                    return;
+                }
+                Some(_) => {}
+            }
+        }
+
+        let below = pos - Vector3::new(0, 1, 0);
+        match world.get_block_id(below) {
+            // Waiting for the chunk below to load
+            None => return,
+            Some(0) => {
+                Self::flow_into(world, below, 1, true);
+                return;
+            }
+            // Flowing water below turns into falling water on its own tick, rather than this spreading on top of it
+            Some(_) if Self::at(world, below).is_some_and(|water| !water.is_source()) => return,
+            Some(_) => {}
+        }
+
+        let level = self.spread_level();
+        if level > MAX_WATER_LEVEL {
+            return;
+        }
+
+        for side in &BLOCK_SIDES[2..] {
+            Self::flow_into(world, pos + side, level, false);
+        }
     }
-}
\ No newline at end of file
+}
@@ -97,6 +97,7 @@ impl BlockImpl for WoodLogBlock {
                     )
                 ],
                 emission: [0; 4],
+                light_filter: [0; 4],
             }
         ]
     }
@@ -10,9 +10,16 @@ use crate::block::blocks::leaves::LeavesBlock;
 use crate::block::blocks::long_grass::LongGrassBlock;
 use crate::block::blocks::pipe::PipeBlock;
 use crate::block::blocks::plaster::PlasterBlock;
+use crate::block::blocks::ruby_ore::RubyOreBlock;
 use crate::block::blocks::sand::SandBlock;
 use crate::block::blocks::water::WaterBlock;
 use crate::block::blocks::wood_log::WoodLogBlock;
+use crate::block::blocks::chest::ChestBlock;
+use crate::block::blocks::sapling::SaplingBlock;
+use crate::block::entity::BlockEntityData;
+use crate::block::properties::BlockProperty;
+use crate::block::tick::BlockTickContext;
+use crate::chunk::GlobalBlockPosition;
 use crate::block::types::{VisualBlock, LootTableEntry};
 
 pub(crate) static BLOCK_DEFINITIONS: OnceLock<Vec<BlockDefinition>> = OnceLock::new();
@@ -31,26 +38,47 @@ pub(crate) fn set_blocks() {
         BlockDefinition::from_block_impl::<PipeBlock>(),
         BlockDefinition::from_block_impl::<PlasterBlock>(),
         BlockDefinition::from_block_impl::<WaterBlock>(),
+        BlockDefinition::from_block_impl::<RubyOreBlock>(),
+        BlockDefinition::from_block_impl::<ChestBlock>(),
+        BlockDefinition::from_block_impl::<SaplingBlock>(),
     ]).unwrap();
 }
 
 #[derive(Debug)]
 pub struct BlockDefinition {
     pub identifier: &'static str,
+    pub properties: &'static [BlockProperty],
     get_variants: fn() -> Vec<VisualBlock>,
     draw: fn(BlockId) -> VisualBlock,
     get_loot: fn(BlockId) -> Vec<LootTableEntry>,
     on_destroy: fn(BlockId),
+    create_entity: fn() -> Option<BlockEntityData>,
+    pub random_ticks: bool,
+    pub fluid: bool,
+    pub replaceable: bool,
+    pub(crate) on_random_tick: BlockTickFn,
+    pub(crate) on_scheduled_tick: BlockTickFn,
+    pub(crate) on_poke: BlockTickFn,
 }
 
+pub(crate) type BlockTickFn = fn(BlockId, GlobalBlockPosition, &mut dyn BlockTickContext);
+
 impl BlockDefinition {
     pub fn from_block_impl<T: BlockImpl>() -> Self {
         Self {
             identifier: T::IDENTIFIER,
+            properties: T::PROPERTIES,
             get_variants: T::get_variants,
             on_destroy: |uid| T::parse_block_state(uid).on_destroy(),
             draw: |uid| T::parse_block_state(uid).draw(),
-            get_loot: |uid| T::parse_block_state(uid).get_loot()
+            get_loot: |uid| T::parse_block_state(uid).get_loot(),
+            create_entity: T::create_entity,
+            random_ticks: T::RANDOM_TICKS,
+            fluid: T::FLUID,
+            replaceable: T::REPLACEABLE,
+            on_random_tick: |uid, pos, world| T::parse_block_state(uid).on_random_tick(pos, world),
+            on_scheduled_tick: |uid, pos, world| T::parse_block_state(uid).on_scheduled_tick(pos, world),
+            on_poke: |uid, pos, world| T::parse_block_state(uid).on_poke(pos, world),
         }
     }
 
@@ -65,4 +93,8 @@ impl BlockDefinition {
     pub fn get_loot(&self, uid: BlockId) -> Vec<LootTableEntry> {
         (self.get_loot)(uid)
     }
-}
\ No newline at end of file
+
+    pub fn create_entity(&self) -> Option<BlockEntityData> {
+        (self.create_entity)()
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use bevy::log::warn;
+use nalgebra::Vector3;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use crate::chunk::LocalBlockPosition;
+use crate::item::types::ItemStack;
+
+/// Data kept for a single placed block, such as a chest's contents or a machine's progress
+pub trait BlockEntity: Serialize + DeserializeOwned {
+    /// Identifies the type of entity in saves and packets
+    const IDENTIFIER: &'static str;
+}
+
+/// A block entity of any type. The entity is kept as JSON so one chunk can hold entities of
+/// different types, and so it can be sent with formats that need to know types up front.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockEntityData {
+    pub identifier: String,
+    data: String,
+}
+
+impl BlockEntityData {
+    pub fn new<T: BlockEntity>(entity: &T) -> BlockEntityData {
+        BlockEntityData {
+            identifier: T::IDENTIFIER.to_string(),
+            data: serde_json::to_string(entity).unwrap(),
+        }
+    }
+
+    pub fn is<T: BlockEntity>(&self) -> bool {
+        self.identifier == T::IDENTIFIER
+    }
+
+    /// Reads the entity as `T`, or None if it's a different type of entity
+    pub fn get<T: BlockEntity>(&self) -> Option<T> {
+        if !self.is::<T>() {
+            return None;
+        }
+
+        match serde_json::from_str(&self.data) {
+            Ok(entity) => Some(entity),
+            Err(e) => {
+                warn!("Block entity {} is corrupted: {:?}", self.identifier, e);
+                None
+            }
+        }
+    }
+
+    /// Replaces the stored entity, which must be of the same type
+    pub fn set<T: BlockEntity>(&mut self, entity: &T) {
+        debug_assert!(self.is::<T>(), "replacing {} with {}", self.identifier, T::IDENTIFIER);
+
+        self.data = serde_json::to_string(entity).unwrap();
+    }
+}
+
+/// The block entities of a chunk, by position within the chunk. Stored as a list so the keys
+/// don't need to be strings in every save format.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<(Vector3<u8>, BlockEntityData)>", into = "Vec<(Vector3<u8>, BlockEntityData)>")]
+pub struct ChunkBlockEntities {
+    entities: HashMap<Vector3<u8>, BlockEntityData>,
+}
+
+impl ChunkBlockEntities {
+    pub fn get(&self, pos: LocalBlockPosition) -> Option<&BlockEntityData> {
+        self.entities.get(&pos.cast::<u8>())
+    }
+
+    pub fn get_mut(&mut self, pos: LocalBlockPosition) -> Option<&mut BlockEntityData> {
+        self.entities.get_mut(&pos.cast::<u8>())
+    }
+
+    /// Sets or removes the entity at `pos`, returning the one it replaced
+    pub fn set(&mut self, pos: LocalBlockPosition, entity: Option<BlockEntityData>) -> Option<BlockEntityData> {
+        match entity {
+            Some(entity) => self.entities.insert(pos.cast::<u8>(), entity),
+            None => self.entities.remove(&pos.cast::<u8>()),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (LocalBlockPosition, &BlockEntityData)> {
+        self.entities.iter().map(|(pos, entity)| (pos.cast::<usize>(), entity))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl From<Vec<(Vector3<u8>, BlockEntityData)>> for ChunkBlockEntities {
+    fn from(entities: Vec<(Vector3<u8>, BlockEntityData)>) -> Self {
+        ChunkBlockEntities { entities: entities.into_iter().collect() }
+    }
+}
+
+impl From<ChunkBlockEntities> for Vec<(Vector3<u8>, BlockEntityData)> {
+    fn from(entities: ChunkBlockEntities) -> Self {
+        entities.entities.into_iter().collect()
+    }
+}
+
+/// Items held by a block, such as a chest
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct InventoryEntity {
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl InventoryEntity {
+    pub fn new(size: usize) -> InventoryEntity {
+        InventoryEntity { slots: vec![None; size] }
+    }
+
+    /// Whether `item` would fit, either with a matching stack or in an empty slot
+    pub fn can_fit(&self, item: &ItemStack) -> bool {
+        self.slots.iter().any(|slot| match slot {
+            Some(stack) => stack.item.identifier == item.item.identifier,
+            None => true,
+        })
+    }
+
+    /// Adds `item` to a matching stack, or an empty slot. Returns false if there was no space.
+    pub fn push_item(&mut self, item: ItemStack) -> bool {
+        if let Some(stack) = self
+            .slots
+            .iter_mut()
+            .flatten()
+            .find(|stack| stack.item.identifier == item.item.identifier)
+        {
+            stack.amount += item.amount;
+            return true;
+        }
+
+        if let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(item);
+            return true;
+        }
+
+        false
+    }
+
+    /// Takes up to `amount` items from the first stack
+    pub fn take_items(&mut self, amount: u32) -> Option<ItemStack> {
+        let slot = self.slots.iter_mut().find(|slot| slot.is_some())?;
+        let stack = slot.as_mut().unwrap();
+
+        if stack.amount <= amount {
+            return slot.take();
+        }
+
+        stack.amount -= amount;
+        Some(ItemStack::new(stack.item.clone(), amount))
+    }
+}
+
+impl BlockEntity for InventoryEntity {
+    const IDENTIFIER: &'static str = "mcv3::entity::Inventory";
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use serde::{Deserialize, Serialize};
+    use crate::block::entity::{BlockEntity, BlockEntityData, ChunkBlockEntities, InventoryEntity};
+    use crate::item::types::{ItemStack, ItemType};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct SignEntity {
+        text: String,
+    }
+
+    impl BlockEntity for SignEntity {
+        const IDENTIFIER: &'static str = "test::Sign";
+    }
+
+    #[test]
+    fn test_typed_access() {
+        let mut entity = BlockEntityData::new(&SignEntity { text: String::from("hello") });
+
+        assert_eq!(entity.get::<SignEntity>(), Some(SignEntity { text: String::from("hello") }));
+        assert_eq!(entity.get::<InventoryEntity>(), None);
+
+        entity.set(&SignEntity { text: String::from("bye") });
+        assert_eq!(entity.get::<SignEntity>().unwrap().text, "bye");
+    }
+
+    #[test]
+    fn test_chunk_entities_round_trip() {
+        let mut entities = ChunkBlockEntities::default();
+        entities.set(Vector3::new(1, 2, 3), Some(BlockEntityData::new(&InventoryEntity::new(4))));
+        entities.set(Vector3::new(15, 0, 15), Some(BlockEntityData::new(&SignEntity { text: String::new() })));
+
+        // JSON needs string keys, which positions aren't
+        let json = serde_json::to_string(&entities).unwrap();
+        assert_eq!(serde_json::from_str::<ChunkBlockEntities>(&json).unwrap(), entities);
+
+        let removed = entities.set(Vector3::new(1, 2, 3), None).unwrap();
+        assert_eq!(removed.get::<InventoryEntity>().unwrap().slots.len(), 4);
+        assert_eq!(entities.len(), 1);
+    }
+
+    fn item(identifier: &str, amount: u32) -> ItemStack {
+        ItemStack::new(
+            ItemType {
+                identifier: identifier.to_string(),
+                name: identifier.to_string(),
+                icon: String::new(),
+                block_definition_index: None,
+            },
+            amount,
+        )
+    }
+
+    #[test]
+    fn test_inventory_items() {
+        let mut inventory = InventoryEntity::new(2);
+
+        assert!(inventory.push_item(item("stone", 5)));
+        assert!(inventory.push_item(item("stone", 2)));
+        assert!(inventory.push_item(item("dirt", 1)));
+        assert!(!inventory.can_fit(&item("sand", 1)));
+        assert!(!inventory.push_item(item("sand", 1)));
+
+        assert_eq!(inventory.take_items(4), Some(item("stone", 4)));
+        assert_eq!(inventory.take_items(4), Some(item("stone", 3)));
+        assert_eq!(inventory.slots[0], None);
+        assert_eq!(inventory.take_items(4), Some(item("dirt", 1)));
+        assert_eq!(inventory.take_items(4), None);
+    }
+}
@@ -3,6 +3,10 @@ pub mod types;
 pub mod blocks;
 mod uid;
 pub mod definition;
+pub mod entity;
+pub mod palette;
+pub mod properties;
+pub mod tick;
 mod bench;
 
 use std::cell::{RefCell, SyncUnsafeCell, UnsafeCell};
@@ -17,6 +21,10 @@ use std::sync::OnceLock;
 use serde::{Deserialize, Serialize};
 use sparse_set::SparseSet;
 use crate::block::definition::{BLOCK_DEFINITIONS, BlockDefinition, set_blocks};
+use crate::block::entity::BlockEntityData;
+use crate::block::properties::{BlockState, PropertyValue, variant_count};
+use crate::block::tick::BlockTickContext;
+use crate::chunk::GlobalBlockPosition;
 
 pub struct BlockStatesPlugin;
 
@@ -68,6 +76,7 @@ impl BlockStates {
             WorldBlock {
                 block_definition_index: *index,
                 definition,
+                start_id: self.block_id[index.0],
                 block_id: *block_id
             }
         } else {
@@ -77,6 +86,7 @@ impl BlockStates {
             WorldBlock {
                 block_definition_index: BlockDefinitionIndex(0),
                 definition,
+                start_id: 0,
                 block_id: 0
             }
         }
@@ -88,6 +98,7 @@ impl BlockStates {
                 let block = WorldBlock {
                     block_definition_index: BlockDefinitionIndex(i),
                     definition,
+                    start_id: self.block_id.get(i).copied().unwrap_or(0),
                     block_id: 0
                 };
                 return Some((i, block));
@@ -132,6 +143,15 @@ impl BlockStates {
 
             let mut variants = block.get_variants_len();
 
+            if !block.properties.is_empty() {
+                assert_eq!(
+                    variants as u32,
+                    variant_count(block.properties),
+                    "{} must have a variant for every combination of its properties",
+                    block.identifier
+                );
+            }
+
             block_id += variants as u32;
 
             let mut indexes = (0..variants)
@@ -144,11 +164,28 @@ impl BlockStates {
     }
 }
 
+/// Block states shared between tests, as they can only be calculated once per process
+#[cfg(any(test, feature = "test-utils"))]
+pub fn test_block_states() -> &'static BlockStates {
+    use crate::atlas::{TextureAtlas, TEXTURE_ATLAS};
+
+    static BLOCK_STATES: OnceLock<BlockStates> = OnceLock::new();
+
+    BLOCK_STATES.get_or_init(|| {
+        TEXTURE_ATLAS.set(TextureAtlas::blank());
+        let mut block_states = BlockStates::new();
+        block_states.calculate_states();
+        block_states
+    })
+}
+
 /// A block in the context of the world
 /// Stores a block definition along with its block uid
 pub struct WorldBlock {
     block_definition_index: BlockDefinitionIndex,
     definition: &'static BlockDefinition,
+    /// The id of the definition's first variant
+    start_id: BlockId,
     block_id: BlockId
 }
 
@@ -158,10 +195,39 @@ impl WorldBlock {
         self.definition.identifier
     }
 
+    /// The id of this block variant over all blocks
+    #[inline]
+    pub fn get_id(&self) -> BlockId {
+        self.start_id + self.block_id
+    }
+
+    pub fn get_state(&self) -> BlockState {
+        BlockState::new(self.definition.properties, self.block_id)
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<PropertyValue> {
+        self.get_state().get(name)
+    }
+
+    /// The same block with one property changed, or None if the block has no such property
+    pub fn with_property(&self, name: &str, value: impl Into<PropertyValue>) -> Option<WorldBlock> {
+        Some(self.with_state(self.get_state().with(name, value)?))
+    }
+
+    /// The same block in another of its states
+    pub fn with_state(&self, state: BlockState) -> WorldBlock {
+        WorldBlock {
+            block_definition_index: self.block_definition_index,
+            definition: self.definition,
+            start_id: self.start_id,
+            block_id: state.variant(),
+        }
+    }
+
     #[inline]
     pub fn draw(&self) -> &VisualBlock {
         // Lookup global block index
-        let index = self.block_definition_index.0 + self.block_id as usize;
+        let index = self.get_id() as usize;
 
         unsafe {
             let mut cache = CACHE.get().as_ref().unwrap();
@@ -188,6 +254,63 @@ impl WorldBlock {
     pub fn get_loot(&self) -> Vec<LootTableEntry> {
         self.definition.get_loot(self.block_id)
     }
+
+    pub fn create_entity(&self) -> Option<BlockEntityData> {
+        self.definition.create_entity()
+    }
+
+    /// Whether random ticks do anything to this block
+    #[inline]
+    pub fn has_random_ticks(&self) -> bool {
+        self.definition.random_ticks
+    }
+
+    /// Whether the block is a fluid like water, which can't be broken
+    #[inline]
+    pub fn is_fluid(&self) -> bool {
+        self.definition.fluid
+    }
+
+    /// Whether placing a block where this one is replaces it
+    #[inline]
+    pub fn is_replaceable(&self) -> bool {
+        self.definition.replaceable
+    }
+
+    pub fn random_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        (self.definition.on_random_tick)(self.block_id, pos, world)
+    }
+
+    pub fn scheduled_tick(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        (self.definition.on_scheduled_tick)(self.block_id, pos, world)
+    }
+
+    pub fn poke(&self, pos: GlobalBlockPosition, world: &mut dyn BlockTickContext) {
+        (self.definition.on_poke)(self.block_id, pos, world)
+    }
 }
 
-pub static CACHE: SyncUnsafeCell<SparseSet<usize, VisualBlock>> = SyncUnsafeCell::new(SparseSet::new());
\ No newline at end of file
+pub static CACHE: SyncUnsafeCell<SparseSet<usize, VisualBlock>> = SyncUnsafeCell::new(SparseSet::new());
+
+#[cfg(test)]
+mod tests {
+    use crate::block::{test_block_states, BlockStates};
+
+    #[test]
+    fn test_draw_variants_cached_per_block() {
+        let block_states = test_block_states();
+
+        let (_, pipe) = block_states.get_by_identifier("mcv3::block::Pipe").unwrap();
+        let (chest_index, chest) = block_states.get_by_identifier("mcv3::block::Chest").unwrap();
+        let pipe_index = BlockStates::get_definition_index_by_identifier("mcv3::block::Pipe").unwrap();
+
+        // The pipe variant whose definition index plus variant is the chest's definition index
+        let variant = (chest_index - *pipe_index) as u32;
+        let pipe = block_states.get_block_from_id(pipe.get_id() + variant);
+        assert_eq!(pipe.get_identifier(), "mcv3::block::Pipe");
+        assert!(pipe.draw().translucent);
+
+        let chest = chest.draw();
+        assert!(!chest.translucent);
+    }
+}
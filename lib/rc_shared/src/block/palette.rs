@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use bevy::log::warn;
+use serde::{Deserialize, Serialize};
+use crate::block::{BlockDefinitionIndex, BlockId, BlockStates};
+use crate::block::definition::BLOCK_DEFINITIONS;
+
+/// The block ids a process assigned to each block, so ids written by another process or an older
+/// version of the block list can be translated into the ids used now
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct BlockPalette {
+    pub entries: Vec<BlockPaletteEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockPaletteEntry {
+    pub identifier: String,
+    /// The id of the block's first variant
+    pub start_id: BlockId,
+    /// How many variants the block has, which are given the ids following `start_id`
+    pub variants: u32,
+}
+
+impl BlockStates {
+    /// The ids of every block
+    pub fn palette(&self) -> BlockPalette {
+        self.palette_for(0..self.block_index.len() as BlockId)
+    }
+
+    /// The ids of only the blocks used by `ids`, which is much smaller for saving a single chunk
+    pub fn palette_for(&self, ids: impl IntoIterator<Item = BlockId>) -> BlockPalette {
+        let mut definitions = ids
+            .into_iter()
+            .filter_map(|id| self.get_definition_index_by_id(id))
+            .collect::<Vec<BlockDefinitionIndex>>();
+
+        definitions.sort_by_key(|index| index.0);
+        definitions.dedup();
+
+        let entries = definitions
+            .into_iter()
+            .map(|index| BlockPaletteEntry {
+                identifier: BLOCK_DEFINITIONS.get().unwrap()[index.0].identifier.to_string(),
+                start_id: self.block_id[index.0],
+                variants: self.variant_count(index),
+            })
+            .collect();
+
+        BlockPalette { entries }
+    }
+
+    fn variant_count(&self, index: BlockDefinitionIndex) -> u32 {
+        let end = self
+            .block_id
+            .get(index.0 + 1)
+            .copied()
+            .unwrap_or(self.block_index.len() as BlockId);
+
+        end - self.block_id[index.0]
+    }
+}
+
+/// Translates block ids from one palette into another, matching blocks by their identifier
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BlockIdRemap {
+    /// Ids that change, any id not listed stays the same
+    ids: HashMap<BlockId, BlockId>,
+}
+
+impl BlockIdRemap {
+    /// Blocks missing from `to` become air. Variants that no longer exist become the block's first variant.
+    pub fn new(from: &BlockPalette, to: &BlockPalette) -> BlockIdRemap {
+        let to_entries = to
+            .entries
+            .iter()
+            .map(|entry| (entry.identifier.as_str(), entry))
+            .collect::<HashMap<&str, &BlockPaletteEntry>>();
+
+        let mut ids = HashMap::new();
+
+        for entry in &from.entries {
+            let Some(to_entry) = to_entries.get(entry.identifier.as_str()) else {
+                warn!("Block {} no longer exists, replacing it with air", entry.identifier);
+
+                for variant in 0..entry.variants {
+                    ids.insert(entry.start_id + variant, 0);
+                }
+                continue;
+            };
+
+            if entry.variants > to_entry.variants {
+                warn!(
+                    "Block {} has {} variants instead of {}, using its default variant for the rest",
+                    entry.identifier, to_entry.variants, entry.variants
+                );
+            }
+
+            for variant in 0..entry.variants {
+                let new_variant = if variant < to_entry.variants { variant } else { 0 };
+                ids.insert(entry.start_id + variant, to_entry.start_id + new_variant);
+            }
+        }
+
+        ids.retain(|from, to| from != to);
+
+        BlockIdRemap { ids }
+    }
+
+    /// Whether every id is translated to itself, so nothing needs changing
+    pub fn is_identity(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, id: BlockId) -> BlockId {
+        self.ids.get(&id).copied().unwrap_or(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::palette::{BlockIdRemap, BlockPalette, BlockPaletteEntry};
+
+    fn palette(entries: &[(&str, u32, u32)]) -> BlockPalette {
+        BlockPalette {
+            entries: entries
+                .iter()
+                .map(|(identifier, start_id, variants)| BlockPaletteEntry {
+                    identifier: identifier.to_string(),
+                    start_id: *start_id,
+                    variants: *variants,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_remap_inserted_block() {
+        let old = palette(&[("air", 0, 1), ("stone", 1, 1), ("pipe", 2, 4), ("lamp", 6, 1)]);
+        // A new block inserted before the pipe shifts every id after it
+        let new = palette(&[("air", 0, 1), ("stone", 1, 1), ("sand", 2, 1), ("pipe", 3, 4), ("lamp", 7, 1)]);
+
+        let remap = BlockIdRemap::new(&old, &new);
+
+        assert!(!remap.is_identity());
+        assert_eq!(remap.get(0), 0);
+        assert_eq!(remap.get(1), 1);
+        assert_eq!(remap.get(2), 3);
+        assert_eq!(remap.get(5), 6);
+        assert_eq!(remap.get(6), 7);
+
+        assert!(BlockIdRemap::new(&new, &new).is_identity());
+    }
+
+    #[test]
+    fn test_remap_changed_blocks() {
+        let old = palette(&[("air", 0, 1), ("pipe", 1, 4), ("removed", 5, 2)]);
+        let new = palette(&[("air", 0, 1), ("pipe", 1, 2)]);
+
+        let remap = BlockIdRemap::new(&old, &new);
+
+        // Variants that still exist are kept
+        assert_eq!(remap.get(2), 2);
+        // Missing variants fall back to the first one
+        assert_eq!(remap.get(4), 1);
+        // Missing blocks become air
+        assert_eq!(remap.get(5), 0);
+        assert_eq!(remap.get(6), 0);
+    }
+}
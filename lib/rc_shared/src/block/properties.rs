@@ -0,0 +1,230 @@
+use crate::block::BlockId;
+use crate::viewable_direction::AxisAlignedDirection;
+
+/// The property names used for a block's connections on each side, indexed like `BLOCK_SIDES`
+pub const SIDE_PROPERTIES: [&str; 6] = ["up", "down", "west", "east", "north", "south"];
+
+/// Every direction, indexed like `BLOCK_SIDES`
+pub const DIRECTIONS: [AxisAlignedDirection; 6] = [
+    AxisAlignedDirection::Top,
+    AxisAlignedDirection::Bottom,
+    AxisAlignedDirection::Left,
+    AxisAlignedDirection::Right,
+    AxisAlignedDirection::Front,
+    AxisAlignedDirection::Back,
+];
+
+/// A named part of a block's state. A block's variants are every combination of its properties'
+/// values, so the variant ids are worked out from the properties rather than decoded by hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockProperty {
+    Bool(&'static str),
+    Enum(&'static str, &'static [&'static str]),
+    /// An integer from `min` to `max` inclusive
+    Int(&'static str, i32, i32),
+    Direction(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Enum(&'static str),
+    Int(i32),
+    Direction(AxisAlignedDirection),
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Bool(value)
+    }
+}
+
+impl From<&'static str> for PropertyValue {
+    fn from(value: &'static str) -> Self {
+        PropertyValue::Enum(value)
+    }
+}
+
+impl From<i32> for PropertyValue {
+    fn from(value: i32) -> Self {
+        PropertyValue::Int(value)
+    }
+}
+
+impl From<AxisAlignedDirection> for PropertyValue {
+    fn from(value: AxisAlignedDirection) -> Self {
+        PropertyValue::Direction(value)
+    }
+}
+
+impl BlockProperty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockProperty::Bool(name)
+            | BlockProperty::Enum(name, _)
+            | BlockProperty::Int(name, _, _)
+            | BlockProperty::Direction(name) => name,
+        }
+    }
+
+    /// How many values the property can take
+    pub fn value_count(&self) -> u32 {
+        match self {
+            BlockProperty::Bool(_) => 2,
+            BlockProperty::Enum(_, options) => options.len() as u32,
+            BlockProperty::Int(_, min, max) => (max - min + 1) as u32,
+            BlockProperty::Direction(_) => DIRECTIONS.len() as u32,
+        }
+    }
+
+    /// The index of `value` within this property, or None if it's the wrong type or out of range
+    pub fn encode(&self, value: PropertyValue) -> Option<u32> {
+        match (self, value) {
+            (BlockProperty::Bool(_), PropertyValue::Bool(value)) => Some(value as u32),
+            (BlockProperty::Enum(_, options), PropertyValue::Enum(value)) => {
+                options.iter().position(|option| *option == value).map(|i| i as u32)
+            }
+            (BlockProperty::Int(_, min, max), PropertyValue::Int(value)) => {
+                (*min..=*max).contains(&value).then(|| (value - min) as u32)
+            }
+            (BlockProperty::Direction(_), PropertyValue::Direction(value)) => Some(value as u32),
+            _ => None,
+        }
+    }
+
+    /// The value at `index` within this property
+    pub fn decode(&self, index: u32) -> PropertyValue {
+        match self {
+            BlockProperty::Bool(_) => PropertyValue::Bool(index != 0),
+            BlockProperty::Enum(_, options) => PropertyValue::Enum(options[index as usize]),
+            BlockProperty::Int(_, min, _) => PropertyValue::Int(min + index as i32),
+            BlockProperty::Direction(_) => PropertyValue::Direction(DIRECTIONS[index as usize]),
+        }
+    }
+}
+
+/// How many variants a block with `properties` has
+pub fn variant_count(properties: &[BlockProperty]) -> u32 {
+    properties.iter().map(|property| property.value_count()).product()
+}
+
+/// A block variant read through its block's properties. Variant ids are mixed radix numbers with
+/// one digit per property, the first property being the most significant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockState {
+    properties: &'static [BlockProperty],
+    variant: BlockId,
+}
+
+impl BlockState {
+    pub fn new(properties: &'static [BlockProperty], variant: BlockId) -> BlockState {
+        BlockState { properties, variant }
+    }
+
+    /// The state with every property at its first value
+    pub fn default(properties: &'static [BlockProperty]) -> BlockState {
+        BlockState::new(properties, 0)
+    }
+
+    pub fn variant(&self) -> BlockId {
+        self.variant
+    }
+
+    /// The place value of the property at `index` within the variant id
+    fn stride(&self, index: usize) -> u32 {
+        variant_count(&self.properties[index + 1..])
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.properties.iter().position(|property| property.name() == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<PropertyValue> {
+        let index = self.index_of(name)?;
+        let property = &self.properties[index];
+
+        Some(property.decode((self.variant / self.stride(index)) % property.value_count()))
+    }
+
+    /// Reads a bool property, treating a missing property as false
+    pub fn get_bool(&self, name: &str) -> bool {
+        self.get(name) == Some(PropertyValue::Bool(true))
+    }
+
+    /// This state with one property changed, or None if the block has no such property or the
+    /// value doesn't fit it
+    pub fn with(&self, name: &str, value: impl Into<PropertyValue>) -> Option<BlockState> {
+        let index = self.index_of(name)?;
+        let property = &self.properties[index];
+        let stride = self.stride(index);
+
+        let new = property.encode(value.into())?;
+        let old = (self.variant / stride) % property.value_count();
+
+        Some(BlockState::new(self.properties, self.variant - old * stride + new * stride))
+    }
+
+    /// Every property with its value in this state
+    pub fn values(&self) -> Vec<(&'static str, PropertyValue)> {
+        self.properties
+            .iter()
+            .map(|property| (property.name(), self.get(property.name()).unwrap()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::properties::{variant_count, BlockProperty, BlockState, PropertyValue};
+    use crate::viewable_direction::AxisAlignedDirection;
+
+    const PROPERTIES: &[BlockProperty] = &[
+        BlockProperty::Bool("lit"),
+        BlockProperty::Enum("colour", &["red", "green", "blue"]),
+        BlockProperty::Int("level", 1, 4),
+        BlockProperty::Direction("facing"),
+    ];
+
+    #[test]
+    fn test_variant_count() {
+        assert_eq!(variant_count(PROPERTIES), 2 * 3 * 4 * 6);
+        assert_eq!(variant_count(&[]), 1);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for variant in 0..variant_count(PROPERTIES) {
+            let state = BlockState::new(PROPERTIES, variant);
+
+            let mut rebuilt = BlockState::default(PROPERTIES);
+            for (name, value) in state.values() {
+                rebuilt = rebuilt.with(name, value).unwrap();
+            }
+
+            assert_eq!(rebuilt.variant(), variant);
+        }
+    }
+
+    #[test]
+    fn test_with_property() {
+        let state = BlockState::default(PROPERTIES)
+            .with("colour", "blue").unwrap()
+            .with("level", 3).unwrap()
+            .with("facing", AxisAlignedDirection::Front).unwrap();
+
+        assert!(!state.get_bool("lit"));
+        assert_eq!(state.get("colour"), Some(PropertyValue::Enum("blue")));
+        assert_eq!(state.get("level"), Some(PropertyValue::Int(3)));
+        assert_eq!(state.get("facing"), Some(PropertyValue::Direction(AxisAlignedDirection::Front)));
+
+        let lit = state.with("lit", true).unwrap();
+        assert!(lit.get_bool("lit"));
+        assert_eq!(lit.get("level"), Some(PropertyValue::Int(3)));
+
+        // Unknown properties and values that don't fit are rejected
+        assert_eq!(state.with("missing", true), None);
+        assert_eq!(state.with("colour", "purple"), None);
+        assert_eq!(state.with("level", 5), None);
+        assert_eq!(state.with("lit", 1), None);
+    }
+}
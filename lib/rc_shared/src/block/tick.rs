@@ -0,0 +1,45 @@
+use rand::RngCore;
+use crate::block::{BlockId, BlockStates, WorldBlock};
+use crate::chunk::GlobalBlockPosition;
+use crate::light::LightLevel;
+
+/// The world as seen by a block reacting to a tick or poke
+pub trait BlockTickContext {
+    fn block_states(&self) -> &BlockStates;
+
+    /// The id of the block at `pos`, or None if its chunk isn't loaded
+    fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<BlockId>;
+
+    /// Replaces the block at `pos`, telling clients and neighbours as if it had been placed
+    fn set_block(&mut self, pos: GlobalBlockPosition, block_id: BlockId);
+
+    /// Replaces the block at `pos` with air, dropping its loot
+    fn destroy_block(&mut self, pos: GlobalBlockPosition);
+
+    /// Ticks the block at `pos` after `delay` server ticks, as long as it's still the same kind of block
+    fn schedule_tick(&mut self, pos: GlobalBlockPosition, delay: u64);
+
+    fn rng(&mut self) -> &mut dyn RngCore;
+
+    /// The light at `pos`, or None if its chunk isn't loaded
+    fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel>;
+
+    /// Whether the block at `pos` is lit at least as brightly as `brightness`
+    fn is_lit(&self, pos: GlobalBlockPosition, brightness: u8) -> bool {
+        self.get_light(pos).is_some_and(|light| light.brightness() >= brightness)
+    }
+
+    fn get_block(&self, pos: GlobalBlockPosition) -> Option<WorldBlock> {
+        self.get_block_id(pos).map(|id| self.block_states().get_block_from_id(id))
+    }
+
+    /// Whether the block at `pos` has the identifier given
+    fn is_block(&self, pos: GlobalBlockPosition, identifier: &str) -> bool {
+        self.get_block(pos).is_some_and(|block| block.get_identifier() == identifier)
+    }
+
+    /// The default state of the block with the identifier given
+    fn block_id_of(&self, identifier: &str) -> Option<BlockId> {
+        self.block_states().get_by_identifier(identifier).map(|(_, block)| block.get_id())
+    }
+}
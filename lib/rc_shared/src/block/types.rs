@@ -11,6 +11,9 @@ pub struct VisualBlock {
     pub collision_boxes: Vec<Aabb>,
     pub bounding_boxes: Vec<Aabb>,
     pub emission: [u8; 4],
+    /// The levels of red, green, blue and skylight lost by light passing into this block, on top of the
+    /// one level lost to every block. Only translucent blocks let light in at all.
+    pub light_filter: [u8; 4],
 }
 
 #[derive(Clone, Debug)]
@@ -1,8 +1,9 @@
 use crate::{CHUNK_SIZE, MAX_LIGHT_VALUE};
 use nalgebra::{Vector2, Vector3};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use crate::block::BlockId;
+use crate::block::palette::BlockIdRemap;
 
 pub type RawChunkData = [[[u32; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
 pub type RawLightingData = [[[LightingColor; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
@@ -14,7 +15,6 @@ pub type GlobalBlockPosition = Vector3<i32>;
 
 pub type Metadata = serde_json::Value;
 
-pub type ChunkBlockMetadata = HashMap<Vector3<u8>, HashMap<String, Metadata>>;
 pub type ChunkMetadata = HashMap<String, Metadata>;
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -44,6 +44,7 @@ pub trait ChunkSystemTrait {
 }
 
 
+
 /// Chunk data in a format that allows for more compact storage
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ChunkDataStorage {
@@ -51,7 +52,10 @@ pub enum ChunkDataStorage {
     Data(Box<RawChunkData>),
 
     /// The chunk contains no blocks other than empty
-    Empty
+    Empty,
+
+    /// The chunk contains a small set of blocks, stored as bit packed indexes into a palette
+    Palette(PalettedChunkData)
 }
 
 impl ChunkDataStorage {
@@ -61,6 +65,7 @@ impl ChunkDataStorage {
             ChunkDataStorage::Data(data) => {
                 data[pos.x][pos.y][pos.z]
             },
+            ChunkDataStorage::Palette(data) => data.get(pos),
             // Every block is empty
             ChunkDataStorage::Empty => 0
         }
@@ -69,7 +74,17 @@ impl ChunkDataStorage {
     #[inline]
     pub fn set(&mut self, pos: LocalBlockPosition, id: BlockId) {
         match self {
-            ChunkDataStorage::Data(_) => {}
+            ChunkDataStorage::Data(data) => {
+                data[pos.x][pos.y][pos.z] = id;
+            }
+            ChunkDataStorage::Palette(data) => {
+                data.set(pos, id);
+
+                // With enough distinct blocks the indexes take as much space as the ids themselves
+                if PalettedChunkData::size_for(data.palette().len()) >= size_of::<RawChunkData>() {
+                    *self = ChunkDataStorage::Data(data.to_raw());
+                }
+            }
             ChunkDataStorage::Empty => {
                 // If the target block is air, the entire chunk is already air so return
                 if id == 0 {
@@ -77,13 +92,43 @@ impl ChunkDataStorage {
                 }
 
                 // Convert `self` to a storage where we can change individual blocks
-                *self = ChunkDataStorage::Data(Box::new([[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]))
+                let mut data = PalettedChunkData::filled(0);
+                data.set(pos, id);
+
+                *self = ChunkDataStorage::Palette(data);
             }
         }
+    }
+
+    /// Every distinct block id in the chunk, in ascending order
+    pub fn block_ids(&self) -> Vec<BlockId> {
+        let mut ids = match self {
+            ChunkDataStorage::Data(data) => data.iter().flatten().flatten().copied().collect::<Vec<BlockId>>(),
+            ChunkDataStorage::Palette(data) => data.palette.clone(),
+            ChunkDataStorage::Empty => vec![0],
+        };
+
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
 
-        let ChunkDataStorage::Data(data) = self else { panic!("Impossible"); };
+    /// Replaces every block id with the one given by `remap`, such as when the ids were assigned by another process
+    pub fn remap(&mut self, remap: &BlockIdRemap) {
+        if remap.is_identity() {
+            return;
+        }
 
-        data[pos.x][pos.y][pos.z] = id;
+        match self {
+            ChunkDataStorage::Data(data) => {
+                data.iter_mut().flatten().flatten().for_each(|id| *id = remap.get(*id));
+            }
+            ChunkDataStorage::Palette(data) => {
+                data.palette.iter_mut().for_each(|id| *id = remap.get(*id));
+            }
+            // Air is always id 0
+            ChunkDataStorage::Empty => {}
+        }
     }
 
     /// Shrinks the data down to the smallest storage possible
@@ -94,22 +139,437 @@ impl ChunkDataStorage {
             return
         }
 
-        let mut is_all_air = true;
+        // Collect every distinct block in the chunk, in the order they're first found
+        let mut palette = vec![];
+        let mut seen = HashSet::new();
 
-        for x in 0..16 {
-            for y in 0..16 {
-                for z in 0..16 {
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
                     let id = self.get(Vector3::new(x, y, z));
 
-                    if id != 0 {
-                        is_all_air = false;
+                    if seen.insert(id) {
+                        palette.push(id);
                     }
                 }
             }
         }
 
-        if is_all_air {
+        if palette == [0] {
             *self = ChunkDataStorage::Empty;
+            return;
+        }
+
+        let paletted_size = PalettedChunkData::size_for(palette.len());
+
+        if paletted_size < size_of::<RawChunkData>() {
+            let mut data = PalettedChunkData::with_palette(palette);
+
+            for x in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_SIZE {
+                    for z in 0..CHUNK_SIZE {
+                        let pos = Vector3::new(x, y, z);
+                        data.set(pos, self.get(pos));
+                    }
+                }
+            }
+
+            *self = ChunkDataStorage::Palette(data);
+        } else if let ChunkDataStorage::Palette(data) = self {
+            *self = ChunkDataStorage::Data(data.to_raw());
+        }
+    }
+}
+
+const CHUNK_BLOCKS: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// Stores a chunk as indexes into a palette of block ids.
+/// Indexes are packed into `u64`s using only as many bits as the palette needs. The width grows as
+/// new block ids are added, after first dropping any ids no longer used by a block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedPalettedChunkData")]
+pub struct PalettedChunkData {
+    palette: Vec<BlockId>,
+    /// Bits used per index, zero when the palette only has a single entry
+    bits: u8,
+    data: Vec<u64>,
+}
+
+/// Paletted chunk data as read, before checking it can be used without panicking
+#[derive(Deserialize)]
+struct UncheckedPalettedChunkData {
+    palette: Vec<BlockId>,
+    bits: u8,
+    data: Vec<u64>,
+}
+
+impl TryFrom<UncheckedPalettedChunkData> for PalettedChunkData {
+    type Error = String;
+
+    fn try_from(value: UncheckedPalettedChunkData) -> Result<Self, Self::Error> {
+        let UncheckedPalettedChunkData { palette, bits, data } = value;
+
+        if palette.is_empty() {
+            return Err(String::from("paletted chunk has an empty palette"));
+        }
+
+        // Zero bits is only used by single entry palettes, which have no data
+        if bits > 16 {
+            return Err(format!("paletted chunk has {} bits per block", bits));
+        }
+
+        if data.len() != Self::words_for(bits) {
+            return Err(format!(
+                "paletted chunk has {} words of data, expected {}",
+                data.len(),
+                Self::words_for(bits)
+            ));
+        }
+
+        let chunk = PalettedChunkData { palette, bits, data };
+
+        if bits > 0 && (0..CHUNK_BLOCKS).any(|i| chunk.get_index(i) >= chunk.palette.len()) {
+            return Err(String::from("paletted chunk has an index outside of its palette"));
+        }
+
+        Ok(chunk)
+    }
+}
+
+impl PalettedChunkData {
+    /// Creates a chunk where every block is `id`
+    pub fn filled(id: BlockId) -> PalettedChunkData {
+        PalettedChunkData::with_palette(vec![id])
+    }
+
+    /// Creates a chunk with a palette, where every block is the first palette entry
+    fn with_palette(palette: Vec<BlockId>) -> PalettedChunkData {
+        let bits = Self::bits_for(palette.len());
+
+        PalettedChunkData {
+            palette,
+            bits,
+            data: vec![0; Self::words_for(bits)],
+        }
+    }
+
+    pub fn palette(&self) -> &[BlockId] {
+        &self.palette
+    }
+
+    #[inline]
+    pub fn get(&self, pos: LocalBlockPosition) -> BlockId {
+        if self.bits == 0 {
+            return self.palette[0];
+        }
+
+        self.palette[self.get_index(Self::block_index(pos))]
+    }
+
+    #[inline]
+    pub fn set(&mut self, pos: LocalBlockPosition, id: BlockId) {
+        let palette_index = match self.palette.iter().position(|v| *v == id) {
+            Some(i) => i,
+            None => {
+                if Self::bits_for(self.palette.len() + 1) != self.bits {
+                    self.compact();
+                }
+
+                self.palette.push(id);
+
+                // Widen indexes if the palette no longer fits
+                let bits = Self::bits_for(self.palette.len());
+                if bits != self.bits {
+                    self.resize(bits);
+                }
+
+                self.palette.len() - 1
+            }
+        };
+
+        if self.bits == 0 {
+            return;
+        }
+
+        self.set_index(Self::block_index(pos), palette_index);
+    }
+
+    /// Every block as its id
+    pub fn to_raw(&self) -> Box<RawChunkData> {
+        let mut data = Box::new([[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]);
+
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    data[x][y][z] = self.get(Vector3::new(x, y, z));
+                }
+            }
+        }
+
+        data
+    }
+
+    /// Approximate size in bytes of a paletted chunk holding `palette_len` distinct blocks
+    pub fn size_for(palette_len: usize) -> usize {
+        Self::words_for(Self::bits_for(palette_len)) * size_of::<u64>()
+            + palette_len * size_of::<BlockId>()
+    }
+
+    #[inline]
+    fn block_index(pos: LocalBlockPosition) -> usize {
+        (pos.x * CHUNK_SIZE + pos.y) * CHUNK_SIZE + pos.z
+    }
+
+    #[inline]
+    fn get_index(&self, i: usize) -> usize {
+        let per_word = 64 / self.bits as usize;
+        let shift = (i % per_word) * self.bits as usize;
+        let mask = (1u64 << self.bits) - 1;
+
+        ((self.data[i / per_word] >> shift) & mask) as usize
+    }
+
+    #[inline]
+    fn set_index(&mut self, i: usize, value: usize) {
+        let per_word = 64 / self.bits as usize;
+        let shift = (i % per_word) * self.bits as usize;
+        let mask = (1u64 << self.bits) - 1;
+
+        let word = &mut self.data[i / per_word];
+        *word = (*word & !(mask << shift)) | ((value as u64 & mask) << shift);
+    }
+
+    /// Removes the palette entries no block uses any more, narrowing the indexes if fewer bits are needed
+    fn compact(&mut self) {
+        if self.bits == 0 {
+            return;
+        }
+
+        let mut used = vec![false; self.palette.len()];
+        for i in 0..CHUNK_BLOCKS {
+            used[self.get_index(i)] = true;
+        }
+
+        if used.iter().all(|used| *used) {
+            return;
+        }
+
+        // The new index of each entry that's kept
+        let mut indexes = vec![0; self.palette.len()];
+        let mut palette = Vec::new();
+        for (i, id) in self.palette.iter().enumerate() {
+            if used[i] {
+                indexes[i] = palette.len();
+                palette.push(*id);
+            }
+        }
+
+        let old = std::mem::replace(self, PalettedChunkData::with_palette(palette));
+
+        if self.bits == 0 {
+            return;
+        }
+
+        for i in 0..CHUNK_BLOCKS {
+            self.set_index(i, indexes[old.get_index(i)]);
+        }
+    }
+
+    /// Repacks every index using a new number of bits
+    fn resize(&mut self, bits: u8) {
+        let old = PalettedChunkData {
+            palette: vec![],
+            bits: self.bits,
+            data: std::mem::take(&mut self.data),
+        };
+
+        self.bits = bits;
+        self.data = vec![0; Self::words_for(bits)];
+
+        if old.bits == 0 {
+            // Every block was palette entry 0, which is already what the new data contains
+            return;
+        }
+
+        for i in 0..CHUNK_BLOCKS {
+            self.set_index(i, old.get_index(i));
+        }
+    }
+
+    fn bits_for(palette_len: usize) -> u8 {
+        if palette_len <= 1 {
+            0
+        } else {
+            (usize::BITS - (palette_len - 1).leading_zeros()) as u8
+        }
+    }
+
+    fn words_for(bits: u8) -> usize {
+        if bits == 0 {
+            0
+        } else {
+            CHUNK_BLOCKS.div_ceil(64 / bits as usize)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use crate::block::palette::{BlockIdRemap, BlockPalette, BlockPaletteEntry};
+    use crate::chunk::{ChunkDataStorage, PalettedChunkData};
+    use crate::CHUNK_SIZE;
+
+    #[test]
+    fn palette_get_set() {
+        let mut data = PalettedChunkData::filled(0);
+
+        // Enough distinct ids to widen the indexes several times
+        for i in 0..40 {
+            data.set(Vector3::new(i % 16, i / 16, 3), i as u32 + 1);
+        }
+
+        for i in 0..40 {
+            assert_eq!(data.get(Vector3::new(i % 16, i / 16, 3)), i as u32 + 1);
+        }
+
+        assert_eq!(data.get(Vector3::new(15, 15, 15)), 0);
+        assert_eq!(data.palette().len(), 41);
+    }
+
+    #[test]
+    fn palette_rejects_corrupt_data() {
+        let mut data = PalettedChunkData::filled(0);
+        for i in 0..5 {
+            data.set(Vector3::new(i, 0, 0), i as u32 + 1);
+        }
+
+        let value = serde_json::to_value(&data).unwrap();
+        assert_eq!(serde_json::from_value::<PalettedChunkData>(value.clone()).unwrap(), data);
+
+        let corrupt = |field: &str, corrupted: serde_json::Value| {
+            let mut value = value.clone();
+            value[field] = corrupted;
+            serde_json::from_value::<PalettedChunkData>(value).is_err()
+        };
+
+        assert!(corrupt("palette", serde_json::json!([])));
+        assert!(corrupt("bits", serde_json::json!(40)));
+        assert!(corrupt("bits", serde_json::json!(2)));
+        assert!(corrupt("data", serde_json::json!([0, 0, 0])));
+        // Indexes of 6 and 7 point past the end of the palette
+        assert!(corrupt("data", serde_json::json!(vec![u64::MAX; data.data.len()])));
+    }
+
+    #[test]
+    fn palette_drops_unused_blocks() {
+        let mut data = PalettedChunkData::filled(0);
+
+        // Each block replaces the last, so only two ids are ever in use
+        for i in 1..100 {
+            data.set(Vector3::new(4, 4, 4), i);
         }
+
+        assert_eq!(data.get(Vector3::new(4, 4, 4)), 99);
+        assert_eq!(data.get(Vector3::new(0, 0, 0)), 0);
+        // Unused ids are dropped whenever the indexes would otherwise widen, so they never need more than 2 bits
+        assert!(data.palette().len() <= 4, "palette kept {} ids", data.palette().len());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn palette_falls_back_to_data() {
+        let id = |pos: Vector3<usize>| ((pos.x * CHUNK_SIZE + pos.y) * CHUNK_SIZE + pos.z) as u32 + 1;
+        let mut storage = ChunkDataStorage::Empty;
+
+        // Every block different, far more than a palette saves space for
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    let pos = Vector3::new(x, y, z);
+                    storage.set(pos, id(pos));
+                }
+            }
+
+            if x == 0 {
+                assert!(matches!(storage, ChunkDataStorage::Palette(_)));
+            }
+        }
+
+        assert!(matches!(storage, ChunkDataStorage::Data(_)));
+        for pos in [Vector3::new(0, 0, 0), Vector3::new(3, 4, 5), Vector3::new(15, 15, 15)] {
+            assert_eq!(storage.get(pos), id(pos));
+        }
+    }
+
+    #[test]
+    fn optimise_picks_smallest() {
+        let mut storage = ChunkDataStorage::Data(Box::new([[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]));
+        storage.optimise();
+        assert_eq!(storage, ChunkDataStorage::Empty);
+
+        let mut storage = ChunkDataStorage::Data(Box::new([[[2; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]));
+        storage.optimise();
+        assert_eq!(storage, ChunkDataStorage::Palette(PalettedChunkData::filled(2)));
+        assert_eq!(storage.get(Vector3::new(4, 5, 6)), 2);
+
+        // Every block unique, a palette would be larger than the raw data
+        let mut raw = [[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
+        for (i, block) in raw.iter_mut().flatten().flatten().enumerate() {
+            *block = i as u32;
+        }
+
+        let mut storage = ChunkDataStorage::Data(Box::new(raw));
+        storage.optimise();
+        assert_eq!(storage, ChunkDataStorage::Data(Box::new(raw)));
+    }
+
+    #[test]
+    fn set_on_empty() {
+        let mut storage = ChunkDataStorage::Empty;
+        storage.set(Vector3::new(1, 2, 3), 0);
+        assert_eq!(storage, ChunkDataStorage::Empty);
+
+        storage.set(Vector3::new(1, 2, 3), 5);
+        assert_eq!(storage.get(Vector3::new(1, 2, 3)), 5);
+        assert_eq!(storage.get(Vector3::new(3, 2, 1)), 0);
+    }
+
+    #[test]
+    fn remap_storage() {
+        let remap = BlockIdRemap::new(
+            &palette(&[("air", 0, 1), ("stone", 1, 1), ("sand", 2, 1)]),
+            &palette(&[("air", 0, 1), ("sand", 1, 1), ("stone", 2, 1)]),
+        );
+
+        let mut data = Box::new([[[1; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]);
+        data[0][0][0] = 2;
+        data[0][1][0] = 0;
+
+        for mut storage in [ChunkDataStorage::Data(data.clone()), {
+            let mut paletted = ChunkDataStorage::Data(data);
+            paletted.optimise();
+            paletted
+        }] {
+            assert_eq!(storage.block_ids(), vec![0, 1, 2]);
+
+            storage.remap(&remap);
+
+            assert_eq!(storage.get(Vector3::new(0, 0, 0)), 1);
+            assert_eq!(storage.get(Vector3::new(0, 1, 0)), 0);
+            assert_eq!(storage.get(Vector3::new(5, 5, 5)), 2);
+        }
+    }
+
+    fn palette(entries: &[(&str, u32, u32)]) -> BlockPalette {
+        BlockPalette {
+            entries: entries
+                .iter()
+                .map(|(identifier, start_id, variants)| BlockPaletteEntry {
+                    identifier: identifier.to_string(),
+                    start_id: *start_id,
+                    variants: *variants,
+                })
+                .collect(),
+        }
+    }
+}
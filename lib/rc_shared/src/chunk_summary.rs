@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use crate::block::BlockId;
+use crate::block::palette::BlockIdRemap;
+use crate::CHUNK_SIZE;
+
+/// The tops of a chunk column's blocks at a reduced resolution, enough to draw terrain too far away to load.
+/// Each cell covers a square of `scale` by `scale` block columns, and holds the highest of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkSummary {
+    /// How many blocks wide each cell is. A power of two no larger than a chunk
+    pub scale: u8,
+    /// The height of each cell's top block, indexed by x then z
+    pub heights: Vec<i32>,
+    /// The block on top of each cell, indexed by x then z
+    pub blocks: Vec<BlockId>,
+}
+
+impl ChunkSummary {
+    /// Summarises a chunk column from the height and block at the top of each of its block columns
+    pub fn from_surface(scale: u8, surface: impl Fn(usize, usize) -> (i32, BlockId)) -> ChunkSummary {
+        let scale = Self::clamp_scale(scale);
+        let cells = CHUNK_SIZE / scale as usize;
+
+        let mut heights = Vec::with_capacity(cells * cells);
+        let mut blocks = Vec::with_capacity(cells * cells);
+
+        for cell_x in 0..cells {
+            for cell_z in 0..cells {
+                let (height, block) = (0..scale as usize)
+                    .flat_map(|x| (0..scale as usize).map(move |z| (x, z)))
+                    .map(|(x, z)| surface(cell_x * scale as usize + x, cell_z * scale as usize + z))
+                    .max_by_key(|(height, _)| *height)
+                    .unwrap();
+
+                heights.push(height);
+                blocks.push(block);
+            }
+        }
+
+        ChunkSummary {
+            scale,
+            heights,
+            blocks,
+        }
+    }
+
+    /// The nearest scale a summary can be made at
+    pub fn clamp_scale(scale: u8) -> u8 {
+        scale.clamp(1, CHUNK_SIZE as u8).next_power_of_two()
+    }
+
+    /// How many cells there are along each side
+    pub fn cells(&self) -> usize {
+        CHUNK_SIZE / self.scale as usize
+    }
+
+    /// The height and top block of the cell at `x` and `z`, counted in cells
+    pub fn get(&self, x: usize, z: usize) -> (i32, BlockId) {
+        let index = x * self.cells() + z;
+        (self.heights[index], self.blocks[index])
+    }
+
+    /// The height of the cell covering the block column at `x` and `z` within the chunk
+    pub fn height_at(&self, x: usize, z: usize) -> i32 {
+        self.get(x / self.scale as usize, z / self.scale as usize).0
+    }
+
+    /// The lowest and highest cells
+    pub fn height_range(&self) -> (i32, i32) {
+        let min = self.heights.iter().copied().min().unwrap_or(0);
+        let max = self.heights.iter().copied().max().unwrap_or(0);
+        (min, max)
+    }
+
+    pub fn remap(&mut self, remap: &BlockIdRemap) {
+        self.blocks.iter_mut().for_each(|id| *id = remap.get(*id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk_summary::ChunkSummary;
+    use crate::CHUNK_SIZE;
+
+    #[test]
+    fn test_cells_keep_their_highest_block() {
+        // A slope rising along x, with a single spike
+        let summary = ChunkSummary::from_surface(4, |x, z| {
+            if (x, z) == (5, 9) {
+                (100, 2)
+            } else {
+                (x as i32, 1)
+            }
+        });
+
+        assert_eq!(summary.cells(), 4);
+        assert_eq!(summary.heights.len(), 16);
+        assert_eq!(summary.get(0, 0), (3, 1));
+        assert_eq!(summary.get(3, 3), (15, 1));
+        assert_eq!(summary.get(1, 2), (100, 2));
+        assert_eq!(summary.height_at(5, 9), 100);
+        assert_eq!(summary.height_at(4, 0), 7);
+        assert_eq!(summary.height_range(), (3, 100));
+    }
+
+    #[test]
+    fn test_scales_are_clamped() {
+        assert_eq!(ChunkSummary::clamp_scale(0), 1);
+        assert_eq!(ChunkSummary::clamp_scale(3), 4);
+        assert_eq!(ChunkSummary::clamp_scale(255), CHUNK_SIZE as u8);
+        assert_eq!(ChunkSummary::from_surface(1, |_, _| (0, 0)).cells(), CHUNK_SIZE);
+    }
+}
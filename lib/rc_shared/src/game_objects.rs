@@ -1,6 +1,14 @@
 use bevy::prelude::Component;
+use nalgebra::Vector3;
 use serde::{Deserialize, Serialize};
 use crate::{constants::UserId, item::types::ItemStack};
+use crate::aabb::Aabb;
+
+/// How far from their eyes a player can reach to place or break blocks
+pub const MAX_INTERACTION_DISTANCE: f32 = 7.0;
+
+/// How high a player's eyes are above their position
+pub const PLAYER_EYE_HEIGHT: f32 = 1.7;
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub enum GameObjectData {
@@ -32,6 +40,16 @@ pub struct PlayerGameObjectData {
     pub username: String
 }
 
+impl PlayerGameObjectData {
+    /// The collider of a player relative to their position
+    pub fn collider() -> Aabb {
+        Aabb::new(
+            Vector3::new(-0.35, 0.0, -0.35),
+            Vector3::new(0.7, 1.85, 0.7),
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Component)]
 pub struct ItemDropGameObjectData {
     pub item_stack: ItemStack
@@ -16,8 +16,11 @@ pub mod relative_chunk_map;
 pub mod relative_chunk_flat_map;
 pub mod game_mode;
 pub mod chunk_column;
+pub mod chunk_summary;
 pub mod time;
 pub mod config;
+pub mod physics;
+pub mod light;
 
 pub const CHUNK_SIZE: usize = 16;
 
@@ -0,0 +1,543 @@
+use std::collections::VecDeque;
+use fnv::FnvHashSet;
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+use crate::block::{BlockId, BlockStates};
+use crate::chunk::{ChunkPosition, GlobalBlockPosition, LightingColor, LocalBlockPosition};
+use crate::helpers::{global_to_local_position, local_to_global_position};
+use crate::viewable_direction::BLOCK_SIDES;
+use crate::{CHUNK_SIZE, MAX_LIGHT_VALUE};
+
+/// The skylight level of blocks open to the sky
+pub const MAX_SKYLIGHT: u8 = 12;
+
+/// The brightness plants need to grow and spread
+pub const PLANT_GROWTH_LIGHT: u8 = 9;
+
+/// Red, green and blue block light, then skylight
+const CHANNELS: usize = 4;
+
+/// The index of skylight within a `LightLevel`
+pub const SKY_CHANNEL: usize = 3;
+
+/// The light reaching a block. Block light spreads separately for each colour channel, so
+/// differently coloured lights mix where they meet, and skylight spreads as a fourth channel.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LightLevel(pub [u8; CHANNELS]);
+
+impl LightLevel {
+    /// How brightly lit the block is, by any light
+    pub fn brightness(&self) -> u8 {
+        self.0.into_iter().max().unwrap_or(0)
+    }
+
+    /// The light in the form chunk meshes are drawn with
+    pub fn color(&self) -> LightingColor {
+        let [r, g, b, skylight] = self.0;
+        let channel = |level: u8| (level as u32 * 255 / MAX_LIGHT_VALUE as u32).min(255) as u8;
+
+        LightingColor {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+            strength: r.max(g).max(b),
+            skylight,
+        }
+    }
+}
+
+/// The light of every block in a chunk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkLight {
+    levels: Box<[[[LightLevel; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
+}
+
+impl Default for ChunkLight {
+    fn default() -> Self {
+        ChunkLight {
+            levels: Box::new([[[LightLevel::default(); CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]),
+        }
+    }
+}
+
+impl ChunkLight {
+    #[inline]
+    pub fn get(&self, pos: LocalBlockPosition) -> LightLevel {
+        self.levels[pos.x][pos.y][pos.z]
+    }
+
+    #[inline]
+    pub fn set(&mut self, pos: LocalBlockPosition, light: LightLevel) {
+        self.levels[pos.x][pos.y][pos.z] = light;
+    }
+}
+
+/// The world as the light engine sees it
+pub trait LightWorld {
+    /// The id of the block at `pos`, or None if its chunk isn't loaded
+    fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<BlockId>;
+
+    /// The lowest y open to the sky in the column of blocks at `x`, `z`, or None if skylight doesn't reach it
+    fn sky_height(&self, x: i32, z: i32) -> Option<i32>;
+
+    /// The light at `pos`, or None if its chunk isn't loaded
+    fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel>;
+
+    /// Sets the light at `pos`, doing nothing if its chunk isn't loaded
+    fn set_light(&mut self, pos: GlobalBlockPosition, light: LightLevel);
+}
+
+/// Something that changes how the world is lit
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LightUpdate {
+    /// A chunk was loaded, so is lit from scratch and lets light in from its neighbours
+    Chunk(ChunkPosition),
+    /// A block was placed or removed. The sky height of its column should already be updated.
+    Block(GlobalBlockPosition),
+    /// The sky height of the column of blocks at `x`, `z` moved, with the blocks at `y` the first to be affected
+    Sky { x: i32, z: i32, y: i32 },
+}
+
+/// Relights the world around the updates given, returning the chunks whose light changed.
+///
+/// Light is only recalculated where it could have changed: light that came through a changed block
+/// is removed, then light spreads back in from the blocks around the removed area and from any
+/// new light sources.
+pub fn update_light(
+    world: &mut dyn LightWorld,
+    block_states: &BlockStates,
+    updates: &[LightUpdate],
+) -> FnvHashSet<ChunkPosition> {
+    let mut engine = LightEngine {
+        world,
+        block_states,
+        changed: FnvHashSet::default(),
+    };
+
+    let mut relight = Vec::new();
+    let mut new_chunks = Vec::new();
+
+    for update in updates {
+        match *update {
+            LightUpdate::Chunk(chunk) => {
+                new_chunks.push(chunk);
+
+                // The chunk may cover blocks below it that were open to the sky
+                let bottom = chunk.y * CHUNK_SIZE as i32 - 1;
+                for x in 0..CHUNK_SIZE as i32 {
+                    for z in 0..CHUNK_SIZE as i32 {
+                        let column = chunk * CHUNK_SIZE as i32 + Vector3::new(x, 0, z);
+                        engine.sky_changes(column.x, column.z, bottom, &mut relight);
+                    }
+                }
+            }
+            LightUpdate::Block(pos) => {
+                relight.push(pos);
+                engine.sky_changes(pos.x, pos.z, pos.y, &mut relight);
+            }
+            LightUpdate::Sky { x, z, y } => engine.sky_changes(x, z, y, &mut relight),
+        }
+    }
+
+    let mut queues = engine.remove(&relight);
+
+    for pos in &relight {
+        engine.seed(*pos, &mut queues);
+
+        // Blocks that were opaque may now let light in from around them
+        for side in &BLOCK_SIDES {
+            engine.queue_lit(pos + side, &mut queues);
+        }
+    }
+
+    for chunk in new_chunks {
+        engine.seed_chunk(chunk, &mut queues);
+    }
+
+    for (channel, queue) in queues.into_iter().enumerate() {
+        engine.spread(channel, queue);
+    }
+
+    engine.changed
+}
+
+struct LightEngine<'a> {
+    world: &'a mut dyn LightWorld,
+    block_states: &'a BlockStates,
+    changed: FnvHashSet<ChunkPosition>,
+}
+
+type LightQueues = [VecDeque<GlobalBlockPosition>; CHANNELS];
+
+impl LightEngine<'_> {
+    /// The levels light loses on top of the usual one when passing into the block at `pos`, or None
+    /// if it can't pass at all
+    fn light_filter(&self, pos: GlobalBlockPosition) -> Option<[u8; CHANNELS]> {
+        let block = self.block_states.get_block_from_id(self.world.get_block_id(pos)?);
+        let visual_block = block.draw();
+
+        visual_block.translucent.then_some(visual_block.light_filter)
+    }
+
+    /// The light the block at `pos` gives off itself, including skylight if it's open to the sky
+    fn emission(&self, pos: GlobalBlockPosition) -> [u8; CHANNELS] {
+        let Some(id) = self.world.get_block_id(pos) else {
+            return [0; CHANNELS];
+        };
+
+        let block = self.block_states.get_block_from_id(id);
+        let visual_block = block.draw();
+        let [r, g, b, strength] = visual_block.emission;
+        let channel = |color: u8| (strength as u32 * color as u32 / 255) as u8;
+
+        let open_to_sky = visual_block.translucent
+            && self.world.sky_height(pos.x, pos.z).is_some_and(|height| pos.y >= height);
+
+        [channel(r), channel(g), channel(b), if open_to_sky { MAX_SKYLIGHT } else { 0 }]
+    }
+
+    fn set_channel(&mut self, pos: GlobalBlockPosition, channel: usize, level: u8) {
+        let Some(mut light) = self.world.get_light(pos) else {
+            return;
+        };
+
+        light.0[channel] = level;
+        self.world.set_light(pos, light);
+        self.changed.insert(global_to_local_position(pos).0);
+    }
+
+    /// Finds the blocks in a column whose skylight no longer matches whether they're open to the
+    /// sky, searching out from `y` in both directions
+    fn sky_changes(&self, x: i32, z: i32, y: i32, out: &mut Vec<GlobalBlockPosition>) {
+        let changed = |pos: GlobalBlockPosition| match self.world.get_light(pos) {
+            Some(light) => (light.0[SKY_CHANNEL] == MAX_SKYLIGHT) != (self.emission(pos)[SKY_CHANNEL] == MAX_SKYLIGHT),
+            None => false,
+        };
+
+        let mut pos = Vector3::new(x, y, z);
+        while changed(pos) {
+            out.push(pos);
+            pos.y -= 1;
+        }
+
+        let mut pos = Vector3::new(x, y + 1, z);
+        while changed(pos) {
+            out.push(pos);
+            pos.y += 1;
+        }
+    }
+
+    /// Darkens every block lit through `positions`, returning the lit blocks around the darkened
+    /// area that need to spread their light back into it
+    fn remove(&mut self, positions: &[GlobalBlockPosition]) -> LightQueues {
+        let mut queues = LightQueues::default();
+        let mut darkened = Vec::new();
+
+        for (channel, queue) in queues.iter_mut().enumerate() {
+            let mut removing = VecDeque::new();
+
+            for pos in positions {
+                let level = self.world.get_light(*pos).map_or(0, |light| light.0[channel]);
+                if level > 0 {
+                    self.set_channel(*pos, channel, 0);
+                    removing.push_back((*pos, level));
+                }
+            }
+
+            while let Some((pos, level)) = removing.pop_front() {
+                for side in &BLOCK_SIDES {
+                    let neighbour = pos + side;
+                    let Some(light) = self.world.get_light(neighbour) else {
+                        continue;
+                    };
+
+                    let neighbour_level = light.0[channel];
+                    if neighbour_level == 0 {
+                        continue;
+                    }
+
+                    if neighbour_level < level {
+                        // Lit by the removed light
+                        self.set_channel(neighbour, channel, 0);
+                        removing.push_back((neighbour, neighbour_level));
+                        darkened.push(neighbour);
+                    } else {
+                        // Lit by something else, which can now light the removed area
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        // Dimmer light sources caught in the darkened area light themselves again
+        for pos in darkened {
+            self.seed(pos, &mut queues);
+        }
+
+        queues
+    }
+
+    /// Lights the block at `pos` with the light it gives off
+    fn seed(&mut self, pos: GlobalBlockPosition, queues: &mut LightQueues) {
+        let Some(light) = self.world.get_light(pos) else {
+            return;
+        };
+
+        for (channel, level) in self.emission(pos).into_iter().enumerate() {
+            if level > light.0[channel] {
+                self.set_channel(pos, channel, level);
+                queues[channel].push_back(pos);
+            }
+        }
+    }
+
+    /// Queues the block at `pos` to spread whatever light it has
+    fn queue_lit(&self, pos: GlobalBlockPosition, queues: &mut LightQueues) {
+        let Some(light) = self.world.get_light(pos) else {
+            return;
+        };
+
+        for (channel, level) in light.0.into_iter().enumerate() {
+            if level > 0 {
+                queues[channel].push_back(pos);
+            }
+        }
+    }
+
+    /// Lights a newly loaded chunk from the light sources inside it and the light around it
+    fn seed_chunk(&mut self, chunk: ChunkPosition, queues: &mut LightQueues) {
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    self.seed(local_to_global_position(chunk, Vector3::new(x, y, z)), queues);
+                }
+            }
+        }
+
+        // The blocks touching each face of the chunk
+        let last = CHUNK_SIZE as i32 - 1;
+        for a in 0..CHUNK_SIZE as i32 {
+            for b in 0..CHUNK_SIZE as i32 {
+                for outside in [
+                    Vector3::new(-1, a, b),
+                    Vector3::new(last + 1, a, b),
+                    Vector3::new(a, -1, b),
+                    Vector3::new(a, last + 1, b),
+                    Vector3::new(a, b, -1),
+                    Vector3::new(a, b, last + 1),
+                ] {
+                    self.queue_lit(chunk * CHUNK_SIZE as i32 + outside, queues);
+                }
+            }
+        }
+    }
+
+    /// Spreads light out from the queued blocks, one level dimmer for each block it passes through
+    /// and dimmer still through blocks that filter it
+    fn spread(&mut self, channel: usize, mut queue: VecDeque<GlobalBlockPosition>) {
+        while let Some(pos) = queue.pop_front() {
+            let Some(light) = self.world.get_light(pos) else {
+                continue;
+            };
+
+            let level = light.0[channel];
+            if level <= 1 {
+                continue;
+            }
+
+            for side in &BLOCK_SIDES {
+                let neighbour = pos + side;
+                let Some(neighbour_light) = self.world.get_light(neighbour) else {
+                    continue;
+                };
+
+                if neighbour_light.0[channel] >= level - 1 {
+                    continue;
+                }
+
+                let Some(filter) = self.light_filter(neighbour) else {
+                    continue;
+                };
+
+                let neighbour_level = (level - 1).saturating_sub(filter[channel]);
+                if neighbour_level <= neighbour_light.0[channel] {
+                    continue;
+                }
+
+                self.set_channel(neighbour, channel, neighbour_level);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fnv::FnvHashMap;
+    use nalgebra::Vector3;
+    use crate::block::{test_block_states, BlockId, BlockStates};
+    use crate::chunk::GlobalBlockPosition;
+    use crate::light::{update_light, LightLevel, LightUpdate, LightWorld, MAX_SKYLIGHT, SKY_CHANNEL};
+    use crate::CHUNK_SIZE;
+
+    /// A single chunk of air at the origin, with blocks open to the sky from `sky_height` upwards
+    struct TestWorld {
+        blocks: FnvHashMap<GlobalBlockPosition, BlockId>,
+        light: FnvHashMap<GlobalBlockPosition, LightLevel>,
+        sky_height: Option<i32>,
+    }
+
+    impl TestWorld {
+        fn new(sky_height: Option<i32>) -> TestWorld {
+            TestWorld {
+                blocks: FnvHashMap::default(),
+                light: FnvHashMap::default(),
+                sky_height,
+            }
+        }
+
+        fn contains(pos: GlobalBlockPosition) -> bool {
+            (0..CHUNK_SIZE as i32).contains(&pos.x)
+                && (0..CHUNK_SIZE as i32).contains(&pos.y)
+                && (0..CHUNK_SIZE as i32).contains(&pos.z)
+        }
+
+        fn level(&self, pos: GlobalBlockPosition) -> LightLevel {
+            LightWorld::get_light(self, pos).unwrap()
+        }
+    }
+
+    impl LightWorld for TestWorld {
+        fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<BlockId> {
+            TestWorld::contains(pos).then(|| self.blocks.get(&pos).copied().unwrap_or(0))
+        }
+
+        fn sky_height(&self, _x: i32, _z: i32) -> Option<i32> {
+            self.sky_height
+        }
+
+        fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel> {
+            TestWorld::contains(pos).then(|| self.light.get(&pos).copied().unwrap_or_default())
+        }
+
+        fn set_light(&mut self, pos: GlobalBlockPosition, light: LightLevel) {
+            if TestWorld::contains(pos) {
+                self.light.insert(pos, light);
+            }
+        }
+    }
+
+    fn block_id(block_states: &BlockStates, identifier: &str) -> BlockId {
+        block_states.get_by_identifier(identifier).unwrap().1.get_id()
+    }
+
+    /// Block light from a lamp, as red, green and blue levels
+    fn lamp_light(block_states: &BlockStates) -> [u8; 3] {
+        let [r, g, b, strength] = block_states.get_by_identifier("mcv3::block::Lamp").unwrap().1.draw().emission;
+        [r, g, b].map(|color| (strength as u32 * color as u32 / 255) as u8)
+    }
+
+    fn light(world: &mut TestWorld, block_states: &BlockStates) {
+        update_light(world, block_states, &[LightUpdate::Chunk(Vector3::new(0, 0, 0))]);
+    }
+
+    #[test]
+    fn test_water_dims_and_tints_light() {
+        let block_states = test_block_states();
+        let water = block_id(block_states, "mcv3::block::Water");
+        let filter = block_states.get_block_from_id(water).draw().light_filter;
+        let lamp = lamp_light(block_states);
+
+        let mut world = TestWorld::new(None);
+        world.blocks.insert(Vector3::new(8, 8, 8), block_id(block_states, "mcv3::block::Lamp"));
+        world.blocks.insert(Vector3::new(9, 8, 8), water);
+        light(&mut world, block_states);
+
+        let in_air = world.level(Vector3::new(7, 8, 8));
+        let in_water = world.level(Vector3::new(9, 8, 8));
+
+        for channel in 0..3 {
+            assert_eq!(in_air.0[channel], lamp[channel] - 1);
+            assert_eq!(in_water.0[channel], (lamp[channel] - 1).saturating_sub(filter[channel]));
+        }
+
+        // Red is filtered out more than blue
+        assert!(in_air.0[0] - in_water.0[0] > in_air.0[2] - in_water.0[2]);
+    }
+
+    #[test]
+    fn test_leaves_partially_block_light() {
+        let block_states = test_block_states();
+        let leaves = block_id(block_states, "mcv3::block::Leaves");
+        let stone = block_id(block_states, "mcv3::block::Stone");
+        let filter = block_states.get_block_from_id(leaves).draw().light_filter;
+        let lamp = lamp_light(block_states);
+
+        // A lamp in a stone box with one side of leaves
+        let mut world = TestWorld::new(None);
+        let lamp_pos = Vector3::new(8, 8, 8);
+        for x in 7..=9 {
+            for y in 7..=9 {
+                for z in 7..=9 {
+                    world.blocks.insert(Vector3::new(x, y, z), stone);
+                }
+            }
+        }
+        world.blocks.insert(lamp_pos, block_id(block_states, "mcv3::block::Lamp"));
+        world.blocks.insert(Vector3::new(9, 8, 8), leaves);
+        light(&mut world, block_states);
+
+        let outside = world.level(Vector3::new(10, 8, 8));
+        assert_eq!(outside.0[0], (lamp[0] - 1).saturating_sub(filter[0]) - 1);
+        assert!(outside.0[0] > 0);
+
+        // The other sides are fully blocked, so light has to go round through the leaves
+        assert_eq!(world.level(Vector3::new(6, 8, 8)).0[0], outside.0[0].saturating_sub(8));
+    }
+
+    #[test]
+    fn test_skylight_fades_through_water() {
+        let block_states = test_block_states();
+        let water = block_id(block_states, "mcv3::block::Water");
+        let filter = block_states.get_block_from_id(water).draw().light_filter[SKY_CHANNEL];
+
+        // A pool of water filling the chunk below y = 8
+        let mut world = TestWorld::new(Some(8));
+        for x in 0..CHUNK_SIZE as i32 {
+            for y in 0..8 {
+                for z in 0..CHUNK_SIZE as i32 {
+                    world.blocks.insert(Vector3::new(x, y, z), water);
+                }
+            }
+        }
+        light(&mut world, block_states);
+
+        assert_eq!(world.level(Vector3::new(4, 8, 4)).0[SKY_CHANNEL], MAX_SKYLIGHT);
+        assert_eq!(world.level(Vector3::new(4, 7, 4)).0[SKY_CHANNEL], MAX_SKYLIGHT - 1 - filter);
+        assert_eq!(world.level(Vector3::new(4, 6, 4)).0[SKY_CHANNEL], MAX_SKYLIGHT - 2 - 2 * filter);
+    }
+
+    #[test]
+    fn test_relighting_through_filters() {
+        let block_states = test_block_states();
+        let water = block_id(block_states, "mcv3::block::Water");
+        let lamp = block_id(block_states, "mcv3::block::Lamp");
+
+        let mut world = TestWorld::new(None);
+        for x in 4..12 {
+            world.blocks.insert(Vector3::new(x, 8, 8), water);
+        }
+        world.blocks.insert(Vector3::new(3, 8, 8), lamp);
+        light(&mut world, block_states);
+        let lit = world.light.clone();
+
+        // Removing the lamp darkens the water, and putting it back restores exactly the same light
+        world.blocks.insert(Vector3::new(3, 8, 8), 0);
+        update_light(&mut world, block_states, &[LightUpdate::Block(Vector3::new(3, 8, 8))]);
+        assert!(world.light.values().all(|light| *light == LightLevel::default()));
+
+        world.blocks.insert(Vector3::new(3, 8, 8), lamp);
+        update_light(&mut world, block_states, &[LightUpdate::Block(Vector3::new(3, 8, 8))]);
+        assert_eq!(world.light, lit);
+    }
+}
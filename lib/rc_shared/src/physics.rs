@@ -0,0 +1,50 @@
+//! Physics constants shared between the client simulation and the server's movement validation
+
+/// Downwards acceleration applied to objects affected by gravity, in blocks per second squared
+pub const GRAVITY_STRENGTH: f32 = 30.0;
+
+/// The fastest an object can move horizontally through its velocity while touching the ground
+pub const MAX_HORIZONTAL_VELOCITY: f32 = 5.0;
+
+/// How quickly objects in the air slow down, which limits how fast they can fall
+pub const AIR_FRICTION: f32 = 0.6;
+
+/// Upwards velocity given to a player when they jump
+pub const JUMP_VELOCITY: f32 = 9.0;
+
+/// Distance a player is moved directly per second of holding a movement key, on top of their velocity
+pub const MOVEMENT_SPEED_POSITION: f32 = 2.4;
+
+/// Velocity a player gains per second of holding a movement key
+pub const MOVEMENT_SPEED_VELOCITY: f32 = 15.0;
+
+/// Multiplier applied to player movement while sprinting
+pub const SPRINTING_MULTIPLIER: f32 = 1.5;
+
+/// Upwards push from water on a fully submerged object, as a fraction of gravity. Just under 1 so
+/// objects slowly sink when nothing else moves them.
+pub const FLUID_BUOYANCY: f32 = 0.9;
+
+/// How quickly objects in water slow down, like `GROUND_FRICTION` on the client
+pub const FLUID_FRICTION: f32 = 4.0;
+
+/// Multiplier applied to player movement while in water
+pub const SWIMMING_MULTIPLIER: f32 = 0.5;
+
+/// Upwards velocity of a player swimming up
+pub const SWIM_VELOCITY: f32 = 3.0;
+
+/// The fastest a player can legitimately move horizontally, in blocks per second
+pub const fn max_player_horizontal_speed() -> f32 {
+    MAX_HORIZONTAL_VELOCITY + MOVEMENT_SPEED_POSITION * SPRINTING_MULTIPLIER
+}
+
+/// The fastest an object can fall through the air, where gravity is balanced by air friction
+pub const fn max_fall_speed() -> f32 {
+    GRAVITY_STRENGTH / AIR_FRICTION
+}
+
+/// The highest a player can raise themselves above the ground by jumping
+pub const fn max_jump_height() -> f32 {
+    (JUMP_VELOCITY * JUMP_VELOCITY) / (2.0 * GRAVITY_STRENGTH)
+}
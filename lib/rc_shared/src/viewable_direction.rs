@@ -156,44 +156,55 @@ pub fn calculate_chunk_viewable(
     let mut direction: u8 = 0;
 
     let identifier = block.get_identifier();
+    let visual_block = block.draw();
 
     if pos.y != CHUNK_SIZE - 1
-        && should_draw_betweens(block_states, world, pos, Vector3::new(0, 1, 0), identifier)
+        && should_draw_betweens(block_states, world, pos, Vector3::new(0, 1, 0), visual_block, identifier)
     {
         direction += ViewableDirectionBitMap::Top as u8;
     }
 
-    if pos.y != 0 && should_draw_betweens(block_states, world, pos, Vector3::new(0, -1, 0), identifier) {
+    if pos.y != 0 && should_draw_betweens(block_states, world, pos, Vector3::new(0, -1, 0), visual_block, identifier) {
         direction += ViewableDirectionBitMap::Bottom as u8;
     }
 
-    if pos.x != CHUNK_SIZE - 1 && should_draw_betweens(block_states, world, pos, Vector3::new(1, 0, 0), identifier)
+    if pos.x != CHUNK_SIZE - 1 && should_draw_betweens(block_states, world, pos, Vector3::new(1, 0, 0), visual_block, identifier)
     {
         direction += ViewableDirectionBitMap::Right as u8;
     }
 
-    if pos.x != 0 && should_draw_betweens(block_states, world, pos, Vector3::new(-1, 0, 0), identifier) {
+    if pos.x != 0 && should_draw_betweens(block_states, world, pos, Vector3::new(-1, 0, 0), visual_block, identifier) {
         direction += ViewableDirectionBitMap::Left as u8;
     }
 
     if pos.z != CHUNK_SIZE - 1
-        && should_draw_betweens(block_states, world, pos, Vector3::new(0, 0, 1), identifier)
+        && should_draw_betweens(block_states, world, pos, Vector3::new(0, 0, 1), visual_block, identifier)
     {
         direction += ViewableDirectionBitMap::Back as u8;
     }
 
-    if pos.z != 0 && should_draw_betweens(block_states, world, pos, Vector3::new(0, 0, -1), identifier) {
+    if pos.z != 0 && should_draw_betweens(block_states, world, pos, Vector3::new(0, 0, -1), visual_block, identifier) {
         direction += ViewableDirectionBitMap::Front as u8;
     }
 
     ViewableDirection(direction)
 }
 
+/// The top of the highest part of a block
+fn block_height(visual_block: &VisualBlock) -> f32 {
+    visual_block
+        .bounding_boxes
+        .iter()
+        .map(|aabb| aabb.bottom_left.y + aabb.size.y)
+        .fold(0.0, f32::max)
+}
+
 fn should_draw_betweens(
     block_states: &BlockStates,
     world: &ChunkDataStorage,
     pos: Vector3<usize>,
     offset: Vector3<isize>,
+    src_visual_block: &VisualBlock,
     src_block_identifier: &str,
 ) -> bool {
     let block_pos = Vector3::new(
@@ -212,6 +223,11 @@ fn should_draw_betweens(
 
     // If its the same block we don't want borders drawn between them, or if they're both waterlogged
     if (visual_block.translucent) && block.get_identifier() == src_block_identifier {
+        // Unless the neighbour is lower, such as shallower water, leaving some of the side showing
+        if offset.y == 0 && block_height(visual_block) < block_height(src_visual_block) {
+            return true;
+        }
+
         return visual_block.draw_betweens;
     }
     if !visual_block.full {
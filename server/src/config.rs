@@ -8,7 +8,34 @@ use std::io::{BufReader, BufWriter};
 pub struct ServerConfig {
     pub port: u16,
     pub save_world: bool,
-    pub world_type: WorldType
+    pub world_type: WorldType,
+    /// Chunks within this many chunks of spawn stay loaded even when no player is nearby
+    #[serde(default = "default_spawn_keep_alive")]
+    pub spawn_keep_alive: i32,
+    /// Seconds between saves of changed chunks and players, or 0 to only save on shutdown
+    #[serde(default = "default_autosave_interval")]
+    pub autosave_interval: u64,
+    /// Seed used when creating a new world, random if not set. Existing worlds keep the seed in their level file
+    #[serde(default)]
+    pub seed: Option<u32>,
+    /// Path to a JSON file overriding the default world generation settings
+    #[serde(default)]
+    pub generation_preset: Option<String>,
+    /// Server ticks between pipe networks pulling items out of the inventories feeding them
+    #[serde(default = "default_pipe_transfer_interval")]
+    pub pipe_transfer_interval: u64,
+    /// The most items pulled from each inventory at once
+    #[serde(default = "default_pipe_transfer_amount")]
+    pub pipe_transfer_amount: u32,
+    /// How fast items travel through pipes, in blocks per second
+    #[serde(default = "default_pipe_item_speed")]
+    pub pipe_item_speed: f32,
+    /// How many blocks in each loaded chunk are picked for a random tick every server tick
+    #[serde(default = "default_random_tick_speed")]
+    pub random_tick_speed: u32,
+    /// Names of the players allowed to use moderation commands
+    #[serde(default)]
+    pub admins: Vec<String>
 }
 
 impl Default for ServerConfig {
@@ -16,11 +43,44 @@ impl Default for ServerConfig {
         ServerConfig {
             port: 25568,
             save_world: true,
-            world_type: WorldType::Regular
+            world_type: WorldType::Regular,
+            spawn_keep_alive: default_spawn_keep_alive(),
+            autosave_interval: default_autosave_interval(),
+            seed: None,
+            generation_preset: None,
+            pipe_transfer_interval: default_pipe_transfer_interval(),
+            pipe_transfer_amount: default_pipe_transfer_amount(),
+            pipe_item_speed: default_pipe_item_speed(),
+            random_tick_speed: default_random_tick_speed(),
+            admins: vec![]
         }
     }
 }
 
+fn default_spawn_keep_alive() -> i32 {
+    4
+}
+
+fn default_autosave_interval() -> u64 {
+    300
+}
+
+fn default_pipe_transfer_interval() -> u64 {
+    20
+}
+
+fn default_pipe_transfer_amount() -> u32 {
+    1
+}
+
+fn default_pipe_item_speed() -> f32 {
+    4.0
+}
+
+fn default_random_tick_speed() -> u32 {
+    3
+}
+
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Copy, Clone)]
 pub enum WorldType {
     Regular,
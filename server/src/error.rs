@@ -7,8 +7,18 @@ pub enum ServerError {
     Disconnect(#[from] io::Error),
     #[error("data store disconnected")]
     Serde(#[from] serde_json::Error),
+    #[error("failed to encode data")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("failed to decode data")]
+    Decode(#[from] rmp_serde::decode::Error),
+    #[error("corrupt region file: {0}")]
+    CorruptRegion(String),
     #[error("invalid header (expected {expected:?}, found {found:?})")]
     InvalidHeader { expected: String, found: String },
+    #[error("{format} version {version} is newer than the supported version {supported}")]
+    UnsupportedVersion { format: &'static str, version: u32, supported: u32 },
+    #[error("failed to migrate data: {0}")]
+    Migration(String),
     #[error("unknown data store error")]
     Unknown,
 }
@@ -1,12 +1,20 @@
 use bevy::ecs::prelude::Component;
-use rc_shared::chunk::{ChunkBlockMetadata, ChunkDataStorage, ChunkMetadata, ChunkPosition};
+use crate::game::update::ticks::ScheduledTick;
+use rc_shared::block::entity::ChunkBlockEntities;
+use rc_shared::chunk::{ChunkDataStorage, ChunkMetadata, ChunkPosition};
+use rc_shared::light::ChunkLight;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Component, Serialize, Deserialize, PartialEq, Clone)]
 pub struct ChunkData {
     pub position: ChunkPosition,
     pub world: ChunkDataStorage,
-    pub block_metadata: ChunkBlockMetadata,
+    pub block_entities: ChunkBlockEntities,
+    /// Block ticks waiting to fire in this chunk
+    pub scheduled_ticks: Vec<ScheduledTick>,
+    /// Lit once the chunk is loaded, rather than saved
+    #[serde(skip)]
+    pub light: ChunkLight,
     pub metadata: ChunkMetadata,
     pub dirty: bool
 }
@@ -16,12 +24,14 @@ impl ChunkData {
         position: ChunkPosition,
         world: ChunkDataStorage,
         metadata: ChunkMetadata,
-        block_metadata: ChunkBlockMetadata,
+        block_entities: ChunkBlockEntities,
     ) -> ChunkData {
         ChunkData {
             position,
             world,
-            block_metadata,
+            block_entities,
+            scheduled_ticks: vec![],
+            light: ChunkLight::default(),
             metadata,
             dirty: false,
         }
@@ -31,7 +41,9 @@ impl ChunkData {
         ChunkData {
             position,
             world: ChunkDataStorage::Empty,
-            block_metadata: Default::default(),
+            block_entities: Default::default(),
+            scheduled_ticks: vec![],
+            light: ChunkLight::default(),
             metadata: Default::default(),
             dirty: false,
         }
@@ -1,4 +1,6 @@
 mod world_gen;
+mod moderation;
+mod structure;
 
 use bevy::app::App;
 use bevy::ecs::system::SystemState;
@@ -18,6 +20,10 @@ use crate::game::world::data::WorldData;
 use crate::game::world::WORLD_SPAWN_LOCATION;
 use crate::transport::TransportSystem;
 use crate::game::commands::world_gen::parse_world_gen;
+use crate::game::commands::moderation::{parse_kick, parse_violations};
+use crate::game::commands::structure::{parse_structure, StructureSelections};
+use crate::game::movement::MovementValidator;
+use crate::systems::tick::ServerTick;
 
 pub struct CommandsPlugin;
 
@@ -25,6 +31,7 @@ impl Plugin for CommandsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, execute_commands);
         app.add_event::<ExecuteCommandRequest>();
+        app.init_resource::<StructureSelections>();
     }
 }
 
@@ -66,6 +73,15 @@ pub fn execute_commands(
             "wg" => {
                 parse_world_gen(args, command.user_id, world)
             }
+            "kick" => {
+                parse_kick(args, command.user_id, world)
+            }
+            "violations" => {
+                parse_violations(args, command.user_id, world)
+            }
+            "structure" => {
+                parse_structure(args, command.user_id, world)
+            }
             &_ => format!("Unknown command <{}>", command_name)
         };
 
@@ -92,11 +108,13 @@ fn parse_spawn(command: Vec<String>, user_id: UserId, world: &mut World) -> Stri
     // Move player for all other connected clients
     transform.position = Vector3::new(WORLD_SPAWN_LOCATION.x, WORLD_SPAWN_LOCATION.y, WORLD_SPAWN_LOCATION.z);
     world.entity_mut(entity).insert(DirtyPosition);
+    world.get_resource_mut::<MovementValidator>().unwrap().reset(user_id, Vector3::new(WORLD_SPAWN_LOCATION.x, WORLD_SPAWN_LOCATION.y, WORLD_SPAWN_LOCATION.z));
 
     // Move player for player
     world.send_event(SendPacket(
         Protocol::GameObjectMoved(GameObjectMoved {
             entity: game_object_id,
+            tick: world.get_resource::<ServerTick>().unwrap().0,
             x: WORLD_SPAWN_LOCATION.x,
             y: WORLD_SPAWN_LOCATION.y,
             z: WORLD_SPAWN_LOCATION.z,
@@ -0,0 +1,141 @@
+use std::cmp::Reverse;
+use bevy::prelude::World;
+use rc_networking::events::disconnect::DisconnectClient;
+use rc_shared::constants::UserId;
+use crate::config::ServerConfig;
+use crate::game::movement::MovementValidator;
+use crate::transport::TransportSystem;
+
+/// Whether the user is listed as an admin in the server config
+fn is_admin(world: &World, user_id: UserId) -> bool {
+    let Some(user) = world.get_resource::<TransportSystem>().unwrap().clients.get(&user_id) else {
+        return false;
+    };
+
+    world.get_resource::<ServerConfig>().unwrap().admins.contains(&user.name)
+}
+
+/// Lists how many moves have been rejected from each connected player
+pub fn parse_violations(command: Vec<String>, user_id: UserId, world: &mut World) -> String {
+    if !is_admin(world, user_id) {
+        return String::from("You don't have permission to use this command");
+    }
+
+    if command.len() != 1 {
+        return format!("Incorrect arguments for command <{}>", command.first().unwrap());
+    }
+
+    let validator = world.get_resource::<MovementValidator>().unwrap();
+
+    let mut players = world.get_resource::<TransportSystem>().unwrap()
+        .clients
+        .iter()
+        .map(|(uid, user)| (user.name.clone(), validator.violations(uid)))
+        .collect::<Vec<(String, u32)>>();
+
+    if players.is_empty() {
+        return String::from("No players connected");
+    }
+
+    players.sort_by_key(|(_, violations)| Reverse(*violations));
+
+    players
+        .iter()
+        .map(|(name, violations)| format!("{}: {} movement violations", name, violations))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Disconnects a player from the server
+pub fn parse_kick(command: Vec<String>, user_id: UserId, world: &mut World) -> String {
+    if !is_admin(world, user_id) {
+        return String::from("You don't have permission to use this command");
+    }
+
+    if command.len() < 2 {
+        return format!("Incorrect arguments for command <{}> Usage: /kick DarkZek [reason]", command.first().unwrap());
+    }
+
+    let target_user = command.get(1).unwrap();
+
+    // Lookup player by name
+    let user_id = {
+        let user_id = world.get_resource::<TransportSystem>().unwrap()
+            .clients
+            .iter()
+            .find(|(_, user)| &user.name == target_user)
+            .map(|(uid, _)| *uid);
+
+        if let Some(user_id) = user_id {
+            user_id
+        } else {
+            return format!("Invalid user {}", target_user);
+        }
+    };
+
+    let reason = if command.len() > 2 {
+        command[2..].join(" ")
+    } else {
+        String::from("Kicked from server.")
+    };
+
+    kick(world, user_id, reason);
+
+    format!("Kicked {}", target_user)
+}
+
+fn kick(world: &mut World, user_id: UserId, reason: String) {
+    // Closes the connection once the reason is sent, then cleans up the player as normal
+    world.send_event(DisconnectClient { client: user_id, reason });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Events, World};
+    use rc_networking::events::disconnect::DisconnectClient;
+    use rc_shared::constants::UserId;
+    use crate::config::ServerConfig;
+    use crate::game::commands::moderation::parse_kick;
+    use crate::systems::connection::GameUser;
+    use crate::transport::TransportSystem;
+
+    fn world_with_players() -> World {
+        let mut world = World::new();
+        let mut transport = TransportSystem::default();
+
+        for (user_id, name) in [(UserId(1), "Admin"), (UserId(2), "Player")] {
+            transport.clients.insert(user_id, GameUser {
+                name: name.to_string(),
+                user_id,
+                game_object_id: None,
+                loading: false,
+            });
+        }
+
+        world.insert_resource(transport);
+        world.insert_resource(ServerConfig {
+            admins: vec![String::from("Admin")],
+            ..Default::default()
+        });
+        world.init_resource::<Events<DisconnectClient>>();
+
+        world
+    }
+
+    fn kicked(world: &World) -> Vec<UserId> {
+        let events = world.resource::<Events<DisconnectClient>>();
+        events.get_reader().read(events).map(|event| event.client).collect()
+    }
+
+    #[test]
+    fn test_kick_requires_admin() {
+        let mut world = world_with_players();
+        let command = |target: &str| vec![String::from("kick"), target.to_string()];
+
+        parse_kick(command("Admin"), UserId(2), &mut world);
+        assert!(kicked(&world).is_empty());
+
+        assert_eq!(parse_kick(command("Player"), UserId(1), &mut world), "Kicked Player");
+        assert_eq!(kicked(&world), vec![UserId(2)]);
+    }
+}
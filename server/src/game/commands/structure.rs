@@ -0,0 +1,255 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::create_dir_all;
+use std::path::Path;
+use bevy::prelude::{info, Resource, World};
+use nalgebra::{Vector2, Vector3};
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::{ChunkPosition, GlobalBlockPosition};
+use rc_shared::constants::UserId;
+use rc_shared::helpers::global_to_local_position;
+use crate::game::generation::structures::template::{find_template, transform, transform_block, PaletteBlock, StructureTemplate, STRUCTURE_DIRECTORY};
+use crate::game::transform::Transform;
+use crate::game::update::block_entities::BlockEntityChangedEvent;
+use crate::game::update::BlockUpdateEvent;
+use crate::game::world::data::WorldData;
+use crate::transport::TransportSystem;
+
+/// The largest selection that can be exported, so a typo in a corner can't stall the server
+const MAX_SELECTION_VOLUME: i64 = 64 * 64 * 64;
+
+/// The corners each player has selected with `structure pos1` and `structure pos2`
+#[derive(Resource, Default)]
+pub struct StructureSelections {
+    selections: HashMap<UserId, (Option<GlobalBlockPosition>, Option<GlobalBlockPosition>)>,
+}
+
+/// Selects a region of the world, exports it as a structure template and pastes templates back into the world
+pub fn parse_structure(command: Vec<String>, user_id: UserId, world: &mut World) -> String {
+    let usage = format!(
+        "Incorrect arguments for command <{}>. Usage: /structure pos1|pos2 [x y z], /structure export <name>, /structure paste <name> [rotation] [mirror]",
+        command.first().unwrap()
+    );
+
+    let Some(action) = command.get(1) else {
+        return usage;
+    };
+
+    match action.as_str() {
+        "pos1" | "pos2" => {
+            let pos = if command.len() == 5 {
+                match parse_position(&command[2..5]) {
+                    Some(pos) => pos,
+                    None => return usage,
+                }
+            } else if command.len() == 2 {
+                match player_block_position(user_id, world) {
+                    Some(pos) => pos,
+                    None => return String::from("Couldn't find your position"),
+                }
+            } else {
+                return usage;
+            };
+
+            let mut selections = world.get_resource_mut::<StructureSelections>().unwrap();
+            let selection = selections.selections.entry(user_id).or_default();
+
+            if action == "pos1" {
+                selection.0 = Some(pos);
+            } else {
+                selection.1 = Some(pos);
+            }
+
+            format!("Set {} to {} {} {}", action, pos.x, pos.y, pos.z)
+        }
+        "export" => {
+            let Some(name) = command.get(2).filter(|name| is_valid_name(name)) else {
+                return usage;
+            };
+
+            export_selection(name, user_id, world)
+        }
+        "paste" => {
+            let Some(name) = command.get(2).filter(|name| is_valid_name(name)) else {
+                return usage;
+            };
+
+            let rotation = match command.get(3).map(|rotation| rotation.parse::<u8>()) {
+                None => 0,
+                Some(Ok(rotation)) if rotation < 4 => rotation,
+                Some(_) => return format!("Invalid rotation {}, expected 0-3 quarter turns", command.get(3).unwrap()),
+            };
+
+            let mirror = match command.get(4).map(|mirror| mirror.as_str()) {
+                None => false,
+                Some("mirror") => true,
+                Some(v) => return format!("Unknown argument {}", v),
+            };
+
+            paste_template(name, rotation, mirror, user_id, world)
+        }
+        v => {
+            format!("Unknown argument {}", v)
+        }
+    }
+}
+
+fn export_selection(name: &str, user_id: UserId, world: &mut World) -> String {
+    let selection = world.get_resource::<StructureSelections>().unwrap().selections.get(&user_id).copied();
+
+    let Some((Some(first), Some(second))) = selection else {
+        return String::from("Select both corners with /structure pos1 and /structure pos2 first");
+    };
+
+    let min = first.inf(&second);
+    let max = first.sup(&second);
+    let size = (max - min).add_scalar(1).cast::<i64>();
+
+    if size.x * size.y * size.z > MAX_SELECTION_VOLUME {
+        return format!("Selection is too large, the most blocks that can be exported is {}", MAX_SELECTION_VOLUME);
+    }
+
+    let world_data = world.get_resource::<WorldData>().unwrap();
+    let block_states = world.get_resource::<BlockStates>().unwrap();
+
+    let mut blocks = vec![];
+    let mut entities = vec![];
+
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                let pos = Vector3::new(x, y, z);
+
+                let Some(block_id) = world_data.get_block_id(pos) else {
+                    return format!("Chunk containing {} {} {} isn't loaded", x, y, z);
+                };
+
+                // Air is left out so pasting the structure doesn't clear its surroundings
+                if block_id == 0 {
+                    continue;
+                }
+
+                blocks.push((pos - min, PaletteBlock::new(&block_states.get_block_from_id(block_id))));
+
+                if let Some(entity) = world_data.get_block_entity(pos) {
+                    entities.push((pos - min, entity.clone()));
+                }
+            }
+        }
+    }
+
+    // Anchored at the bottom centre, so the structure pastes standing on the ground where the player is
+    let anchor = Vector3::new((max.x - min.x) / 2, 0, (max.z - min.z) / 2);
+    let template = StructureTemplate::capture(format!("custom::structure::{}", name), blocks, entities, anchor);
+    let block_count = template.blocks.len();
+
+    let path = Path::new(STRUCTURE_DIRECTORY).join(format!("{}.json", name));
+
+    if let Err(err) = create_dir_all(STRUCTURE_DIRECTORY).map_err(Into::into).and_then(|_| template.save(&path)) {
+        return format!("Error writing structure {}: {:?}", name, err);
+    }
+
+    info!("Exported structure {} with {} blocks", path.display(), block_count);
+    format!("Exported {} blocks to structure {}", block_count, name)
+}
+
+fn paste_template(name: &str, rotation: u8, mirror: bool, user_id: UserId, world: &mut World) -> String {
+    let Some(path) = find_template(name) else {
+        return format!("Unknown structure {}", name);
+    };
+
+    let template = match StructureTemplate::load(&path) {
+        Ok(template) => template,
+        Err(err) => return format!("Error reading structure {}: {:?}", name, err),
+    };
+
+    let block_states = world.get_resource::<BlockStates>().unwrap();
+
+    let blocks = match template.resolve_blocks(block_states) {
+        Ok(blocks) => blocks,
+        Err(err) => return format!("Invalid structure {}: {}", name, err),
+    };
+
+    // Blocks keep the entity saved with them, and the rest get a new one.
+    // Blocks are turned with the structure so their connections still line up.
+    let mut saved_entities = template
+        .block_entities()
+        .map(|(pos, entity)| (pos, entity.clone()))
+        .collect::<HashMap<_, _>>();
+    let blocks = blocks
+        .into_iter()
+        .map(|(pos, block_id)| {
+            let block = transform_block(&block_states.get_block_from_id(block_id), rotation, mirror);
+            let entity = saved_entities
+                .remove(&pos)
+                .or_else(|| block.create_entity());
+            (pos, block.get_id(), entity)
+        })
+        .collect::<Vec<_>>();
+
+    let Some(origin) = player_block_position(user_id, world) else {
+        return String::from("Couldn't find your position");
+    };
+
+    let mut world_data = world.get_resource_mut::<WorldData>().unwrap();
+
+    let mut changed_chunks = HashSet::new();
+    let mut placed = Vec::new();
+    let mut changed_entities = Vec::new();
+    let mut skipped = 0;
+
+    for (pos, block_id, entity) in blocks {
+        let pos = origin + transform(pos, rotation, mirror);
+
+        if world_data.set_block_id(pos, block_id).is_some() {
+            changed_chunks.insert(global_to_local_position(pos).0);
+            placed.push(BlockUpdateEvent { pos, block_id });
+
+            let had_entity = world_data.set_block_entity(pos, entity.clone()).is_some();
+            if had_entity || entity.is_some() {
+                changed_entities.push(BlockEntityChangedEvent { pos });
+            }
+        } else {
+            skipped += 1;
+        }
+    }
+
+    // Resend the changed chunks to every player that has them loaded
+    for pos in &changed_chunks {
+        world_data.chunks.get_mut(pos).unwrap().dirty = true;
+    }
+
+    for column in changed_chunks.iter().map(|pos: &ChunkPosition| Vector2::new(pos.x, pos.z)).collect::<HashSet<_>>() {
+        world_data.update_column(column);
+    }
+
+    world.send_event_batch(placed);
+    world.send_event_batch(changed_entities);
+
+    if skipped > 0 {
+        format!("Pasted structure {}, skipping {} blocks in chunks that aren't loaded", name, skipped)
+    } else {
+        format!("Pasted structure {}", name)
+    }
+}
+
+/// Names become file names, so only allow characters that can't escape the structure directory
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse_position(args: &[String]) -> Option<GlobalBlockPosition> {
+    Some(Vector3::new(
+        args[0].parse().ok()?,
+        args[1].parse().ok()?,
+        args[2].parse().ok()?,
+    ))
+}
+
+/// The block the player is standing in
+fn player_block_position(user_id: UserId, world: &mut World) -> Option<GlobalBlockPosition> {
+    let game_object_id = world.get_resource::<TransportSystem>()?.clients.get(&user_id)?.game_object_id?;
+    let entity = *world.get_resource::<WorldData>()?.game_objects_mapping.get(&game_object_id)?;
+    let transform = world.query::<&Transform>().get(world, entity).ok()?;
+
+    Some(transform.position.map(|v| v.floor() as i32))
+}
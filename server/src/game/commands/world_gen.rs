@@ -6,6 +6,7 @@ use rc_networking::protocol::clientbound::unload_all_chunks::UnloadAllChunks;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::SendPacket;
 use rc_shared::constants::UserId;
+use rc_shared::light::LightUpdate;
 use crate::game::chunk::ChunkData;
 use crate::game::generation::ChunkGenerationConfig;
 use crate::game::world::data::WorldData;
@@ -17,7 +18,7 @@ pub fn parse_world_gen(
     world: &mut World
 ) -> String {
     if command.len() == 1 {
-        return format!("Incorrect arguments for command <{}>. Options: regen, mod", command.get(0).unwrap());
+        return format!("Incorrect arguments for command <{}>. Options: regen, mod, seed", command.get(0).unwrap());
     }
 
     match command.get(1).unwrap().as_str() {
@@ -28,6 +29,11 @@ pub fn parse_world_gen(
 
             format!("Regenerated {chunks} chunks")
         }
+        "seed" => {
+            let seed = world.get_resource::<ChunkGenerationConfig>().unwrap().seed;
+
+            format!("World seed is {seed}")
+        }
         "mod" => {
             let mut config = world.get_resource_mut::<ChunkGenerationConfig>().unwrap();
 
@@ -45,13 +51,21 @@ pub fn parse_world_gen(
                 "grey.ground_scale_2" => { config.greybox_map_config.ground_scale_2 = value; }
                 "grey.ground_scale_3" => { config.greybox_map_config.ground_scale_3 = value; }
                 "grey.ground_scale_4" => { config.greybox_map_config.ground_scale_4 = value; }
-                "grey.cave_scale" => { config.greybox_map_config.cave_scale = value as f64; }
                 "grey.hilly_scaler_1" => { config.greybox_map_config.hilly_scaler_1 = value as f64; }
                 "grey.hilly_scaler_2" => { config.greybox_map_config.hilly_scaler_2 = value as f64; }
                 "grey.terrain_scaler" => { config.greybox_map_config.terrain_scaler = value as f64; }
                 "grey.hilly_pow" => { config.greybox_map_config.hilly_pow = value as f64; }
                 "grey.ground_scaler_1" => { config.greybox_map_config.ground_scaler_1 = value as f64; }
                 "grey.ground_scaler_2" => { config.greybox_map_config.ground_scaler_2 = value as f64; }
+
+                "cave.cheese_scale" => { config.cave_config.cheese_scale = value; }
+                "cave.cheese_threshold" => { config.cave_config.cheese_threshold = value as f64; }
+                "cave.worm_scale" => { config.cave_config.worm_scale = value; }
+                "cave.worm_width" => { config.cave_config.worm_width = value as f64; }
+                "cave.ravine_scale" => { config.cave_config.ravine_scale = value; }
+                "cave.ravine_region_scale" => { config.cave_config.ravine_region_scale = value; }
+                "cave.ravine_width" => { config.cave_config.ravine_width = value as f64; }
+                "cave.ravine_depth" => { config.cave_config.ravine_depth = value as f64; }
                 v => {
                     return format!("Invalid key {}", v)
                 }
@@ -75,16 +89,22 @@ fn reload_chunks(
     info!("Regenerating all chunks");
 
     // Regenerate all chunks
-    for (pos, chunk) in &mut world_data.chunks {
+    let WorldData { chunks, unsaved_chunks, .. } = &mut *world_data;
+    for (pos, chunk) in chunks {
         let data = ChunkData::generate(*pos, &resource);
         *chunk = data;
         chunk.dirty = true;
+        unsaved_chunks.insert(*pos);
     }
 
     for (pos, _) in world_data.chunks_columns.clone() {
         world_data.update_column(pos);
     }
 
+    // The regenerated chunks are lit from scratch
+    let regenerated = world_data.chunks.keys().map(|pos| LightUpdate::Chunk(*pos)).collect::<Vec<_>>();
+    world_data.light_updates.extend(regenerated);
+
     // Unload all chunks for all users, since we will resend them soon
     for (user, _) in &chunk_system.user_loaded_chunks {
         event_writer.send(SendPacket(
@@ -10,11 +10,13 @@ use crate::game::game_object::GameObject;
 use crate::game::transform::Transform;
 use rc_shared::helpers::global_to_local_position;
 use crate::systems::chunk::ChunkSystem;
+use crate::systems::tick::ServerTick;
 
 pub fn sync_entities(
     transform_query: Query<(&Transform, &PlayerGameObjectData, &GameObject)>,
     entities_query: Query<(Entity, &Transform, &GameObject, Option<&DirtyPosition>, Option<&DirtyRotation>), Or<(With<DirtyPosition>, With<DirtyRotation>)>>,
     chunk_system: Res<ChunkSystem>,
+    tick: Res<ServerTick>,
     mut ew: EventWriter<SendPacket>,
     mut commands: Commands
 ) {
@@ -39,13 +41,13 @@ pub fn sync_entities(
         for (player_transform, player_data, player_game_object) in transform_query.iter() {
 
             // If player has chunk loaded
-            if !chunk_system.user_loaded_chunks.get(&player_data.user_id).unwrap().contains(&chunk_pos) {
+            if !chunk_system.user_loaded_chunks.get(&player_data.user_id).is_some_and(|chunks| chunks.contains(&chunk_pos)) {
                 continue
             }
 
             if game_object.id == player_game_object.id {
                 // Don't send player updates for their own player
-                // Players currently have full authority over their position
+                // Their position came from them, and is corrected by MovementValidator if rejected
                 continue
             }
 
@@ -55,6 +57,7 @@ pub fn sync_entities(
                     Protocol::GameObjectMoved(
                         GameObjectMoved {
                             entity: game_object.id,
+                            tick: tick.0,
                             x: transform.position.x,
                             y: transform.position.y,
                             z: transform.position.z,
@@ -69,6 +72,7 @@ pub fn sync_entities(
                     Protocol::GameObjectRotated(
                         GameObjectRotated {
                             entity: game_object.id,
+                            tick: tick.0,
                             x: transform.rotation.coords.x,
                             y: transform.rotation.coords.y,
                             z: transform.rotation.coords.z,
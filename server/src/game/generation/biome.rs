@@ -0,0 +1,317 @@
+use std::cell::RefCell;
+use crate::game::generation::blocks::GenerationBlocks;
+use crate::game::generation::noise::SimplexNoise;
+use rc_shared::biome::EnvironmentEntry;
+use rc_shared::block::BlockId;
+
+/// How far apart two environments can be before the further biome stops affecting a column.
+/// Larger values give wider transitions between biomes.
+const BLEND_DISTANCE: f64 = 0.3;
+
+/// Scale of the noise used to choose between biomes where they blend
+const BIOME_DITHER_SCALE: f32 = 4.0;
+
+/// Weights below this are ignored, so columns deep inside a biome only consider that biome
+const MIN_BLEND_WEIGHT: f64 = 0.01;
+
+/// A structure that can spawn in a biome, and how often
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureSpawn {
+    pub structure: StructureType,
+    /// Columns with less vegetation than this never spawn the structure
+    pub min_vegetation: f64,
+    /// Structure noise must be above this to spawn the structure, so higher values give fewer structures
+    pub threshold: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StructureType {
+    Tree,
+}
+
+/// Blocks placed on top of the surface, such as long grass
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundCover {
+    pub block: BlockId,
+    /// Cover noise must be above this to place the block
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biome {
+    pub identifier: &'static str,
+    /// The climate this biome is centred on, from warm to cold
+    pub climate: f64,
+    /// The terrain this biome is centred on, from flat to hilly
+    pub terrain: f64,
+
+    /// The top block of the ground
+    pub surface_block: BlockId,
+    /// The blocks beneath the surface, before reaching stone
+    pub subsurface_block: BlockId,
+    pub subsurface_depth: i32,
+    pub ground_cover: Option<GroundCover>,
+
+    /// Multiplies how far the ground strays from the base height in phase 2
+    pub height_scale: f64,
+    /// Raises or lowers the ground in phase 2
+    pub height_offset: f64,
+    /// Multiplies how common caves are beneath this biome
+    pub cave_density: f64,
+
+    pub structures: Vec<StructureSpawn>,
+}
+
+/// Every biome that can generate, which columns are classified into by their environment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BiomeRegistry {
+    pub biomes: Vec<Biome>,
+}
+
+impl BiomeRegistry {
+    pub fn new(blocks: &GenerationBlocks) -> BiomeRegistry {
+        BiomeRegistry {
+            biomes: vec![
+                Biome {
+                    identifier: "plains",
+                    climate: 0.5,
+                    terrain: 0.2,
+                    surface_block: blocks.grass,
+                    subsurface_block: blocks.dirt,
+                    subsurface_depth: 3,
+                    ground_cover: Some(GroundCover { block: blocks.long_grass, threshold: 0.6 }),
+                    height_scale: 0.7,
+                    height_offset: 0.0,
+                    cave_density: 1.0,
+                    structures: vec![
+                        StructureSpawn { structure: StructureType::Tree, min_vegetation: 0.5, threshold: 0.95 },
+                    ],
+                },
+                Biome {
+                    identifier: "forest",
+                    climate: 0.5,
+                    terrain: 0.55,
+                    surface_block: blocks.grass,
+                    subsurface_block: blocks.dirt,
+                    subsurface_depth: 3,
+                    ground_cover: Some(GroundCover { block: blocks.long_grass, threshold: 0.7 }),
+                    height_scale: 1.0,
+                    height_offset: 0.0,
+                    cave_density: 1.0,
+                    structures: vec![
+                        StructureSpawn { structure: StructureType::Tree, min_vegetation: 0.2, threshold: 0.85 },
+                    ],
+                },
+                Biome {
+                    identifier: "desert",
+                    climate: 0.0,
+                    terrain: 0.3,
+                    surface_block: blocks.sand,
+                    subsurface_block: blocks.sand,
+                    subsurface_depth: 4,
+                    ground_cover: None,
+                    height_scale: 0.5,
+                    height_offset: -1.0,
+                    cave_density: 0.8,
+                    structures: vec![],
+                },
+                Biome {
+                    identifier: "tundra",
+                    climate: 1.0,
+                    terrain: 0.4,
+                    surface_block: blocks.dirt,
+                    subsurface_block: blocks.dirt,
+                    subsurface_depth: 2,
+                    ground_cover: Some(GroundCover { block: blocks.long_grass, threshold: 0.9 }),
+                    height_scale: 0.8,
+                    height_offset: 0.0,
+                    cave_density: 0.9,
+                    structures: vec![
+                        StructureSpawn { structure: StructureType::Tree, min_vegetation: 0.6, threshold: 0.97 },
+                    ],
+                },
+                Biome {
+                    identifier: "mountains",
+                    climate: 0.5,
+                    terrain: 1.0,
+                    surface_block: blocks.stone,
+                    subsurface_block: blocks.stone,
+                    subsurface_depth: 0,
+                    ground_cover: None,
+                    height_scale: 1.6,
+                    height_offset: 6.0,
+                    cave_density: 1.3,
+                    structures: vec![],
+                },
+            ],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> &Biome {
+        &self.biomes[index]
+    }
+
+    /// Finds how much each biome contributes to a column with this environment.
+    /// Weights change smoothly with the environment, so blended values have no seams at biome borders.
+    pub fn blend(&self, environment: &EnvironmentEntry) -> BiomeBlend {
+        let mut blend = BiomeBlend::default();
+        self.blend_into(environment, &mut blend);
+        blend
+    }
+
+    /// Blends like `blend`, reusing the weights of an earlier blend so columns can be blended without allocating
+    pub fn blend_into(&self, environment: &EnvironmentEntry, blend: &mut BiomeBlend) {
+        let weights = &mut blend.weights;
+
+        weights.clear();
+        weights.extend(self.biomes.iter().enumerate().map(|(i, biome)| {
+            let distance_squared = (environment.climate - biome.climate).powi(2)
+                + (environment.terrain - biome.terrain).powi(2);
+
+            (i, (-distance_squared / BLEND_DISTANCE.powi(2)).exp())
+        }));
+
+        // Far from every biome all weights underflow, so fall back to the closest one
+        let strongest = weights
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("no biomes registered");
+
+        weights.retain(|(_, weight)| *weight >= strongest.1 * MIN_BLEND_WEIGHT);
+
+        let total = weights.iter().map(|(_, weight)| weight).sum::<f64>();
+
+        if total > 0.0 {
+            weights.iter_mut().for_each(|(_, weight)| *weight /= total);
+        } else {
+            weights.clear();
+            weights.push((strongest.0, 1.0));
+        }
+    }
+}
+
+/// The biomes contributing to a column, with weights that sum to 1
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BiomeBlend {
+    pub weights: Vec<(usize, f64)>,
+}
+
+impl BiomeBlend {
+    /// The biome contributing the most to this column
+    pub fn dominant(&self) -> usize {
+        self.weights
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+            .0
+    }
+
+    /// Chooses a biome in proportion to its weight using `t` in the range 0-1, so borders between
+    /// biomes are ragged rather than straight when `t` comes from noise
+    pub fn pick(&self, t: f64) -> usize {
+        let mut remaining = t;
+
+        for (biome, weight) in &self.weights {
+            if remaining < *weight {
+                return *biome;
+            }
+            remaining -= weight;
+        }
+
+        self.weights.last().unwrap().0
+    }
+
+    /// Averages a value across the contributing biomes by their weights
+    pub fn blend_value(&self, registry: &BiomeRegistry, value: impl Fn(&Biome) -> f64) -> f64 {
+        self.weights
+            .iter()
+            .map(|(biome, weight)| value(registry.get(*biome)) * weight)
+            .sum()
+    }
+}
+
+/// Chooses the biome of each column, consistently between generation phases
+pub struct BiomeSelector<'a> {
+    registry: &'a BiomeRegistry,
+    noise: SimplexNoise,
+    /// Reused between columns
+    blend: RefCell<BiomeBlend>,
+}
+
+impl<'a> BiomeSelector<'a> {
+    pub fn new(seed: u32, registry: &'a BiomeRegistry) -> BiomeSelector<'a> {
+        BiomeSelector {
+            registry,
+            noise: SimplexNoise::new(seed.wrapping_add(20)).with_scale(BIOME_DITHER_SCALE),
+            blend: RefCell::new(BiomeBlend::default()),
+        }
+    }
+
+    /// The biome of the column at `x`, `z`, picked randomly between the biomes blended there
+    pub fn biome(&self, x: i32, z: i32, environment: &EnvironmentEntry) -> &'a Biome {
+        self.registry.get(self.biome_index(x, z, environment))
+    }
+
+    /// The index in the registry of the biome of the column at `x`, `z`
+    pub fn biome_index(&self, x: i32, z: i32, environment: &EnvironmentEntry) -> usize {
+        let t = self.noise.sample_2d(x, z).clamp(0.0, 1.0);
+
+        let mut blend = self.blend.borrow_mut();
+        self.registry.blend_into(environment, &mut blend);
+        blend.pick(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::generation::biome::BiomeRegistry;
+    use crate::game::generation::blocks::GenerationBlocks;
+    use rc_shared::block::test_block_states;
+    use rc_shared::biome::EnvironmentEntry;
+
+    fn environment(climate: f64, terrain: f64) -> EnvironmentEntry {
+        EnvironmentEntry {
+            climate,
+            terrain,
+            vegetation: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_classify_biomes() {
+        let registry = BiomeRegistry::new(&GenerationBlocks::new(test_block_states()));
+        let classify = |climate, terrain| {
+            registry.get(registry.blend(&environment(climate, terrain)).dominant()).identifier
+        };
+
+        assert_eq!(classify(0.5, 0.2), "plains");
+        assert_eq!(classify(0.5, 0.6), "forest");
+        assert_eq!(classify(-0.2, 0.3), "desert");
+        assert_eq!(classify(1.2, 0.4), "tundra");
+        assert_eq!(classify(0.5, 1.2), "mountains");
+
+        // Environments far outside of every biome still get the closest one
+        assert_eq!(classify(0.5, 40.0), "mountains");
+    }
+
+    #[test]
+    fn test_blend_is_smooth() {
+        let registry = BiomeRegistry::new(&GenerationBlocks::new(test_block_states()));
+        let height_scale = |terrain| {
+            registry
+                .blend(&environment(0.5, terrain))
+                .blend_value(&registry, |biome| biome.height_scale)
+        };
+
+        // Moving from plains to mountains never jumps suddenly
+        let mut terrain = 0.2;
+        while terrain < 1.0 {
+            assert!((height_scale(terrain + 0.01) - height_scale(terrain)).abs() < 0.05);
+            terrain += 0.01;
+        }
+
+        let blend = registry.blend(&environment(0.5, 0.375));
+        assert!((blend.weights.iter().map(|(_, weight)| weight).sum::<f64>() - 1.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,38 @@
+use rc_shared::block::{BlockId, BlockStates};
+
+/// The blocks generation places itself. Block ids are assigned when the server starts, so these are looked up
+/// by identifier once block states are known
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationBlocks {
+    pub stone: BlockId,
+    pub dirt: BlockId,
+    pub grass: BlockId,
+    pub long_grass: BlockId,
+    pub sand: BlockId,
+    pub ruby_ore: BlockId,
+    pub log: BlockId,
+    pub leaves: BlockId,
+}
+
+impl GenerationBlocks {
+    pub fn new(block_states: &BlockStates) -> GenerationBlocks {
+        GenerationBlocks {
+            stone: block_id(block_states, "mcv3::block::Stone"),
+            dirt: block_id(block_states, "mcv3::block::Dirt"),
+            grass: block_id(block_states, "mcv3::block::Grass"),
+            long_grass: block_id(block_states, "mcv3::block::LongGrass"),
+            sand: block_id(block_states, "mcv3::block::Sand"),
+            ruby_ore: block_id(block_states, "mcv3::block::RubyOre"),
+            log: block_id(block_states, "mcv3::block::WoodLog"),
+            leaves: block_id(block_states, "mcv3::block::Leaves"),
+        }
+    }
+}
+
+fn block_id(block_states: &BlockStates, identifier: &str) -> BlockId {
+    block_states
+        .get_by_identifier(identifier)
+        .unwrap_or_else(|| panic!("{} is used by world generation but isn't registered", identifier))
+        .1
+        .get_id()
+}
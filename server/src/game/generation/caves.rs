@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+use crate::game::generation::biome::{BiomeBlend, BiomeRegistry};
+use crate::game::generation::noise::SimplexNoise;
+use crate::game::generation::phase2::clamp_map;
+use nalgebra::Vector3;
+use rc_shared::biome::EnvironmentEntry;
+use serde::{Deserialize, Serialize};
+
+/// Cheese caves don't generate closer to the surface than this, so they stay underground
+const CHEESE_MIN_DEPTH: f64 = 12.0;
+
+/// Cheese caves reach their full size this far below `CHEESE_MIN_DEPTH`
+const CHEESE_FULL_DEPTH: f64 = 32.0;
+
+/// Worm caves are squashed vertically by this much, so they wind sideways more than up and down
+const WORM_VERTICAL_SQUASH: f64 = 2.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CaveConfig {
+    /// Size of the large open caverns
+    pub cheese_scale: f32,
+    /// Cheese noise must be above this to carve, so higher values give fewer caverns
+    pub cheese_threshold: f64,
+
+    /// Length of the bends in winding tunnels
+    pub worm_scale: f32,
+    /// Thickness of winding tunnels
+    pub worm_width: f64,
+
+    /// Length of the bends in ravines
+    pub ravine_scale: f32,
+    /// Size of the areas that ravines can appear in
+    pub ravine_region_scale: f32,
+    /// Width of ravines at the surface
+    pub ravine_width: f64,
+    /// The deepest a ravine can cut below the surface
+    pub ravine_depth: f64,
+}
+
+impl Default for CaveConfig {
+    fn default() -> Self {
+        Self {
+            cheese_scale: 40.0,
+            cheese_threshold: 0.85,
+            worm_scale: 48.0,
+            worm_width: 0.06,
+            ravine_scale: 96.0,
+            ravine_region_scale: 256.0,
+            ravine_width: 0.02,
+            ravine_depth: 40.0,
+        }
+    }
+}
+
+/// Decides which blocks below the ground are hollowed out into caves.
+/// Every position is decided from its absolute coordinates alone, so caves line up across chunk borders
+/// and later phases can check for caves outside of the chunk being generated.
+pub struct CaveCarver<'a> {
+    config: &'a CaveConfig,
+    biomes: &'a BiomeRegistry,
+    cheese_noise: SimplexNoise,
+    worm_noise_1: SimplexNoise,
+    worm_noise_2: SimplexNoise,
+    ravine_noise: SimplexNoise,
+    ravine_region_noise: SimplexNoise,
+    /// Reused between columns
+    blend: RefCell<BiomeBlend>,
+}
+
+impl<'a> CaveCarver<'a> {
+    pub fn new(seed: u32, config: &'a CaveConfig, biomes: &'a BiomeRegistry) -> CaveCarver<'a> {
+        CaveCarver {
+            config,
+            biomes,
+            cheese_noise: SimplexNoise::new(seed.wrapping_add(300)).with_scale(config.cheese_scale),
+            worm_noise_1: SimplexNoise::new(seed.wrapping_add(301)).with_scale(config.worm_scale),
+            worm_noise_2: SimplexNoise::new(seed.wrapping_add(302)).with_scale(config.worm_scale),
+            ravine_noise: SimplexNoise::new(seed.wrapping_add(303)).with_scale(config.ravine_scale),
+            ravine_region_noise: SimplexNoise::new(seed.wrapping_add(304)).with_scale(config.ravine_region_scale),
+            blend: RefCell::new(BiomeBlend::default()),
+        }
+    }
+
+    /// How common caves are in a column, blended between biomes
+    pub fn density(&self, environment: &EnvironmentEntry) -> f64 {
+        let mut blend = self.blend.borrow_mut();
+        self.biomes.blend_into(environment, &mut blend);
+        blend.blend_value(self.biomes, |biome| biome.cave_density)
+    }
+
+    /// Whether the block at `pos` is inside of a cave, for a column with its ground at `ground_level`
+    /// and cave density from `density`
+    pub fn is_carved(&self, pos: Vector3<i32>, ground_level: i32, density: f64) -> bool {
+        let depth = (ground_level - pos.y) as f64;
+
+        if depth <= 0.0 || density <= 0.0 {
+            return false;
+        }
+
+        self.is_ravine(pos, depth, density)
+            || self.is_worm(pos, density)
+            || self.is_cheese(pos, depth, density)
+    }
+
+    /// Whether a cave breaks through the ground of a column, leaving nothing for grass or structures to stand on
+    pub fn is_cave_mouth(&self, x: i32, z: i32, ground_level: i32, environment: &EnvironmentEntry) -> bool {
+        self.is_carved(Vector3::new(x, ground_level - 1, z), ground_level, self.density(environment))
+    }
+
+    /// Large caverns, which open up the deeper they are
+    fn is_cheese(&self, pos: Vector3<i32>, depth: f64, density: f64) -> bool {
+        if depth < CHEESE_MIN_DEPTH {
+            return false;
+        }
+
+        let size = clamp_map(CHEESE_MIN_DEPTH..CHEESE_MIN_DEPTH + CHEESE_FULL_DEPTH, 0.0..1.0, depth) * density;
+
+        self.cheese_noise.sample_3d(pos.x, pos.y, pos.z) * size > self.config.cheese_threshold
+    }
+
+    /// Tunnels where two noise fields both cross their midpoint, which can reach the surface
+    fn is_worm(&self, pos: Vector3<i32>, density: f64) -> bool {
+        let y = pos.y as f64 * WORM_VERTICAL_SQUASH;
+        let width = self.config.worm_width * density;
+
+        (self.worm_noise_1.sample_3d(pos.x as f64, y, pos.z as f64) - 0.5).abs() < width
+            && (self.worm_noise_2.sample_3d(pos.x as f64, y, pos.z as f64) - 0.5).abs() < width
+    }
+
+    /// Narrow cracks cut down from the surface, only in some areas of the world
+    fn is_ravine(&self, pos: Vector3<i32>, depth: f64, density: f64) -> bool {
+        let max_depth = clamp_map(
+            0.7..0.9,
+            0.0..self.config.ravine_depth * density,
+            self.ravine_region_noise.sample_2d(pos.x, pos.z)
+        );
+
+        if depth >= max_depth {
+            return false;
+        }
+
+        // Narrows towards the bottom
+        let width = self.config.ravine_width * (1.0 - depth / max_depth);
+
+        (self.ravine_noise.sample_2d(pos.x, pos.z) - 0.5).abs() < width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::generation::biome::BiomeRegistry;
+    use crate::game::generation::blocks::GenerationBlocks;
+    use crate::game::generation::caves::{CaveCarver, CaveConfig};
+    use crate::game::generation::phase1::{generate_environment_map, EnvironmentMapConfig};
+    use crate::game::generation::phase2::{generate_greybox_chunk, GreyboxMapConfig};
+    use rc_shared::block::test_block_states;
+    use nalgebra::Vector3;
+    use rc_shared::CHUNK_SIZE;
+
+    #[test]
+    fn test_caves_continuous_across_chunks() {
+        let seed = 42;
+        let blocks = GenerationBlocks::new(test_block_states());
+        let biomes = BiomeRegistry::new(&blocks);
+        let cave_config = CaveConfig::default();
+        let carver = CaveCarver::new(seed, &cave_config, &biomes);
+
+        let mut carved = 0;
+
+        // Neighbouring chunks, so caves crossing the border between them are checked from both sides
+        for pos in [Vector3::new(0, -2, 0), Vector3::new(1, -2, 0), Vector3::new(0, -1, 0)] {
+            let environment = generate_environment_map(seed, pos, &EnvironmentMapConfig::default());
+            let (chunk, heightmap) = generate_greybox_chunk(
+                seed,
+                pos,
+                &environment,
+                &GreyboxMapConfig::default(),
+                &cave_config,
+                &biomes,
+                &blocks
+            );
+
+            for x in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_SIZE {
+                    for z in 0..CHUNK_SIZE {
+                        let absolute = pos * CHUNK_SIZE as i32 + Vector3::new(x, y, z).cast::<i32>();
+                        let ground_level = *heightmap.get([absolute.x, absolute.z]).unwrap();
+                        let density = carver.density(environment.get([absolute.x, absolute.z]).unwrap());
+
+                        let is_cave = carver.is_carved(absolute, ground_level, density);
+                        let is_stone = absolute.y < ground_level && !is_cave;
+                        assert_eq!(chunk[x][y][z] == blocks.stone, is_stone, "Mismatch at {:?}", absolute);
+
+                        if is_cave {
+                            carved += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!(carved > 0, "No caves were generated");
+    }
+
+    #[test]
+    fn test_no_caves_above_ground() {
+        let biomes = BiomeRegistry::new(&GenerationBlocks::new(test_block_states()));
+        let cave_config = CaveConfig::default();
+        let carver = CaveCarver::new(0, &cave_config, &biomes);
+
+        for x in 0..64 {
+            for z in 0..64 {
+                assert!(!carver.is_carved(Vector3::new(x, 10, z), 10, 1.0));
+                assert!(!carver.is_carved(Vector3::new(x, 20, z), 10, 1.0));
+            }
+        }
+    }
+}
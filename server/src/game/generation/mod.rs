@@ -1,26 +1,116 @@
+mod biome;
+mod blocks;
+mod caves;
 mod noise;
+mod ores;
 mod phase1;
 mod phase2;
 mod phase3;
 mod phase4;
-mod structures;
+pub mod structures;
 
+use std::fs;
 use std::time::Instant;
 
+use crate::config::ServerConfig;
+use crate::error::ServerError;
 use crate::game::chunk::ChunkData;
+use crate::game::generation::biome::{BiomeRegistry, BiomeSelector};
+use crate::game::generation::blocks::GenerationBlocks;
+use crate::game::generation::caves::{CaveCarver, CaveConfig};
+use crate::game::generation::ores::{add_ores, OreTable};
 use crate::game::generation::phase1::{EnvironmentMapConfig, generate_environment_map};
-use crate::game::generation::phase2::{generate_greybox_chunk, GreyboxMapConfig};
+use crate::game::generation::phase2::{generate_greybox_chunk, generate_heightmap, GreyboxMapConfig};
 use crate::game::generation::phase3::decorate_chunk;
 use crate::game::generation::phase4::add_structures;
-use bevy::prelude::{Resource, trace};
-use nalgebra::Vector3;
+use crate::game::generation::structures::template::{load_templates, template_directories, TemplateStructure};
+use crate::game::world::level::{LevelData, LEGACY_SEED};
+use bevy::prelude::{error, info, Res, ResMut, Resource, trace};
+use nalgebra::{Vector2, Vector3};
+use rc_shared::block::{BlockId, BlockStates};
 use rc_shared::chunk::ChunkDataStorage;
+use rc_shared::chunk_summary::ChunkSummary;
 use rc_shared::CHUNK_SIZE;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource, Default)]
+/// The height and block at the top of each block column in a chunk column, indexed by x then z
+pub type ChunkSurface = [[(i32, BlockId); CHUNK_SIZE]; CHUNK_SIZE];
+
+/// Everything that affects how chunks are generated. Generating a chunk with the same config always gives the same chunk
+#[derive(Resource, Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct ChunkGenerationConfig {
+    /// Comes from the world's level file rather than presets, so is not serialized
+    #[serde(skip)]
+    pub seed: u32,
+    /// Looked up once block states are known
+    #[serde(skip)]
+    pub blocks: GenerationBlocks,
+    #[serde(skip)]
+    pub biomes: BiomeRegistry,
+    #[serde(skip)]
+    pub ores: OreTable,
+    /// Loaded from template files once block states are known
+    #[serde(skip)]
+    pub structures: Vec<TemplateStructure>,
     pub environment_map_config: EnvironmentMapConfig,
-    pub greybox_map_config: GreyboxMapConfig
+    pub greybox_map_config: GreyboxMapConfig,
+    pub cave_config: CaveConfig
+}
+
+impl ChunkGenerationConfig {
+    /// Reads generation settings from a preset file. Settings missing from the preset use their defaults
+    pub fn load_preset(path: &str) -> Result<ChunkGenerationConfig, ServerError> {
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Creates the generation config for the current world, from its level file and the preset chosen in `config`
+    pub fn load(config: &ServerConfig) -> ChunkGenerationConfig {
+        // The level file is left alone so it can be fixed by hand, rather than being replaced with a different seed
+        let level = LevelData::load_or_create(config).unwrap_or_else(|err| {
+            let seed = config.seed.unwrap_or(LEGACY_SEED);
+            error!("Error reading level data, generating with seed {}: {:?}", seed, err);
+            LevelData { seed }
+        });
+
+        let generation_config = match &config.generation_preset {
+            Some(path) => match Self::load_preset(path) {
+                Ok(preset) => {
+                    info!("Loaded generation preset {}", path);
+                    preset
+                }
+                Err(err) => {
+                    error!("Error reading generation preset {}, using defaults: {:?}", path, err);
+                    ChunkGenerationConfig::default()
+                }
+            },
+            None => ChunkGenerationConfig::default()
+        };
+
+        ChunkGenerationConfig {
+            seed: level.seed,
+            ..generation_config
+        }
+    }
+
+    /// Looks up the blocks generation places, which must happen before any chunks generate
+    pub fn resolve_blocks(&mut self, block_states: &BlockStates) {
+        self.blocks = GenerationBlocks::new(block_states);
+        self.biomes = BiomeRegistry::new(&self.blocks);
+        self.ores = OreTable::new(&self.blocks);
+    }
+}
+
+/// Looks up the blocks generation places, and loads structure templates bundled with the assets and made for
+/// this server. Block ids are needed for both, so this must run after block states are calculated and before any
+/// chunks generate.
+pub fn prepare_generation(
+    mut config: ResMut<ChunkGenerationConfig>,
+    block_states: Res<BlockStates>
+) {
+    config.resolve_blocks(&block_states);
+    config.structures = load_templates(&template_directories(), &block_states, &config.biomes);
 }
 
 impl ChunkData {
@@ -28,11 +118,12 @@ impl ChunkData {
     /// Phase 1: Biome Generation
     ///     This affects all the other phases so it is performed first
     /// Phase 2: Greyboxing
-    ///     Stone blocks are placed for the ground, holes are left for caves
+    ///     Stone blocks are placed for the ground, holes are left for caves and ravines. Ground height is blended between biomes
     /// Phase 3: Decoration
-    ///     More block types are added, such as grass, dirt, sand, water
+    ///     More block types are added, such as grass, dirt, sand, water, depending on each column's biome.
+    ///     Ore veins are scattered through stone
     /// Phase 4: Structures
-    ///     Structures are generated in this step such as trees
+    ///     Structures are generated in this step such as trees, from each biome's spawn table
     pub fn generate(
         position: Vector3<i32>,
         config: &ChunkGenerationConfig
@@ -40,18 +131,22 @@ impl ChunkData {
 
         let started = Instant::now();
 
-        let seed = 0;
+        let seed = config.seed;
         let environment_map = generate_environment_map(
             seed,
             position,
             &config.environment_map_config
         );
 
-        let (mut chunk_data, heightmap) = generate_greybox_chunk(seed, position, &environment_map, &config.greybox_map_config);
+        let (mut chunk_data, heightmap) = generate_greybox_chunk(seed, position, &environment_map, &config.greybox_map_config, &config.cave_config, &config.biomes, &config.blocks);
+
+        let carver = CaveCarver::new(seed, &config.cave_config, &config.biomes);
 
-        decorate_chunk(seed, position, &mut chunk_data, &heightmap, &environment_map);
+        decorate_chunk(seed, position, &mut chunk_data, &heightmap, &environment_map, &carver, &config.biomes, &config.blocks);
 
-        add_structures(seed, position, &mut chunk_data, &heightmap, &environment_map);
+        add_ores(seed, position, &mut chunk_data, &config.ores);
+
+        add_structures(seed, position, &mut chunk_data, &heightmap, &environment_map, &carver, &config.biomes, &config.structures, &config.blocks);
         
         trace!("Took {}ms to build chunk", started.elapsed().as_millis());
 
@@ -67,6 +162,57 @@ impl ChunkData {
         data
     }
 
+    /// The height and block of the top of each block column in a chunk column as it is first generated, without
+    /// generating any of its chunks. Structures such as trees are left out, as are any changes made to the world since.
+    pub fn generate_surface(position: Vector2<i32>, config: &ChunkGenerationConfig) -> ChunkSurface {
+        let seed = config.seed;
+        let position = Vector3::new(position.x, 0, position.y);
+
+        let environment_map = generate_environment_map(seed, position, &config.environment_map_config);
+        let heightmap = generate_heightmap(seed, position, &environment_map, &config.greybox_map_config, &config.biomes);
+
+        let carver = CaveCarver::new(seed, &config.cave_config, &config.biomes);
+        let biome_selector = BiomeSelector::new(seed, &config.biomes);
+
+        let mut surface = [[(0, 0); CHUNK_SIZE]; CHUNK_SIZE];
+
+        for (x, row) in surface.iter_mut().enumerate() {
+            for (z, top) in row.iter_mut().enumerate() {
+                let absolute_x = position.x * CHUNK_SIZE as i32 + x as i32;
+                let absolute_z = position.z * CHUNK_SIZE as i32 + z as i32;
+
+                let ground_level = *heightmap.get([absolute_x, absolute_z]).unwrap();
+                let environment_entry = environment_map.get([absolute_x, absolute_z]).unwrap();
+                let biome = biome_selector.biome(absolute_x, absolute_z, environment_entry);
+
+                // Cave mouths have no surface, so show what is under it
+                *top = if carver.is_cave_mouth(absolute_x, absolute_z, ground_level, environment_entry) {
+                    (ground_level - 1, biome.subsurface_block)
+                } else {
+                    (ground_level, biome.surface_block)
+                };
+            }
+        }
+
+        surface
+    }
+
+    /// Summarises the surface of a chunk column as it is first generated
+    pub fn generate_summary(
+        position: Vector2<i32>,
+        scale: u8,
+        config: &ChunkGenerationConfig
+    ) -> ChunkSummary {
+        let surface = ChunkData::generate_surface(position, config);
+
+        ChunkSummary::from_surface(scale, |x, z| surface[x][z])
+    }
+
+    /// The surface of a chunk column of the canvas world
+    pub fn generate_canvas_surface() -> ChunkSurface {
+        [[(0, 1); CHUNK_SIZE]; CHUNK_SIZE]
+    }
+
     pub fn generate_canvas(position: Vector3<i32>) -> ChunkData {
 
         let y_plane = 0;
@@ -86,13 +232,92 @@ impl ChunkData {
             ChunkDataStorage::Empty
         };
 
-        let data = ChunkData::new(
+        let mut data = ChunkData::new(
             position,
             chunk_data,
             Default::default(),
             Default::default()
         );
 
+        data.optimise_data();
+
         data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::generation::ChunkGenerationConfig;
+    use rc_shared::block::test_block_states;
+    use nalgebra::{Vector2, Vector3};
+    use rc_shared::CHUNK_SIZE;
+
+    fn generation_config(seed: u32) -> ChunkGenerationConfig {
+        let mut config = ChunkGenerationConfig {
+            seed,
+            ..Default::default()
+        };
+        config.resolve_blocks(test_block_states());
+        config
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let config = generation_config(1234);
+
+        for pos in [Vector3::new(0, 0, 0), Vector3::new(3, 1, -2), Vector3::new(-7, 0, 5)] {
+            assert_eq!(ChunkData::generate(pos, &config), ChunkData::generate(pos, &config));
+        }
+
+        let other_seed = generation_config(4321);
+
+        assert_ne!(
+            ChunkData::generate(Vector3::new(0, 0, 0), &config),
+            ChunkData::generate(Vector3::new(0, 0, 0), &other_seed)
+        );
+    }
+
+    #[test]
+    fn test_summary_matches_generated_surface() {
+        let config = generation_config(1234);
+
+        for column in [Vector2::new(0, 0), Vector2::new(-5, 3)] {
+            let summary = ChunkData::generate_summary(column, 1, &config);
+            let mut matching = 0;
+
+            for x in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    let (height, block) = summary.get(x, z);
+                    let chunk_y = height.div_euclid(CHUNK_SIZE as i32);
+                    let chunk = ChunkData::generate(Vector3::new(column.x, chunk_y, column.y), &config);
+
+                    let y = height.rem_euclid(CHUNK_SIZE as i32) as usize;
+                    if chunk.world.get(Vector3::new(x, y, z)) == block {
+                        matching += 1;
+                    }
+                }
+            }
+
+            // Only the block columns where trees are planted or caves open up differ
+            assert!(matching > CHUNK_SIZE * CHUNK_SIZE * 9 / 10, "{} of the surface matched", matching);
+        }
+
+        // Coarser summaries keep the highest ground
+        let fine = ChunkData::generate_summary(Vector2::new(0, 0), 1, &config);
+        let coarse = ChunkData::generate_summary(Vector2::new(0, 0), 4, &config);
+        assert_eq!(coarse.cells(), 4);
+        assert_eq!(coarse.height_range().1, fine.height_range().1);
+    }
+
+    #[test]
+    fn test_preset_defaults() {
+        let preset: ChunkGenerationConfig = serde_json::from_str(
+            r#"{ "greybox_map_config": { "terrain_scaler": 20.0 } }"#
+        ).unwrap();
+
+        assert_eq!(preset.greybox_map_config.terrain_scaler, 20.0);
+        assert_eq!(preset.greybox_map_config.hilly_pow, ChunkGenerationConfig::default().greybox_map_config.hilly_pow);
+        assert_eq!(preset.environment_map_config, ChunkGenerationConfig::default().environment_map_config);
+    }
+}
@@ -1,4 +1,5 @@
 
+use nalgebra::Vector3;
 use noise::{NoiseFn, OpenSimplex};
 
 
@@ -34,3 +35,17 @@ impl SimplexNoise {
             + 0.5
     }
 }
+
+/// A seed for random decisions made about a position, such as a chunk, that is the same no matter which chunk
+/// is being generated. `salt` separates the decisions of different features at the same position.
+pub fn position_seed(seed: u32, salt: u64, pos: Vector3<i32>) -> u64 {
+    (seed as u64)
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(salt)
+        .wrapping_mul(1442695040888963407)
+        .wrapping_add(pos.x as u64)
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(pos.y as u64)
+        .wrapping_mul(1442695040888963407)
+        .wrapping_add(pos.z as u64)
+}
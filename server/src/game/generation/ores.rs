@@ -0,0 +1,152 @@
+use crate::game::generation::blocks::GenerationBlocks;
+use crate::game::generation::noise::position_seed;
+use nalgebra::Vector3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rc_shared::block::BlockId;
+use rc_shared::chunk::RawChunkData;
+use rc_shared::helpers::global_to_local_position;
+use rc_shared::CHUNK_SIZE;
+
+/// How an ore is spread through the world
+#[derive(Debug, Clone, PartialEq)]
+pub struct OreDistribution {
+    pub block: BlockId,
+    /// The only block the ore replaces, so veins don't float in caves or show through the surface
+    pub host_block: BlockId,
+    /// How many veins start in each chunk, when the chunk is within the height range
+    pub veins_per_chunk: u32,
+    /// Veins only start between these heights
+    pub min_height: i32,
+    pub max_height: i32,
+    /// The most blocks in a single vein
+    pub vein_size: u32,
+}
+
+/// Every ore that generates in the world
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OreTable {
+    pub ores: Vec<OreDistribution>,
+}
+
+impl OreTable {
+    pub fn new(blocks: &GenerationBlocks) -> OreTable {
+        OreTable {
+            ores: vec![
+                OreDistribution {
+                    block: blocks.ruby_ore,
+                    host_block: blocks.stone,
+                    veins_per_chunk: 3,
+                    min_height: -64,
+                    max_height: 4,
+                    vein_size: 6,
+                },
+            ],
+        }
+    }
+}
+
+/// Scatters ore veins through the host blocks of a chunk. Veins started in neighbouring chunks are
+/// generated too, so veins crossing chunk borders are not cut off.
+pub fn add_ores(seed: u32, chunk_pos: Vector3<i32>, world: &mut RawChunkData, table: &OreTable) {
+    for x in -1..=1 {
+        for y in -1..=1 {
+            for z in -1..=1 {
+                let origin_chunk = chunk_pos + Vector3::new(x, y, z);
+
+                for (i, ore) in table.ores.iter().enumerate() {
+                    add_veins(seed, i, ore, origin_chunk, chunk_pos, world);
+                }
+            }
+        }
+    }
+}
+
+/// Places the veins of `ore` that start in `origin_chunk`, only changing blocks inside `chunk_pos`
+fn add_veins(
+    seed: u32,
+    ore_index: usize,
+    ore: &OreDistribution,
+    origin_chunk: Vector3<i32>,
+    chunk_pos: Vector3<i32>,
+    world: &mut RawChunkData,
+) {
+    let chunk_bottom = origin_chunk.y * CHUNK_SIZE as i32;
+    let chunk_top = chunk_bottom + CHUNK_SIZE as i32 - 1;
+
+    let min_height = ore.min_height.max(chunk_bottom);
+    let max_height = ore.max_height.min(chunk_top);
+
+    if min_height > max_height {
+        return;
+    }
+
+    let mut rng = StdRng::seed_from_u64(position_seed(seed, ore_index as u64, origin_chunk));
+
+    for _ in 0..ore.veins_per_chunk {
+        let mut pos = Vector3::new(
+            origin_chunk.x * CHUNK_SIZE as i32 + rng.gen_range(0..CHUNK_SIZE as i32),
+            rng.gen_range(min_height..=max_height),
+            origin_chunk.z * CHUNK_SIZE as i32 + rng.gen_range(0..CHUNK_SIZE as i32),
+        );
+
+        // Random walk, so veins are clumps rather than lines
+        for _ in 0..rng.gen_range(1..=ore.vein_size) {
+            let (block_chunk, local) = global_to_local_position(pos);
+
+            if block_chunk == chunk_pos && world[local.x][local.y][local.z] == ore.host_block {
+                world[local.x][local.y][local.z] = ore.block;
+            }
+
+            let axis = rng.gen_range(0..3);
+            pos[axis] += if rng.gen_bool(0.5) { 1 } else { -1 };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::generation::blocks::GenerationBlocks;
+    use crate::game::generation::ores::{add_ores, OreDistribution, OreTable};
+    use rc_shared::block::test_block_states;
+    use nalgebra::Vector3;
+    use rc_shared::CHUNK_SIZE;
+
+    #[test]
+    fn test_ore_block_ids() {
+        let block_states = test_block_states();
+        let table = OreTable::new(&GenerationBlocks::new(block_states));
+        let identifier = |id| block_states.get_block_from_id(id).get_identifier();
+
+        assert_eq!(identifier(table.ores[0].block), "mcv3::block::RubyOre");
+        assert_eq!(identifier(table.ores[0].host_block), "mcv3::block::Stone");
+    }
+
+    #[test]
+    fn test_add_ores() {
+        let table = OreTable {
+            ores: vec![OreDistribution {
+                block: 2,
+                host_block: 1,
+                veins_per_chunk: 4,
+                min_height: 0,
+                max_height: 15,
+                vein_size: 8,
+            }],
+        };
+
+        // Only replaces the host block
+        let mut chunk = [[[1; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
+        chunk[0] = [[3; CHUNK_SIZE]; CHUNK_SIZE];
+        add_ores(7, Vector3::new(0, 0, 0), &mut chunk, &table);
+
+        let ores = chunk.iter().flatten().flatten().filter(|block| **block == 2).count();
+        assert!(ores > 0);
+        assert!(chunk[0].iter().flatten().all(|block| *block == 3));
+
+        // Outside the height range
+        let mut chunk = [[[1; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
+        add_ores(7, Vector3::new(0, 4, 0), &mut chunk, &table);
+        assert!(chunk.iter().flatten().flatten().all(|block| *block == 1));
+    }
+}
@@ -2,7 +2,10 @@ use crate::game::generation::noise::SimplexNoise;
 use nalgebra::{Vector2, Vector3};
 use rc_shared::biome::{EnvironmentEntry, EnvironmentMap};
 use rc_shared::CHUNK_SIZE;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct EnvironmentMapConfig {
     pub terrain_scale: f32,
     pub vegetation_scale: f32,
@@ -42,11 +45,11 @@ pub fn generate_biome(seed: u32, pos: Vector3<i32>, config: &EnvironmentMapConfi
 
     let climate = climate_noise.sample_2d(pos.x, pos.z);
 
-    let terrain = SimplexNoise::new(seed + 1)
+    let terrain = SimplexNoise::new(seed.wrapping_add(1))
         .with_scale(config.terrain_scale)
         .sample_2d(pos.x, pos.z);
 
-    let vegetation = SimplexNoise::new(seed + 2)
+    let vegetation = SimplexNoise::new(seed.wrapping_add(2))
         .with_scale(config.vegetation_scale)
         .sample_2d(pos.x, pos.z);
 
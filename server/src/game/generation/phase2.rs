@@ -1,14 +1,21 @@
 use std::ops::Range;
 
+use crate::game::generation::biome::{BiomeBlend, BiomeRegistry};
+use crate::game::generation::blocks::GenerationBlocks;
+use crate::game::generation::caves::{CaveCarver, CaveConfig};
 use crate::game::generation::noise::SimplexNoise;
 use nalgebra::{Vector2, Vector3};
 use rc_shared::biome::{EnvironmentMap};
 use rc_shared::chunk::RawChunkData;
 use rc_shared::CHUNK_SIZE;
-use ::noise::{RidgedMulti, Perlin};
-use ::noise::{MultiFractal, NoiseFn, Seedable, Turbulence};
 use rc_shared::relative_chunk_flat_map::RelativeChunkFlatMap;
+use serde::{Deserialize, Serialize};
 
+/// The height biomes scale the ground towards or away from
+const BASE_HEIGHT: f64 = 10.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct GreyboxMapConfig {
     pub ground_scale_1: f32,
     pub ground_scale_2: f32,
@@ -22,9 +29,7 @@ pub struct GreyboxMapConfig {
     pub terrain_scaler: f64,
 
     pub ground_scaler_1: f64,
-    pub ground_scaler_2: f64,
-
-    pub cave_scale: f64
+    pub ground_scaler_2: f64
 }
 
 impl Default for GreyboxMapConfig {
@@ -39,26 +44,28 @@ impl Default for GreyboxMapConfig {
             hilly_scaler_2: 1.2,
             terrain_scaler: 10.0,
             ground_scaler_1: 5.0,
-            ground_scaler_2: 2.0,
-            cave_scale: 0.03
+            ground_scaler_2: 2.0
         }
     }
 }
 
-pub fn generate_greybox_chunk(
+/// The ground level of every block column in and around the chunk column at `pos`, before any caves are carved
+pub fn generate_heightmap(
     seed: u32,
     pos: Vector3<i32>,
     environment: &EnvironmentMap,
-    config: &GreyboxMapConfig
-) -> (RawChunkData, RelativeChunkFlatMap<i32>) {
-    let ground_noise = SimplexNoise::new(0).with_scale(config.ground_scale_1);
-    let ground_noise_2 = SimplexNoise::new(100).with_scale(config.ground_scale_2);
-    let ground_noise_3 = SimplexNoise::new(200).with_scale(config.ground_scale_3);
-    let ground_noise_4 = SimplexNoise::new(150).with_scale(config.ground_scale_4);
+    config: &GreyboxMapConfig,
+    biomes: &BiomeRegistry
+) -> RelativeChunkFlatMap<i32> {
+    let ground_noise = SimplexNoise::new(seed).with_scale(config.ground_scale_1);
+    let ground_noise_2 = SimplexNoise::new(seed.wrapping_add(100)).with_scale(config.ground_scale_2);
+    let ground_noise_3 = SimplexNoise::new(seed.wrapping_add(200)).with_scale(config.ground_scale_3);
+    let ground_noise_4 = SimplexNoise::new(seed.wrapping_add(150)).with_scale(config.ground_scale_4);
 
     let world_pos = pos * CHUNK_SIZE as i32;
 
     let mut heightmap = RelativeChunkFlatMap::new_empty(Vector2::new(world_pos.x, world_pos.z), CHUNK_SIZE);
+    let mut blend = BiomeBlend::default();
 
     for x in (world_pos.x - CHUNK_SIZE as i32)..(world_pos.x + (CHUNK_SIZE as i32 * 2)) {
         for z in (world_pos.z - CHUNK_SIZE as i32)..(world_pos.z + (CHUNK_SIZE as i32 * 2)) {
@@ -71,53 +78,56 @@ pub fn generate_greybox_chunk(
                     * clamp_map(0.3..0.7, 0.8..config.hilly_scaler_2, ground_noise_4.sample_2d(x, z))
                 );
 
-            let ground_level = (ground_noise_3.sample_2d(x, z)
+            let height = ground_noise_3.sample_2d(x, z)
                 * height_multiplier
                 + environment_entry.terrain * config.terrain_scaler
                 + ground_noise_2.sample_2d(x, z) * config.ground_scaler_1
-                + ground_noise.sample_2d(x, z) * config.ground_scaler_2)
+                + ground_noise.sample_2d(x, z) * config.ground_scaler_2;
+
+            // Blend biome height modifiers so there are no cliffs at biome borders
+            biomes.blend_into(environment_entry, &mut blend);
+            let height_scale = blend.blend_value(biomes, |biome| biome.height_scale);
+            let height_offset = blend.blend_value(biomes, |biome| biome.height_offset);
+
+            let ground_level = (BASE_HEIGHT + (height - BASE_HEIGHT) * height_scale + height_offset)
                 .floor() as i32;
 
             heightmap.set([x, z], ground_level);
         }
     }
 
-    let primary_jade = RidgedMulti::<Perlin>::new(seed)
-        .set_lacunarity(2.20703125)
-        .set_octaves(1);
+    heightmap
+}
 
-    let caves = Turbulence::<_, Perlin>::new(primary_jade)
-        .set_seed(seed)
-        .set_frequency(2.0)
-        .set_power(1.0 / 16.0)
-        .set_roughness(4);
+pub fn generate_greybox_chunk(
+    seed: u32,
+    pos: Vector3<i32>,
+    environment: &EnvironmentMap,
+    config: &GreyboxMapConfig,
+    cave_config: &CaveConfig,
+    biomes: &BiomeRegistry,
+    blocks: &GenerationBlocks
+) -> (RawChunkData, RelativeChunkFlatMap<i32>) {
+    let heightmap = generate_heightmap(seed, pos, environment, config, biomes);
+
+    let carver = CaveCarver::new(seed, cave_config, biomes);
 
     let mut world = [[[0; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
 
     for x in 0..CHUNK_SIZE {
-        for y in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                let absolute = Vector3::new(
-                    (pos.x * 16) + x as i32,
-                    (pos.y * 16) + y as i32,
-                    (pos.z * 16) + z as i32,
-                );
+        for z in 0..CHUNK_SIZE {
+            let absolute_x = (pos.x * 16) + x as i32;
+            let absolute_z = (pos.z * 16) + z as i32;
 
-                let v = caves.get([
-                    absolute.x as f64 * config.cave_scale,
-                    absolute.z as f64 * config.cave_scale,
-                    absolute.y as f64 * config.cave_scale * 2.0
-                ]);
+            let ground_level = *heightmap.get([absolute_x, absolute_z]).unwrap();
+            let cave_density = carver.density(environment.get([absolute_x, absolute_z]).unwrap());
 
-                let threshold = clamp_map(
-                    10.0..25.0,
-                    0.7..1.0,
-                    absolute.y as f32
-                );
+            for y in 0..CHUNK_SIZE {
+                let absolute = Vector3::new(absolute_x, (pos.y * 16) + y as i32, absolute_z);
 
-                // Set stone of world
-                if absolute.y < *heightmap.get([absolute.x, absolute.z]).unwrap() && v < threshold {
-                    world[x][y][z] = 6;
+                // Set stone of world, leaving caves hollow
+                if absolute.y < ground_level && !carver.is_carved(absolute, ground_level, cave_density) {
+                    world[x][y][z] = blocks.stone;
                 }
             }
         }
@@ -1,3 +1,6 @@
+use crate::game::generation::biome::{BiomeRegistry, BiomeSelector};
+use crate::game::generation::blocks::GenerationBlocks;
+use crate::game::generation::caves::CaveCarver;
 use crate::game::generation::noise::SimplexNoise;
 use nalgebra::Vector3;
 use rc_shared::biome::{EnvironmentMap};
@@ -5,58 +8,57 @@ use rc_shared::chunk::RawChunkData;
 use rc_shared::CHUNK_SIZE;
 use rc_shared::relative_chunk_flat_map::RelativeChunkFlatMap;
 
+#[allow(clippy::too_many_arguments)]
 pub fn decorate_chunk(
     seed: u32,
     pos: Vector3<i32>,
     world: &mut RawChunkData,
     heightmap: &RelativeChunkFlatMap<i32>,
     environment: &EnvironmentMap,
+    carver: &CaveCarver,
+    biomes: &BiomeRegistry,
+    blocks: &GenerationBlocks,
 ) {
-    let ground_noise = SimplexNoise::new(seed);
-    let tropic_noise = SimplexNoise::new(seed + 1).with_scale(24.0);
-    let grass_noise = SimplexNoise::new(seed + 2).with_scale(1.0);
-    let ruby_noise = SimplexNoise::new(seed + 3).with_scale(5.0);
+    let biome_selector = BiomeSelector::new(seed, biomes);
+    let cover_noise = SimplexNoise::new(seed.wrapping_add(2)).with_scale(1.0);
 
     for x in 0..CHUNK_SIZE {
-        for y in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                let absolute = Vector3::new(
-                    (pos.x * 16) + x as i32,
-                    (pos.y * 16) + y as i32,
-                    (pos.z * 16) + z as i32,
-                );
-
-                let ground_level = *heightmap.get([absolute.x, absolute.z]).unwrap();
-
-                // Mountains without grass or dirt
-                if ground_level < 18 {
-                    // Dirt
-                    if absolute.y < ground_level && absolute.y > ground_level - 4 {
-                        world[x][y][z] = 1;
-                    }
-                    // Grass
-                    if absolute.y == ground_level {
-                        world[x][y][z] = 2;
-                    }
+        for z in 0..CHUNK_SIZE {
+            let absolute_x = (pos.x * 16) + x as i32;
+            let absolute_z = (pos.z * 16) + z as i32;
 
-                    let tropic_sand = tropic_noise.sample_2d(absolute.x, absolute.z);
-                    if absolute.y == ground_level && tropic_sand > 0.8 {
-                        world[x][y][z] = 8;
-                    }
+            let ground_level = *heightmap.get([absolute_x, absolute_z]).unwrap();
+            let environment_entry = environment.get([absolute_x, absolute_z]).unwrap();
+            let biome = biome_selector.biome(absolute_x, absolute_z, environment_entry);
 
-                    // Long grass
+            // Nothing to put the surface on where a cave opens up
+            let cave_mouth = carver.is_cave_mouth(absolute_x, absolute_z, ground_level, environment_entry);
+
+            for y in 0..CHUNK_SIZE {
+                let absolute = Vector3::new(absolute_x, (pos.y * 16) + y as i32, absolute_z);
+
+                // Surface
+                if absolute.y == ground_level && !cave_mouth {
+                    world[x][y][z] = biome.surface_block;
+                }
+
+                // Subsurface, only replacing stone so caves near the surface stay open
+                if world[x][y][z] == blocks.stone
+                    && absolute.y < ground_level
+                    && absolute.y >= ground_level - biome.subsurface_depth
+                {
+                    world[x][y][z] = biome.subsurface_block;
+                }
+
+                // Ground cover, such as long grass
+                if let Some(cover) = &biome.ground_cover {
                     if absolute.y == ground_level + 1
-                        && grass_noise.sample_2d(absolute.x, absolute.z) > 0.7
-                        && !(absolute.y - 1 == ground_level && tropic_sand > 0.8) // No sand beneath
+                        && !cave_mouth
+                        && cover_noise.sample_2d(absolute.x, absolute.z) > cover.threshold
                     {
-                        world[x][y][z] = 3;
+                        world[x][y][z] = cover.block;
                     }
                 }
-
-                if world[x][y][z] == 6 &&
-                    ruby_noise.sample_3d(absolute.x, absolute.y, absolute.z) > 0.82 {
-                    // world[x][y][z] = 76;
-                }
             }
         }
     }
@@ -1,6 +1,13 @@
-use crate::game::generation::noise::SimplexNoise;
+use crate::game::generation::biome::{BiomeRegistry, BiomeSelector, StructureType};
+use crate::game::generation::blocks::GenerationBlocks;
+use crate::game::generation::caves::CaveCarver;
+use crate::game::generation::noise::{position_seed, SimplexNoise};
+use crate::game::generation::structures::template::{PlacementLocation, TemplateStructure};
+use crate::game::generation::structures::try_place_block;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use nalgebra::Vector3;
-use rc_shared::biome::EnvironmentMap;
+use rc_shared::biome::{EnvironmentEntry, EnvironmentMap};
 use rc_shared::chunk::RawChunkData;
 use rc_shared::CHUNK_SIZE;
 use rc_shared::relative_chunk_flat_map::RelativeChunkFlatMap;
@@ -10,13 +17,19 @@ use crate::game::generation::structures::tree::TreeStructureGenerator;
 /// Adds structures to chunk
 /// `world_pos` is the position of `world`, while `generation_pos` is a surrounding chunk that is having its structures generated also to generate overlap
 /// in the chunks structures.
+#[allow(clippy::too_many_arguments)]
 pub fn add_structures(
     seed: u32,
     chunk_pos: Vector3<i32>,
     world: &mut RawChunkData,
     heightmap: &RelativeChunkFlatMap<i32>,
-    environment: &EnvironmentMap
+    environment: &EnvironmentMap,
+    carver: &CaveCarver,
+    biomes: &BiomeRegistry,
+    templates: &[TemplateStructure],
+    blocks: &GenerationBlocks
 ) {
+    let biome_selector = BiomeSelector::new(seed, biomes);
 
     let mut trees = vec![];
 
@@ -25,7 +38,7 @@ pub fn add_structures(
         Vector3::new(CHUNK_SIZE as i32, CHUNK_SIZE as i32, CHUNK_SIZE as i32)
     );
 
-    let tree_noise = SimplexNoise::new(seed + 10).with_scale(1.0);
+    let tree_noise = SimplexNoise::new(seed.wrapping_add(10)).with_scale(1.0);
 
     let world_pos = chunk_pos * CHUNK_SIZE as i32;
 
@@ -33,28 +46,122 @@ pub fn add_structures(
         for z in (world_pos.z - CHUNK_SIZE as i32)..(world_pos.z + (2*CHUNK_SIZE as i32)) {
             let ground_level = *heightmap.get([x, z]).unwrap();
 
-            if ground_level > 17 {
-                continue;
-            }
-
             let environment = *environment.get([x, z]).unwrap();
+            let biome = biome_selector.biome(x, z, &environment);
+
+            for spawn in &biome.structures {
+                if environment.vegetation < spawn.min_vegetation
+                    || tree_noise.sample_2d(x as f64, z as f64) <= spawn.threshold
+                {
+                    continue;
+                }
+
+                let Some(y) = surface_height(x, z, ground_level, &environment, carver) else {
+                    continue;
+                };
 
-            let affects_chunk =
-                chunk_bounding_box.collides(&TreeStructureGenerator::bounding_box().shifted(Vector3::new(x, ground_level + 1, z)));
+                match spawn.structure {
+                    StructureType::Tree => {
+                        let affects_chunk =
+                            chunk_bounding_box.collides(&TreeStructureGenerator::bounding_box().shifted(Vector3::new(x, y, z)));
 
-            let intersects_tree = does_tree_intersect(&trees, Vector3::new(x, ground_level + 1, z));
+                        let intersects_tree = does_tree_intersect(&trees, Vector3::new(x, y, z));
+
+                        if !intersects_tree && affects_chunk {
+                            trees.push(Vector3::new(x, y, z));
+                            TreeStructureGenerator::spawn(seed, chunk_pos, Vector3::new(x, y, z), world, blocks);
+                        }
+                    }
+                }
+            }
+        }
+    }
 
-            if environment.vegetation > 0.3
-                && !intersects_tree && affects_chunk
-                && tree_noise.sample_2d(x as f64, z as f64) > 0.9
-            {
-                trees.push(Vector3::new(x, ground_level + 1, z));
-                TreeStructureGenerator::spawn(seed, chunk_pos, Vector3::new(x, ground_level + 1, z), world);
+    // Templates fit within a chunk, so only ones starting in neighbouring chunk columns can reach this chunk
+    for chunk_x in (chunk_pos.x - 1)..=(chunk_pos.x + 1) {
+        for chunk_z in (chunk_pos.z - 1)..=(chunk_pos.z + 1) {
+            for template in templates {
+                add_template(
+                    seed,
+                    Vector3::new(chunk_x, 0, chunk_z),
+                    template,
+                    chunk_pos,
+                    &chunk_bounding_box,
+                    world,
+                    heightmap,
+                    environment,
+                    carver,
+                    &biome_selector
+                );
             }
         }
     }
 }
 
+/// Places `template` if it spawns in the chunk column `origin_chunk`, only changing blocks inside `chunk_pos`
+#[allow(clippy::too_many_arguments)]
+fn add_template(
+    seed: u32,
+    origin_chunk: Vector3<i32>,
+    template: &TemplateStructure,
+    chunk_pos: Vector3<i32>,
+    chunk_bounding_box: &StructureBoundingBox,
+    world: &mut RawChunkData,
+    heightmap: &RelativeChunkFlatMap<i32>,
+    environment: &EnvironmentMap,
+    carver: &CaveCarver,
+    biome_selector: &BiomeSelector,
+) {
+    let placement = &template.placement;
+    let mut rng = StdRng::seed_from_u64(position_seed(seed, template.salt, origin_chunk));
+
+    if !rng.gen_bool(placement.rarity.clamp(0.0, 1.0)) {
+        return;
+    }
+
+    let x = origin_chunk.x * CHUNK_SIZE as i32 + rng.gen_range(0..CHUNK_SIZE as i32);
+    let z = origin_chunk.z * CHUNK_SIZE as i32 + rng.gen_range(0..CHUNK_SIZE as i32);
+    let rotation = if placement.rotate { rng.gen_range(0..4) } else { 0 };
+    let depth = rng.gen_range(placement.min_depth..=placement.max_depth.max(placement.min_depth));
+
+    let ground_level = *heightmap.get([x, z]).unwrap();
+    let environment = environment.get([x, z]).unwrap();
+
+    if !template.biomes.is_empty() && !template.biomes.contains(&biome_selector.biome_index(x, z, environment)) {
+        return;
+    }
+
+    let y = match placement.location {
+        PlacementLocation::Surface => {
+            let Some(y) = surface_height(x, z, ground_level, environment, carver) else {
+                return;
+            };
+            y
+        }
+        PlacementLocation::Underground => ground_level - depth,
+    };
+
+    let origin = Vector3::new(x, y, z);
+
+    if !chunk_bounding_box.collides(&template.rotated_bounding_box(rotation).shifted(origin)) {
+        return;
+    }
+
+    for (pos, block) in template.rotated_blocks(rotation) {
+        try_place_block(chunk_pos, world, origin + pos, block);
+    }
+}
+
+/// The height structures standing on the ground at `x`, `z` start at, if anything can stand there
+fn surface_height(x: i32, z: i32, ground_level: i32, environment: &EnvironmentEntry, carver: &CaveCarver) -> Option<i32> {
+    // Structures must be rooted on the ground rather than over a cave
+    if carver.is_cave_mouth(x, z, ground_level, environment) {
+        return None;
+    }
+
+    Some(ground_level + 1)
+}
+
 fn does_tree_intersect(trees: &Vec<Vector3<i32>>, pos: Vector3<i32>) -> bool {
     for tree in trees {
         if TreeStructureGenerator::bounding_box().shifted(pos).collides(
@@ -1,10 +1,12 @@
+use crate::game::generation::blocks::GenerationBlocks;
 use nalgebra::Vector3;
 use rc_shared::chunk::{ChunkPosition, RawChunkData};
 use rc_shared::helpers::global_to_local_position;
 
+pub mod template;
 pub mod tree;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct StructureBoundingBox {
     pub bottom_left: Vector3<i32>,
     pub size: Vector3<i32>
@@ -42,10 +44,10 @@ impl StructureBoundingBox {
 
 pub trait StructureGenerator {
     fn bounding_box() -> StructureBoundingBox;
-    fn spawn(seed: u32, chunk_pos: Vector3<i32>, pos: Vector3<i32>, world: &mut RawChunkData);
+    fn spawn(seed: u32, chunk_pos: Vector3<i32>, pos: Vector3<i32>, world: &mut RawChunkData, blocks: &GenerationBlocks);
 }
 
-fn try_place_block(
+pub(crate) fn try_place_block(
     affected_chunk_pos: ChunkPosition,
     world: &mut RawChunkData,
     pos: Vector3<i32>,
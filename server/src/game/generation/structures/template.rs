@@ -0,0 +1,644 @@
+use crate::error::ServerError;
+use crate::game::generation::biome::BiomeRegistry;
+use crate::game::generation::structures::StructureBoundingBox;
+use crate::helpers::write_atomic;
+use bevy::log::{error, info};
+use fnv::FnvHasher;
+use nalgebra::Vector3;
+use rc_shared::block::entity::BlockEntityData;
+use rc_shared::block::properties::{BlockProperty, PropertyValue, DIRECTIONS, SIDE_PROPERTIES};
+use rc_shared::block::{BlockId, BlockStates, WorldBlock};
+use rc_shared::viewable_direction::{AxisAlignedDirection, BLOCK_SIDES};
+use rc_shared::CHUNK_SIZE;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+/// Templates made by players for this server, alongside the ones bundled in the assets
+pub const STRUCTURE_DIRECTORY: &str = "./structures";
+
+/// A structure loaded from a template file, with its blocks listed relative to its anchor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StructureTemplate {
+    pub identifier: String,
+    /// Blocks referred to by index from `blocks`
+    pub palette: Vec<PaletteBlock>,
+    /// Blocks not listed are left as they were in the world. List air to clear space, such as inside a house.
+    pub blocks: Vec<TemplateBlock>,
+    /// The block of the template placed at the spawn position, usually the bottom centre
+    pub anchor: Vector3<i32>,
+    #[serde(default)]
+    pub placement: PlacementRules,
+    /// Block entities kept with their blocks, such as a chest's contents. Blocks with entities that
+    /// aren't listed are given a new one when pasted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<TemplateBlockEntity>,
+}
+
+/// A block in a template's palette. Blocks with properties list the value of each, any left out
+/// keep their default value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum PaletteBlock {
+    Identifier(String),
+    State {
+        block: String,
+        properties: BTreeMap<String, Value>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct TemplateBlock {
+    pub pos: Vector3<i32>,
+    pub block: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TemplateBlockEntity {
+    pub pos: Vector3<i32>,
+    pub entity: BlockEntityData,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PlacementLocation {
+    /// Anchored one block above the ground
+    #[default]
+    Surface,
+    /// Anchored between `min_depth` and `max_depth` blocks beneath the ground
+    Underground,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PlacementRules {
+    pub location: PlacementLocation,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    /// Biome identifiers the structure can spawn in, or any biome when empty
+    pub biomes: Vec<String>,
+    /// Chance of the structure spawning in each chunk column
+    pub rarity: f64,
+    /// Whether the structure is turned randomly by quarter turns
+    pub rotate: bool,
+}
+
+impl Default for PlacementRules {
+    fn default() -> Self {
+        PlacementRules {
+            location: PlacementLocation::Surface,
+            min_depth: 8,
+            max_depth: 32,
+            biomes: vec![],
+            rarity: 0.01,
+            rotate: true,
+        }
+    }
+}
+
+/// A template with its palette looked up, ready for world generation
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateStructure {
+    pub identifier: String,
+    /// Separates the random choices of this structure from others, without depending on load order
+    pub salt: u64,
+    /// Blocks relative to the anchor
+    pub blocks: Vec<(Vector3<i32>, BlockId)>,
+    pub bounding_box: StructureBoundingBox,
+    pub placement: PlacementRules,
+    /// Indexes into the biome registry, or any biome when empty
+    pub biomes: Vec<usize>,
+}
+
+impl StructureTemplate {
+    pub fn load(path: impl AsRef<Path>) -> Result<StructureTemplate, ServerError> {
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Builds a template from `blocks` and their `entities` given by their position, such as a build
+    /// selected in the world. The palette is in the order blocks are first seen.
+    pub fn capture(
+        identifier: String,
+        blocks: impl IntoIterator<Item = (Vector3<i32>, PaletteBlock)>,
+        entities: impl IntoIterator<Item = (Vector3<i32>, BlockEntityData)>,
+        anchor: Vector3<i32>,
+    ) -> StructureTemplate {
+        let mut palette: Vec<PaletteBlock> = vec![];
+
+        let blocks = blocks
+            .into_iter()
+            .map(|(pos, palette_block)| {
+                let block = match palette.iter().position(|entry| *entry == palette_block) {
+                    Some(index) => index,
+                    None => {
+                        palette.push(palette_block);
+                        palette.len() - 1
+                    }
+                };
+
+                TemplateBlock { pos, block }
+            })
+            .collect();
+
+        StructureTemplate {
+            identifier,
+            palette,
+            blocks,
+            anchor,
+            placement: PlacementRules::default(),
+            entities: entities
+                .into_iter()
+                .map(|(pos, entity)| TemplateBlockEntity { pos, entity })
+                .collect(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ServerError> {
+        let data = serde_json::to_string_pretty(self)?;
+        write_atomic(path, data)?;
+        Ok(())
+    }
+
+    /// The blocks of the template relative to its anchor, with block ids looked up
+    pub fn resolve_blocks(&self, block_states: &BlockStates) -> Result<Vec<(Vector3<i32>, BlockId)>, String> {
+        let palette = self
+            .palette
+            .iter()
+            .map(|block| block.resolve(block_states))
+            .collect::<Result<Vec<BlockId>, String>>()?;
+
+        self.blocks
+            .iter()
+            .map(|block| {
+                palette
+                    .get(block.block)
+                    .map(|id| (block.pos - self.anchor, *id))
+                    .ok_or_else(|| format!("palette index {} out of range", block.block))
+            })
+            .collect()
+    }
+
+    /// The block entities of the template relative to its anchor
+    pub fn block_entities(&self) -> impl Iterator<Item = (Vector3<i32>, &BlockEntityData)> {
+        self.entities.iter().map(|entity| (entity.pos - self.anchor, &entity.entity))
+    }
+
+    /// Looks up the template's blocks and biomes, checking the template can be generated
+    pub fn resolve(&self, block_states: &BlockStates, biomes: &BiomeRegistry) -> Result<TemplateStructure, String> {
+        let blocks = self.resolve_blocks(block_states)?;
+
+        let Some(bounding_box) = bounding_box(blocks.iter().map(|(pos, _)| *pos)) else {
+            return Err(String::from("template has no blocks"));
+        };
+
+        // Structures are only generated from neighbouring chunks, so they must fit in one whichever way they are turned
+        if bounding_box.size.x > CHUNK_SIZE as i32 || bounding_box.size.z > CHUNK_SIZE as i32 {
+            return Err(format!("template is wider than {} blocks", CHUNK_SIZE));
+        }
+
+        let biome_indexes = self
+            .placement
+            .biomes
+            .iter()
+            .map(|identifier| {
+                biomes
+                    .biomes
+                    .iter()
+                    .position(|biome| biome.identifier == identifier)
+                    .ok_or_else(|| format!("unknown biome {}", identifier))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+
+        let mut hasher = FnvHasher::default();
+        hasher.write(self.identifier.as_bytes());
+
+        Ok(TemplateStructure {
+            identifier: self.identifier.clone(),
+            salt: hasher.finish(),
+            blocks,
+            bounding_box,
+            placement: self.placement.clone(),
+            biomes: biome_indexes,
+        })
+    }
+}
+
+impl PaletteBlock {
+    /// The palette entry for a block in the world, keeping its state
+    pub fn new(block: &WorldBlock) -> PaletteBlock {
+        let identifier = block.get_identifier().to_string();
+        let properties = block.get_state().values();
+
+        if properties.is_empty() {
+            return PaletteBlock::Identifier(identifier);
+        }
+
+        PaletteBlock::State {
+            block: identifier,
+            properties: properties
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), property_to_json(value)))
+                .collect(),
+        }
+    }
+
+    /// Looks up the id of the block variant with the listed properties
+    pub fn resolve(&self, block_states: &BlockStates) -> Result<BlockId, String> {
+        let (identifier, properties) = match self {
+            PaletteBlock::Identifier(identifier) => (identifier, None),
+            PaletteBlock::State { block, properties } => (block, Some(properties)),
+        };
+
+        let Some((_, mut block)) = block_states.get_by_identifier(identifier) else {
+            return Err(format!("unknown block {}", identifier));
+        };
+
+        for (name, value) in properties.into_iter().flatten() {
+            let Some(definition) = BlockStates::get_definition_index_by_identifier(identifier)
+                .and_then(BlockStates::get_definition_by_index) else {
+                return Err(format!("unknown block {}", identifier));
+            };
+
+            let Some(property) = definition.properties.iter().find(|property| property.name() == name) else {
+                return Err(format!("block {} has no property {}", identifier, name));
+            };
+
+            block = property_from_json(property, value)
+                .and_then(|value| block.with_property(name, value))
+                .ok_or_else(|| format!("invalid value {} for property {} of block {}", value, name, identifier))?;
+        }
+
+        Ok(block.get_id())
+    }
+}
+
+fn property_to_json(value: PropertyValue) -> Value {
+    match value {
+        PropertyValue::Bool(value) => Value::from(value),
+        PropertyValue::Enum(value) => Value::from(value),
+        PropertyValue::Int(value) => Value::from(value),
+        PropertyValue::Direction(value) => serde_json::to_value(value).unwrap(),
+    }
+}
+
+/// Reads a property value written by `property_to_json`, or None if it's the wrong type for `property`
+fn property_from_json(property: &BlockProperty, value: &Value) -> Option<PropertyValue> {
+    match property {
+        BlockProperty::Bool(_) => value.as_bool().map(PropertyValue::Bool),
+        BlockProperty::Enum(_, options) => {
+            let value = value.as_str()?;
+            options.iter().find(|option| **option == value).map(|option| PropertyValue::Enum(option))
+        }
+        BlockProperty::Int(..) => value
+            .as_i64()
+            .and_then(|value| i32::try_from(value).ok())
+            .map(PropertyValue::Int),
+        BlockProperty::Direction(_) => serde_json::from_value::<AxisAlignedDirection>(value.clone())
+            .ok()
+            .map(PropertyValue::Direction),
+    }
+}
+
+impl TemplateStructure {
+    /// The blocks of the structure turned by `rotation` quarter turns around its anchor
+    pub fn rotated_blocks(&self, rotation: u8) -> impl Iterator<Item = (Vector3<i32>, BlockId)> + '_ {
+        self.blocks
+            .iter()
+            .map(move |(pos, block)| (rotate(*pos, rotation), *block))
+    }
+
+    /// The bounding box of the structure turned by `rotation` quarter turns around its anchor
+    pub fn rotated_bounding_box(&self, rotation: u8) -> StructureBoundingBox {
+        let corner = self.bounding_box.bottom_left;
+        let far_corner = corner + self.bounding_box.size - Vector3::new(1, 1, 1);
+
+        bounding_box([rotate(corner, rotation), rotate(far_corner, rotation)].into_iter()).unwrap()
+    }
+}
+
+/// Flips `pos` along the x axis if `mirror` is set, then turns it by `rotation` quarter turns
+pub fn transform(pos: Vector3<i32>, rotation: u8, mirror: bool) -> Vector3<i32> {
+    if mirror {
+        rotate(Vector3::new(-pos.x, pos.y, pos.z), rotation)
+    } else {
+        rotate(pos, rotation)
+    }
+}
+
+/// Turns `block` the same way `transform` moves positions, so the sides it faces or connects to
+/// still point at the same blocks of the structure
+pub fn transform_block(block: &WorldBlock, rotation: u8, mirror: bool) -> WorldBlock {
+    let state = block.get_state();
+    let mut transformed = state;
+
+    for (name, value) in state.values() {
+        if let PropertyValue::Direction(direction) = value {
+            let side = transform_side(direction as usize, rotation, mirror);
+            transformed = transformed.with(name, DIRECTIONS[side]).unwrap();
+        }
+    }
+
+    // Connections move to the side they now face
+    for (side, name) in SIDE_PROPERTIES.iter().enumerate() {
+        let Some(value) = state.get(name) else {
+            continue;
+        };
+
+        if let Some(moved) = transformed.with(SIDE_PROPERTIES[transform_side(side, rotation, mirror)], value) {
+            transformed = moved;
+        }
+    }
+
+    block.with_state(transformed)
+}
+
+/// The index into `BLOCK_SIDES` that `side` points to after being transformed
+fn transform_side(side: usize, rotation: u8, mirror: bool) -> usize {
+    let direction = transform(BLOCK_SIDES[side], rotation, mirror);
+    BLOCK_SIDES.iter().position(|side| *side == direction).unwrap()
+}
+
+/// Turns `pos` clockwise around the y axis by `rotation` quarter turns
+fn rotate(pos: Vector3<i32>, rotation: u8) -> Vector3<i32> {
+    match rotation % 4 {
+        0 => pos,
+        1 => Vector3::new(-pos.z, pos.y, pos.x),
+        2 => Vector3::new(-pos.x, pos.y, -pos.z),
+        _ => Vector3::new(pos.z, pos.y, -pos.x),
+    }
+}
+
+fn bounding_box(mut positions: impl Iterator<Item = Vector3<i32>>) -> Option<StructureBoundingBox> {
+    let first = positions.next()?;
+    let (min, max) = positions.fold((first, first), |(min, max), pos| (min.inf(&pos), max.sup(&pos)));
+
+    Some(StructureBoundingBox::new(min, max - min + Vector3::new(1, 1, 1)))
+}
+
+/// The directories templates are loaded from, with templates made for this server last so they take priority
+pub fn template_directories() -> [PathBuf; 2] {
+    [
+        Path::new(rc_shared::config!("ASSETS_DIR")).join("game/structures"),
+        PathBuf::from(STRUCTURE_DIRECTORY),
+    ]
+}
+
+/// Finds the template file called `name`, preferring templates made for this server over bundled ones
+pub fn find_template(name: &str) -> Option<PathBuf> {
+    template_directories()
+        .iter()
+        .rev()
+        .map(|directory| directory.join(format!("{}.json", name)))
+        .find(|path| path.is_file())
+}
+
+/// Loads every template in `directories`, logging and skipping any that can't be generated.
+/// Templates in later directories replace earlier ones with the same identifier.
+/// They are sorted by identifier so they generate in the same order however the files are listed.
+pub fn load_templates(
+    directories: &[PathBuf],
+    block_states: &BlockStates,
+    biomes: &BiomeRegistry,
+) -> Vec<TemplateStructure> {
+    let mut structures = BTreeMap::new();
+
+    for directory in directories {
+        let Ok(entries) = fs::read_dir(directory) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            let template = match StructureTemplate::load(&path) {
+                Ok(template) => template,
+                Err(err) => {
+                    error!("Error reading structure template {}: {:?}", path.display(), err);
+                    continue;
+                }
+            };
+
+            match template.resolve(block_states, biomes) {
+                Ok(structure) => {
+                    structures.insert(structure.identifier.clone(), structure);
+                }
+                Err(err) => error!("Invalid structure template {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    info!("Loaded {} structure templates", structures.len());
+
+    structures.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::generation::biome::BiomeRegistry;
+    use crate::game::generation::blocks::GenerationBlocks;
+    use crate::game::generation::structures::template::{load_templates, transform, transform_block, PaletteBlock, StructureTemplate, TemplateBlock};
+    use rc_shared::block::test_block_states;
+    use nalgebra::Vector3;
+    use rc_shared::block::BlockId;
+    use rc_shared::block::entity::{BlockEntityData, InventoryEntity};
+    use rc_shared::item::types::{ItemStack, ItemType};
+    use serde_json::json;
+    use std::fs;
+    use std::path::Path;
+
+    fn block_id(identifier: &str) -> BlockId {
+        test_block_states().get_by_identifier(identifier).unwrap().1.get_id()
+    }
+
+    fn palette_block(identifier: &str) -> PaletteBlock {
+        PaletteBlock::Identifier(identifier.to_string())
+    }
+
+    fn template(blocks: Vec<TemplateBlock>) -> StructureTemplate {
+        StructureTemplate {
+            identifier: String::from("test::pillar"),
+            palette: vec![palette_block("mcv3::block::Stone"), palette_block("mcv3::block::Sand")],
+            blocks,
+            anchor: Vector3::new(1, 0, 0),
+            placement: Default::default(),
+            entities: vec![],
+        }
+    }
+
+    #[test]
+    fn test_resolve_template() {
+        let biomes = BiomeRegistry::new(&GenerationBlocks::new(test_block_states()));
+        let stone = block_id("mcv3::block::Stone");
+        let sand = block_id("mcv3::block::Sand");
+        let structure = template(vec![
+            TemplateBlock { pos: Vector3::new(1, 0, 0), block: 0 },
+            TemplateBlock { pos: Vector3::new(1, 1, 0), block: 0 },
+            TemplateBlock { pos: Vector3::new(3, 2, 0), block: 1 },
+        ])
+        .resolve(test_block_states(), &biomes)
+        .unwrap();
+
+        assert_eq!(structure.blocks, vec![
+            (Vector3::new(0, 0, 0), stone),
+            (Vector3::new(0, 1, 0), stone),
+            (Vector3::new(2, 2, 0), sand),
+        ]);
+        assert_eq!(structure.bounding_box.bottom_left, Vector3::new(0, 0, 0));
+        assert_eq!(structure.bounding_box.size, Vector3::new(3, 3, 1));
+
+        // A quarter turn moves the blocks along x onto z
+        assert!(structure.rotated_blocks(1).any(|block| block == (Vector3::new(0, 2, 2), sand)));
+        let rotated = structure.rotated_bounding_box(1);
+        assert_eq!(rotated.bottom_left, Vector3::new(0, 0, 0));
+        assert_eq!(rotated.size, Vector3::new(1, 3, 3));
+
+        // Unknown blocks
+        let mut unknown = template(vec![TemplateBlock { pos: Vector3::new(0, 0, 0), block: 0 }]);
+        unknown.palette[0] = palette_block("mcv3::block::Missing");
+        assert!(unknown.resolve(test_block_states(), &biomes).is_err());
+
+        // Too large to generate
+        let wide = template(vec![
+            TemplateBlock { pos: Vector3::new(0, 0, 0), block: 0 },
+            TemplateBlock { pos: Vector3::new(20, 0, 0), block: 0 },
+        ]);
+        assert!(wide.resolve(test_block_states(), &biomes).is_err());
+    }
+
+    #[test]
+    fn test_capture_template() {
+        let mut chest = InventoryEntity::new(3);
+        chest.push_item(ItemStack::new(
+            ItemType {
+                identifier: String::from("mcv3::StoneItem"),
+                name: String::from("Stone"),
+                icon: String::from("stone"),
+                block_definition_index: None,
+            },
+            5,
+        ));
+
+        let captured = StructureTemplate::capture(
+            String::from("test::captured"),
+            vec![
+                (Vector3::new(0, 0, 0), palette_block("mcv3::block::Stone")),
+                (Vector3::new(1, 0, 0), palette_block("mcv3::block::Sand")),
+                (Vector3::new(1, 1, 0), palette_block("mcv3::block::Stone")),
+                (Vector3::new(0, 1, 0), palette_block("mcv3::block::Chest")),
+            ],
+            vec![(Vector3::new(0, 1, 0), BlockEntityData::new(&chest))],
+            Vector3::new(1, 0, 0),
+        );
+
+        assert_eq!(captured.palette, vec![
+            palette_block("mcv3::block::Stone"),
+            palette_block("mcv3::block::Sand"),
+            palette_block("mcv3::block::Chest"),
+        ]);
+        assert_eq!(captured.blocks[2], TemplateBlock { pos: Vector3::new(1, 1, 0), block: 0 });
+
+        // Survives being written and read back
+        let path = std::env::temp_dir().join(format!("rc_structure_test_{}.json", std::process::id()));
+        captured.save(&path).unwrap();
+        let loaded = StructureTemplate::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, captured);
+
+        assert_eq!(loaded.resolve_blocks(test_block_states()).unwrap(), vec![
+            (Vector3::new(-1, 0, 0), block_id("mcv3::block::Stone")),
+            (Vector3::new(0, 0, 0), block_id("mcv3::block::Sand")),
+            (Vector3::new(0, 1, 0), block_id("mcv3::block::Stone")),
+            (Vector3::new(-1, 1, 0), block_id("mcv3::block::Chest")),
+        ]);
+
+        // The chest keeps its contents
+        let entities = loaded.block_entities().collect::<Vec<_>>();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].0, Vector3::new(-1, 1, 0));
+        assert_eq!(entities[0].1.get::<InventoryEntity>(), Some(chest));
+    }
+
+    #[test]
+    fn test_palette_block_state() {
+        let block_states = test_block_states();
+        let (_, water) = block_states.get_by_identifier("mcv3::block::Water").unwrap();
+        let water = water.with_property("level", 3).unwrap().with_property("falling", true).unwrap();
+
+        let palette = PaletteBlock::new(&water);
+        assert_eq!(palette, PaletteBlock::State {
+            block: String::from("mcv3::block::Water"),
+            properties: [(String::from("level"), json!(3)), (String::from("falling"), json!(true))].into_iter().collect(),
+        });
+
+        // Keeps its state through being written and read back
+        let written = serde_json::to_string(&palette).unwrap();
+        let read = serde_json::from_str::<PaletteBlock>(&written).unwrap();
+        assert_eq!(read.resolve(block_states).unwrap(), water.get_id());
+
+        // Blocks without properties are written as just their identifier
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+        assert_eq!(serde_json::to_value(PaletteBlock::new(&stone)).unwrap(), json!("mcv3::block::Stone"));
+
+        // Properties left out keep their default value
+        let partial = serde_json::from_value::<PaletteBlock>(json!({ "block": "mcv3::block::Water", "properties": { "level": 3 } })).unwrap();
+        assert_eq!(partial.resolve(block_states).unwrap(), water.with_property("falling", false).unwrap().get_id());
+
+        let invalid = serde_json::from_value::<PaletteBlock>(json!({ "block": "mcv3::block::Water", "properties": { "level": "high" } })).unwrap();
+        assert!(invalid.resolve(block_states).is_err());
+    }
+
+    #[test]
+    fn test_transform() {
+        let pos = Vector3::new(2, 1, 3);
+
+        assert_eq!(transform(pos, 0, false), pos);
+        assert_eq!(transform(pos, 0, true), Vector3::new(-2, 1, 3));
+        assert_eq!(transform(pos, 1, true), Vector3::new(-3, 1, -2));
+
+        // Four quarter turns end where they started
+        assert_eq!(transform(transform(pos, 3, false), 1, false), pos);
+    }
+
+    #[test]
+    fn test_transform_block() {
+        let block_states = test_block_states();
+        let (_, pipe) = block_states.get_by_identifier("mcv3::block::Pipe").unwrap();
+        let pipe = pipe.with_property("up", true).unwrap().with_property("east", true).unwrap();
+        let sides = |rotation, mirror| {
+            let block = transform_block(&pipe, rotation, mirror);
+            ["up", "down", "west", "east", "north", "south"]
+                .into_iter()
+                .filter(|side| block.get_state().get_bool(side))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sides(0, false), ["up", "east"]);
+        // Connections follow the blocks they connect to, which move from +x to +z
+        assert_eq!(sides(1, false), ["up", "south"]);
+        assert_eq!(sides(2, false), ["up", "west"]);
+        assert_eq!(sides(0, true), ["up", "west"]);
+        assert_eq!(sides(1, true), ["up", "north"]);
+
+        // Blocks without sides are left alone
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+        assert_eq!(transform_block(&stone, 1, true).get_id(), stone.get_id());
+    }
+
+    #[test]
+    fn test_bundled_templates() {
+        let directory = Path::new(rc_shared::config!("ASSETS_DIR")).join("game/structures");
+        let bundled = fs::read_dir(&directory).unwrap().count();
+
+        let structures = load_templates(&[directory], test_block_states(), &BiomeRegistry::new(&GenerationBlocks::new(test_block_states())));
+
+        assert!(bundled > 0);
+        assert_eq!(structures.len(), bundled);
+    }
+}
@@ -1,5 +1,6 @@
 use nalgebra::Vector3;
 use rc_shared::chunk::RawChunkData;
+use crate::game::generation::blocks::GenerationBlocks;
 use crate::game::generation::structures::{StructureBoundingBox, StructureGenerator, try_place_block};
 
 pub struct TreeStructureGenerator;
@@ -12,31 +13,31 @@ impl StructureGenerator for TreeStructureGenerator {
         }
     }
 
-    fn spawn(seed: u32, chunk_pos: Vector3<i32>, pos: Vector3<i32>, world: &mut RawChunkData) {
-        try_place_block(chunk_pos, world, pos, 4);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 1, 0), 4);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 2, 0), 4);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 3, 0), 4);
+    fn spawn(seed: u32, chunk_pos: Vector3<i32>, pos: Vector3<i32>, world: &mut RawChunkData, blocks: &GenerationBlocks) {
+        try_place_block(chunk_pos, world, pos, blocks.log);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 1, 0), blocks.log);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 2, 0), blocks.log);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 3, 0), blocks.log);
 
-        try_place_block(chunk_pos, world, pos + Vector3::new(1, 3, 0), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(1, 3, -1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(1, 3, 1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 3, 1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 3, -1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 3, 0), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 3, -1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 3, 1), 5);
+        try_place_block(chunk_pos, world, pos + Vector3::new(1, 3, 0), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(1, 3, -1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(1, 3, 1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 3, 1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 3, -1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 3, 0), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 3, -1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 3, 1), blocks.leaves);
 
-        try_place_block(chunk_pos, world, pos + Vector3::new(1, 4, 0), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(1, 4, -1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(1, 4, 1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 4, 1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 4, -1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 4, 0), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 4, 0), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 4, -1), 5);
-        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 4, 1), 5);
+        try_place_block(chunk_pos, world, pos + Vector3::new(1, 4, 0), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(1, 4, -1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(1, 4, 1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 4, 1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 4, -1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 4, 0), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 4, 0), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 4, -1), blocks.leaves);
+        try_place_block(chunk_pos, world, pos + Vector3::new(-1, 4, 1), blocks.leaves);
 
-        try_place_block(chunk_pos, world, pos + Vector3::new(0, 5, 0), 5);
+        try_place_block(chunk_pos, world, pos + Vector3::new(0, 5, 0), blocks.leaves);
     }
 }
\ No newline at end of file
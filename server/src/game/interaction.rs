@@ -0,0 +1,166 @@
+use crate::game::transform::Transform;
+use crate::game::world::data::WorldData;
+use nalgebra::Vector3;
+use rc_shared::aabb::Aabb;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::{ChunkPosition, GlobalBlockPosition};
+use rc_shared::game_objects::{PlayerGameObjectData, MAX_INTERACTION_DISTANCE, PLAYER_EYE_HEIGHT};
+use rc_shared::helpers::global_to_local_position;
+use std::collections::HashSet;
+
+/// Extra reach allowed over the client's limit, to account for the player moving while the packet was in flight
+const REACH_LEEWAY: f32 = 1.5;
+
+/// Why the server refused to let a player place or break a block
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlockActionRejection {
+    /// The block is further from the player than they can reach
+    OutOfReach,
+    /// The block is in a chunk the player doesn't have loaded
+    ChunkNotLoaded,
+    /// There is already a block where the player tried to place one, which can't be replaced
+    Occupied,
+    /// The placed block would be inside a player
+    CollidesWithPlayer,
+    /// There is no block to break, or only a fluid
+    NothingToBreak,
+}
+
+/// Checks whether `player` is allowed to place a block at `pos`.
+/// `players` are the transforms of every player, which the block cannot be placed inside of.
+pub fn validate_place<'a>(
+    world: &WorldData,
+    block_states: &BlockStates,
+    player: &Transform,
+    loaded_chunks: Option<&HashSet<ChunkPosition>>,
+    pos: GlobalBlockPosition,
+    mut players: impl Iterator<Item = &'a Transform>,
+) -> Result<(), BlockActionRejection> {
+    validate_reach(player, loaded_chunks, pos)?;
+
+    match world.get_block_id(pos) {
+        None => return Err(BlockActionRejection::ChunkNotLoaded),
+        Some(0) => {}
+        Some(id) if block_states.get_block_from_id(id).is_replaceable() => {}
+        Some(_) => return Err(BlockActionRejection::Occupied),
+    }
+
+    let block_collider = Aabb::new(pos.cast::<f32>(), Vector3::new(1.0, 1.0, 1.0));
+
+    if players.any(|transform| PlayerGameObjectData::collider()
+        .offset(transform.position)
+        .aabb_collides(&block_collider))
+    {
+        return Err(BlockActionRejection::CollidesWithPlayer);
+    }
+
+    Ok(())
+}
+
+/// Checks whether `player` is allowed to break the block at `pos`
+pub fn validate_destroy(
+    world: &WorldData,
+    block_states: &BlockStates,
+    player: &Transform,
+    loaded_chunks: Option<&HashSet<ChunkPosition>>,
+    pos: GlobalBlockPosition,
+) -> Result<(), BlockActionRejection> {
+    validate_reach(player, loaded_chunks, pos)?;
+
+    match world.get_block_id(pos) {
+        None => Err(BlockActionRejection::ChunkNotLoaded),
+        Some(0) => Err(BlockActionRejection::NothingToBreak),
+        Some(id) if block_states.get_block_from_id(id).is_fluid() => Err(BlockActionRejection::NothingToBreak),
+        Some(_) => Ok(()),
+    }
+}
+
+fn validate_reach(
+    player: &Transform,
+    loaded_chunks: Option<&HashSet<ChunkPosition>>,
+    pos: GlobalBlockPosition,
+) -> Result<(), BlockActionRejection> {
+    let (chunk_pos, _) = global_to_local_position(pos);
+
+    if !loaded_chunks.is_some_and(|chunks| chunks.contains(&chunk_pos)) {
+        return Err(BlockActionRejection::ChunkNotLoaded);
+    }
+
+    let eyes = player.position + Vector3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let block_centre = pos.cast::<f32>() + Vector3::new(0.5, 0.5, 0.5);
+
+    if (block_centre - eyes).magnitude() > MAX_INTERACTION_DISTANCE + REACH_LEEWAY {
+        return Err(BlockActionRejection::OutOfReach);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::interaction::{validate_destroy, validate_place, BlockActionRejection};
+    use crate::game::transform::Transform;
+    use crate::game::world::data::WorldData;
+    use nalgebra::Vector3;
+    use rc_shared::block::test_block_states;
+    use std::collections::HashSet;
+
+    fn world() -> WorldData {
+        let (_, water) = test_block_states().get_by_identifier("mcv3::block::Water").unwrap();
+
+        let mut world = WorldData::default();
+        world.insert_chunk(ChunkData::blank(Vector3::new(0, 0, 0)));
+        world.set_block_id(Vector3::new(4, 4, 4), 1);
+        world.set_block_id(Vector3::new(3, 4, 4), water.get_id());
+        world
+    }
+
+    #[test]
+    fn test_validate_place() {
+        let block_states = test_block_states();
+        let world = world();
+        let loaded = HashSet::from([Vector3::new(0, 0, 0)]);
+        let player = Transform::from_translation(Vector3::new(2.5, 5.0, 2.5));
+
+        assert_eq!(validate_place(&world, block_states, &player, Some(&loaded), Vector3::new(4, 5, 4), [].iter()), Ok(()));
+        assert_eq!(
+            validate_place(&world, block_states, &player, Some(&loaded), Vector3::new(4, 4, 4), [].iter()),
+            Err(BlockActionRejection::Occupied)
+        );
+        // Water is replaced by the placed block
+        assert_eq!(validate_place(&world, block_states, &player, Some(&loaded), Vector3::new(3, 4, 4), [].iter()), Ok(()));
+        assert_eq!(
+            validate_place(&world, block_states, &player, Some(&loaded), Vector3::new(2, 5, 2), [player].iter()),
+            Err(BlockActionRejection::CollidesWithPlayer)
+        );
+        assert_eq!(
+            validate_place(&world, block_states, &player, None, Vector3::new(4, 5, 4), [].iter()),
+            Err(BlockActionRejection::ChunkNotLoaded)
+        );
+
+        let far_player = Transform::from_translation(Vector3::new(15.0, 15.0, 15.0));
+        assert_eq!(
+            validate_place(&world, block_states, &far_player, Some(&loaded), Vector3::new(0, 0, 0), [].iter()),
+            Err(BlockActionRejection::OutOfReach)
+        );
+    }
+
+    #[test]
+    fn test_validate_destroy() {
+        let block_states = test_block_states();
+        let world = world();
+        let loaded = HashSet::from([Vector3::new(0, 0, 0)]);
+        let player = Transform::from_translation(Vector3::new(2.5, 5.0, 2.5));
+
+        assert_eq!(validate_destroy(&world, block_states, &player, Some(&loaded), Vector3::new(4, 4, 4)), Ok(()));
+        assert_eq!(
+            validate_destroy(&world, block_states, &player, Some(&loaded), Vector3::new(4, 5, 4)),
+            Err(BlockActionRejection::NothingToBreak)
+        );
+        assert_eq!(
+            validate_destroy(&world, block_states, &player, Some(&loaded), Vector3::new(3, 4, 4)),
+            Err(BlockActionRejection::NothingToBreak)
+        );
+    }
+}
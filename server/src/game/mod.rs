@@ -9,4 +9,6 @@ pub mod world;
 pub mod inventory;
 pub mod entity;
 pub mod join_message;
-pub mod commands;
\ No newline at end of file
+pub mod commands;
+pub mod interaction;
+pub mod movement;
\ No newline at end of file
@@ -0,0 +1,318 @@
+use crate::game::world::data::WorldData;
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{EventReader, ResMut, Resource};
+use nalgebra::Vector3;
+use rc_networking::events::disconnect::NetworkDisconnectionEvent;
+use rc_shared::block::BlockStates;
+use rc_shared::constants::UserId;
+use rc_shared::game_objects::PlayerGameObjectData;
+use rc_shared::physics::{max_fall_speed, max_jump_height, max_player_horizontal_speed, GRAVITY_STRENGTH};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Multiplier on the client's top speed, to account for packets arriving in bursts and air movement
+const SPEED_LEEWAY: f32 = 1.5;
+
+/// The most horizontal distance a player can save up by standing still, in blocks
+const MAX_HORIZONTAL_ALLOWANCE: f32 = 4.0;
+
+/// The distance a player can drop straight away after standing on the ground, to account for packets
+/// arriving in bursts
+const FALL_LEEWAY: f32 = 1.5;
+
+/// The most distance a player can save up to fall, in blocks
+const MAX_FALL_ALLOWANCE: f32 = 8.0;
+
+/// Extra height allowed above a jump from the last ground the player stood on
+const JUMP_LEEWAY: f32 = 0.5;
+
+/// How far below the player to look for the ground they are standing on
+const GROUND_CHECK_DISTANCE: f32 = 0.1;
+
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MovementValidator>()
+            .add_systems(Update, handle_disconnections);
+    }
+}
+
+/// Why the server refused a player's move
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MovementRejection {
+    /// The player moved further horizontally than they could have in the time since their last move
+    TooFast,
+    /// The player is higher above the last ground they stood on than they could have jumped
+    TooHigh,
+    /// The player dropped further than they could have fallen since they last stood on the ground
+    FellTooFast,
+    /// The player's collider is inside of a solid block
+    InsideBlock,
+}
+
+/// Movement history for a single player, used to decide whether their next move is possible
+#[derive(Debug, Clone)]
+pub struct PlayerMovement {
+    last_update: Instant,
+    /// Horizontal distance the player can move before being considered too fast
+    horizontal_allowance: f32,
+    /// The height of the last ground the player was standing on
+    ground_height: f32,
+    /// The fastest the player could be falling, having accelerated since they last stood on the ground
+    fall_speed: f32,
+    /// Distance the player can drop before being considered to be falling too fast
+    fall_allowance: f32,
+    /// The number of moves rejected from this player this session
+    pub violations: u32,
+}
+
+impl PlayerMovement {
+    fn new(position: Vector3<f32>, now: Instant) -> PlayerMovement {
+        PlayerMovement {
+            last_update: now,
+            horizontal_allowance: MAX_HORIZONTAL_ALLOWANCE,
+            ground_height: position.y,
+            fall_speed: 0.0,
+            fall_allowance: FALL_LEEWAY,
+            violations: 0,
+        }
+    }
+}
+
+/// Checks player movement packets against the client's physics limits, since players send their own position
+#[derive(Resource, Default)]
+pub struct MovementValidator {
+    pub players: HashMap<UserId, PlayerMovement>,
+}
+
+impl MovementValidator {
+    /// Checks whether `user` could have moved from `from` to `to`, counting a violation if not
+    pub fn validate(
+        &mut self,
+        user: UserId,
+        world: &WorldData,
+        block_states: &BlockStates,
+        from: Vector3<f32>,
+        to: Vector3<f32>,
+        now: Instant,
+    ) -> Result<(), MovementRejection> {
+        let movement = self
+            .players
+            .entry(user)
+            .or_insert_with(|| PlayerMovement::new(from, now));
+
+        let elapsed = now.saturating_duration_since(movement.last_update).as_secs_f32();
+        movement.last_update = now;
+        movement.horizontal_allowance = (movement.horizontal_allowance
+            + max_player_horizontal_speed() * SPEED_LEEWAY * elapsed)
+            .min(MAX_HORIZONTAL_ALLOWANCE);
+        movement.fall_speed = (movement.fall_speed + GRAVITY_STRENGTH * elapsed).min(max_fall_speed());
+        movement.fall_allowance = (movement.fall_allowance + movement.fall_speed * SPEED_LEEWAY * elapsed)
+            .min(MAX_FALL_ALLOWANCE);
+
+        let horizontal_distance = Vector3::new(to.x - from.x, 0.0, to.z - from.z).magnitude();
+        let drop = (from.y - to.y).max(0.0);
+
+        let result = if horizontal_distance > movement.horizontal_allowance {
+            Err(MovementRejection::TooFast)
+        } else if to.y > movement.ground_height + max_jump_height() + JUMP_LEEWAY {
+            Err(MovementRejection::TooHigh)
+        } else if drop > movement.fall_allowance {
+            Err(MovementRejection::FellTooFast)
+        } else if collides_with_blocks(world, block_states, to) {
+            Err(MovementRejection::InsideBlock)
+        } else {
+            Ok(())
+        };
+
+        match result {
+            Ok(()) => {
+                movement.horizontal_allowance -= horizontal_distance;
+                movement.fall_allowance -= drop;
+
+                // Players can swim up through water, so it holds them up like the ground
+                if collides_with_blocks(world, block_states, to - Vector3::new(0.0, GROUND_CHECK_DISTANCE, 0.0))
+                    || in_water(world, block_states, to)
+                {
+                    movement.ground_height = to.y;
+                    movement.fall_speed = 0.0;
+                    movement.fall_allowance = FALL_LEEWAY;
+                }
+            }
+            Err(_) => movement.violations += 1,
+        }
+
+        result
+    }
+
+    /// Forgets a player's movement history, for when the server moves them to `position` itself
+    pub fn reset(&mut self, user: UserId, position: Vector3<f32>) {
+        if let Some(movement) = self.players.get_mut(&user) {
+            *movement = PlayerMovement {
+                violations: movement.violations,
+                ..PlayerMovement::new(position, Instant::now())
+            };
+        }
+    }
+
+    /// The number of moves rejected from a player this session
+    pub fn violations(&self, user: &UserId) -> u32 {
+        self.players.get(user).map_or(0, |movement| movement.violations)
+    }
+}
+
+/// Whether a player standing at `position` would be inside any block colliders
+fn collides_with_blocks(world: &WorldData, block_states: &BlockStates, position: Vector3<f32>) -> bool {
+    let collider = PlayerGameObjectData::collider().offset(position);
+
+    collider
+        .get_surrounding_voxel_collision_colliders(world, block_states)
+        .iter()
+        .any(|block| block.aabb_collides(&collider))
+}
+
+fn in_water(world: &WorldData, block_states: &BlockStates, position: Vector3<f32>) -> bool {
+    PlayerGameObjectData::collider()
+        .offset(position)
+        .fluid_submersion(world, block_states)
+        > 0.0
+}
+
+fn handle_disconnections(
+    mut network_disconnection_events: EventReader<NetworkDisconnectionEvent>,
+    mut validator: ResMut<MovementValidator>,
+) {
+    for disconnection in network_disconnection_events.read() {
+        validator.players.remove(&disconnection.client);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::movement::{MovementRejection, MovementValidator};
+    use crate::game::world::data::WorldData;
+    use rc_shared::block::test_block_states;
+    use nalgebra::Vector3;
+    use rc_shared::constants::UserId;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_validate_movement() {
+        let block_states = test_block_states();
+        let stone = block_states.get_by_identifier("mcv3::block::Stone").unwrap().0 as u32;
+
+        // Stone floor at y = 0
+        let mut world = WorldData::default();
+        world.insert_chunk(ChunkData::blank(Vector3::new(0, 0, 0)));
+        for x in 0..16 {
+            for z in 0..16 {
+                world.set_block_id(Vector3::new(x, 0, z), stone);
+            }
+        }
+        world.set_block_id(Vector3::new(8, 1, 8), stone);
+
+        let user = UserId(1);
+        let start = Vector3::new(4.5, 1.0, 4.5);
+        let mut now = Instant::now();
+        let mut validator = MovementValidator::default();
+
+        let mut step = |validator: &mut MovementValidator, from: Vector3<f32>, to: Vector3<f32>| {
+            now += Duration::from_millis(50);
+            validator.validate(user, &world, block_states, from, to, now)
+        };
+
+        // Walking
+        assert_eq!(step(&mut validator, start, start + Vector3::new(0.3, 0.0, 0.0)), Ok(()));
+
+        // Teleporting
+        assert_eq!(
+            step(&mut validator, start, start + Vector3::new(10.0, 0.0, 0.0)),
+            Err(MovementRejection::TooFast)
+        );
+
+        // Flying
+        assert_eq!(
+            step(&mut validator, start, start + Vector3::new(0.0, 3.0, 0.0)),
+            Err(MovementRejection::TooHigh)
+        );
+
+        // Walking into a block
+        assert_eq!(
+            step(&mut validator, Vector3::new(7.5, 1.0, 8.5), Vector3::new(8.5, 1.0, 8.5)),
+            Err(MovementRejection::InsideBlock)
+        );
+
+        // Jumping onto a block raises the height the player can jump from
+        assert_eq!(step(&mut validator, Vector3::new(8.5, 1.0, 7.5), Vector3::new(8.5, 2.0, 8.5)), Ok(()));
+        assert_eq!(step(&mut validator, Vector3::new(8.5, 2.0, 8.5), Vector3::new(8.5, 3.5, 8.5)), Ok(()));
+
+        assert_eq!(validator.violations(&user), 3);
+    }
+
+    #[test]
+    fn test_swimming_up() {
+        let block_states = test_block_states();
+        let (_, water) = block_states.get_by_identifier("mcv3::block::Water").unwrap();
+
+        let mut world = WorldData::default();
+        world.insert_chunk(ChunkData::blank(Vector3::new(0, 0, 0)));
+        for y in 0..10 {
+            world.set_block_id(Vector3::new(4, y, 4), water.get_id());
+        }
+
+        let user = UserId(1);
+        let mut now = Instant::now();
+        let mut validator = MovementValidator::default();
+
+        // Far higher than a jump, but each step starts in water
+        for y in 0..10 {
+            now += Duration::from_millis(200);
+            let from = Vector3::new(4.5, y as f32, 4.5);
+            assert_eq!(validator.validate(user, &world, block_states, from, from + Vector3::new(0.0, 1.0, 0.0), now), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_falling() {
+        let block_states = test_block_states();
+        let stone = block_states.get_by_identifier("mcv3::block::Stone").unwrap().1.get_id();
+
+        // A cave between y = 1 and y = 8, with its roof only covering x < 8
+        let mut world = WorldData::default();
+        world.insert_chunk(ChunkData::blank(Vector3::new(0, 0, 0)));
+        for x in 0..16 {
+            for z in 0..16 {
+                world.set_block_id(Vector3::new(x, 0, z), stone);
+                if x < 8 {
+                    world.set_block_id(Vector3::new(x, 8, z), stone);
+                }
+            }
+        }
+
+        let mut now = Instant::now();
+        let mut validator = MovementValidator::default();
+
+        // Dropping through the roof into the cave
+        now += Duration::from_millis(50);
+        let on_roof = Vector3::new(4.5, 9.0, 4.5);
+        assert_eq!(
+            validator.validate(UserId(1), &world, block_states, on_roof, Vector3::new(4.5, 1.0, 4.5), now),
+            Err(MovementRejection::FellTooFast)
+        );
+
+        // Falling past the edge of the roof, as the client would
+        let mut y = 9.0;
+        let mut t = 0.0;
+        while y > 1.0 {
+            now += Duration::from_millis(50);
+            t += 0.05;
+
+            let next = (9.0 - 0.5 * 30.0 * t * t).max(1.0);
+            let from = Vector3::new(12.5, y, 4.5);
+            assert_eq!(validator.validate(UserId(2), &world, block_states, from, Vector3::new(12.5, next, 4.5), now), Ok(()));
+            y = next;
+        }
+    }
+}
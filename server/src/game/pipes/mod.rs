@@ -0,0 +1,413 @@
+pub mod network;
+pub mod transport;
+
+use crate::config::ServerConfig;
+use crate::error::ServerError;
+use crate::game::pipes::network::{PipeNetwork, PipeNetworks};
+use crate::game::pipes::transport::{ItemProgress, TravellingItem};
+use crate::game::update::block_entities::BlockEntityChangedEvent;
+use crate::game::update::BlockUpdateEvent;
+use crate::helpers::write_atomic;
+use crate::systems::chunk::ChunkSystem;
+use crate::systems::connection::message::item_drop_request;
+use crate::systems::game_object::spawn::SpawnGameObjectRequest;
+use crate::systems::tick::ServerTick;
+use crate::WorldData;
+use bevy::prelude::{App, EventReader, EventWriter, FixedUpdate, IntoSystemConfigs, Plugin, Res, ResMut, Resource, Time, Update};
+use nalgebra::Vector3;
+use rc_networking::protocol::clientbound::pipe_item::{PipeItemRemoved, PipeItemSent};
+use rc_networking::protocol::Protocol;
+use rc_networking::types::SendPacket;
+use rc_shared::block::entity::InventoryEntity;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::{ChunkPosition, GlobalBlockPosition};
+use rc_shared::helpers::{global_to_local_position, local_to_global_position};
+use rc_shared::item::types::ItemStack;
+use rc_shared::viewable_direction::BLOCK_SIDES;
+use rc_shared::CHUNK_SIZE;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+const PIPE_IDENTIFIER: &str = "mcv3::block::Pipe";
+
+/// The items travelling through pipes when the world was last saved
+pub const PIPE_ITEMS_PATH: &str = "./world/pipe_items";
+
+pub struct PipePlugin;
+
+impl Plugin for PipePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PipeSystem>()
+            .add_systems(Update, (track_loaded_chunks, track_pipe_changes).chain())
+            .add_systems(FixedUpdate, (transfer_items, move_items).chain());
+    }
+}
+
+/// The pipe networks of the loaded world, and the items travelling through them
+#[derive(Resource, Default)]
+pub struct PipeSystem {
+    pub networks: PipeNetworks,
+    pub items: Vec<TravellingItem>,
+    /// Chunks whose pipes have been added to the networks
+    scanned_chunks: HashSet<ChunkPosition>,
+    next_item_id: u64,
+}
+
+impl PipeSystem {
+    /// Writes the items travelling through pipes. They have already been taken out of their source
+    /// inventories, so need saving alongside the chunks for them not to be lost
+    pub fn save_items(&self, path: impl AsRef<Path>) -> Result<(), ServerError> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        write_atomic(path, serde_json::to_string(&self.items)?)?;
+
+        Ok(())
+    }
+
+    /// Reads the items that were travelling through pipes when the world was saved. They carry on
+    /// once the chunks along their path load
+    pub fn load_items(&mut self, path: impl AsRef<Path>) -> Result<(), ServerError> {
+        if !fs::exists(&path)? {
+            return Ok(());
+        }
+
+        self.items = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        self.next_item_id = self.items.iter().map(|item| item.id + 1).max().unwrap_or(0);
+
+        Ok(())
+    }
+}
+
+/// An inventory next to a network, and the pipe it's connected through
+type ConnectedInventory = (GlobalBlockPosition, GlobalBlockPosition);
+
+fn is_pipe(block_states: &BlockStates, block_id: u32) -> bool {
+    block_states.get_block_from_id(block_id).get_identifier() == PIPE_IDENTIFIER
+}
+
+/// Adds the pipes of newly loaded chunks to the networks, and removes those of unloaded chunks
+fn track_loaded_chunks(
+    mut pipes: ResMut<PipeSystem>,
+    world_data: Res<WorldData>,
+    block_states: Res<BlockStates>,
+) {
+    let PipeSystem { networks, scanned_chunks, .. } = &mut *pipes;
+
+    let unloaded = scanned_chunks
+        .iter()
+        .filter(|chunk| !world_data.chunks.contains_key(chunk))
+        .copied()
+        .collect::<HashSet<ChunkPosition>>();
+
+    if !unloaded.is_empty() {
+        let removed = networks
+            .pipes()
+            .filter(|pos| unloaded.contains(&global_to_local_position(*pos).0))
+            .collect::<Vec<GlobalBlockPosition>>();
+
+        networks.remove_pipes(removed);
+        scanned_chunks.retain(|chunk| !unloaded.contains(chunk));
+    }
+
+    for (position, chunk) in &world_data.chunks {
+        if !scanned_chunks.insert(*position) {
+            continue;
+        }
+
+        if !chunk.world.block_ids().iter().any(|id| is_pipe(&block_states, *id)) {
+            continue;
+        }
+
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    let local = Vector3::new(x, y, z);
+                    if is_pipe(&block_states, chunk.world.get(local)) {
+                        networks.add_pipe(local_to_global_position(*position, local));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the networks up to date as pipes are placed and broken
+fn track_pipe_changes(
+    mut pipes: ResMut<PipeSystem>,
+    mut update_event: EventReader<BlockUpdateEvent>,
+    block_states: Res<BlockStates>,
+) {
+    for event in update_event.read() {
+        if is_pipe(&block_states, event.block_id) {
+            pipes.networks.add_pipe(event.pos);
+        } else if pipes.networks.contains(event.pos) {
+            pipes.networks.remove_pipe(event.pos);
+        }
+    }
+}
+
+/// The inventories next to a network, each with the pipe it's connected to. Inventories sitting on
+/// top of a pipe feed items into the network, the rest receive them.
+fn find_inventories(
+    network: &PipeNetwork,
+    networks: &PipeNetworks,
+    world_data: &WorldData,
+) -> (Vec<ConnectedInventory>, Vec<ConnectedInventory>) {
+    let mut sources = HashMap::new();
+    let mut destinations = HashMap::new();
+
+    for pipe in &network.pipes {
+        for (i, side) in BLOCK_SIDES.iter().enumerate() {
+            let pos = pipe + side;
+
+            if networks.contains(pos)
+                || !world_data.get_block_entity(pos).is_some_and(|entity| entity.is::<InventoryEntity>())
+            {
+                continue;
+            }
+
+            // The first side is up
+            if i == 0 {
+                sources.entry(pos).or_insert(*pipe);
+            } else {
+                destinations.entry(pos).or_insert(*pipe);
+            }
+        }
+    }
+
+    destinations.retain(|pos, _| !sources.contains_key(pos));
+
+    // Sorted so items go the same way every time
+    let mut sources = sources.into_iter().collect::<Vec<_>>();
+    let mut destinations = destinations.into_iter().collect::<Vec<_>>();
+    sources.sort_by_key(|(pos, _)| (pos.x, pos.y, pos.z));
+    destinations.sort_by_key(|(pos, _)| (pos.x, pos.y, pos.z));
+
+    (sources, destinations)
+}
+
+/// Pulls items out of the inventories feeding each network, sending them to the closest inventory
+/// that has space for them
+fn transfer_items(
+    mut pipes: ResMut<PipeSystem>,
+    mut world_data: ResMut<WorldData>,
+    mut changed_event: EventWriter<BlockEntityChangedEvent>,
+    mut send_packet: EventWriter<SendPacket>,
+    chunk_system: Res<ChunkSystem>,
+    config: Res<ServerConfig>,
+    tick: Res<ServerTick>,
+) {
+    if config.pipe_transfer_interval == 0 || !tick.0.is_multiple_of(config.pipe_transfer_interval) {
+        return;
+    }
+
+    let PipeSystem { networks, items, next_item_id, .. } = &mut *pipes;
+
+    for (_, network) in networks.iter() {
+        let (sources, destinations) = find_inventories(network, networks, &world_data);
+
+        if destinations.is_empty() {
+            continue;
+        }
+
+        for (source, source_pipe) in sources {
+            let Some(mut inventory) = world_data
+                .get_block_entity(source)
+                .and_then(|entity| entity.get::<InventoryEntity>()) else {
+                continue;
+            };
+
+            let Some(next) = inventory.slots.iter().flatten().next() else {
+                continue;
+            };
+
+            // The closest inventory with space
+            let route = destinations
+                .iter()
+                .filter(|(destination, _)| has_space(&world_data, items, *destination, next))
+                .filter_map(|(destination, pipe)| Some((*destination, networks.path(source_pipe, *pipe)?)))
+                .min_by_key(|(_, path)| path.len());
+
+            let Some((destination, path)) = route else {
+                continue;
+            };
+
+            let item = inventory.take_items(config.pipe_transfer_amount).unwrap();
+            world_data.get_block_entity_mut(source).unwrap().set(&inventory);
+            changed_event.send(BlockEntityChangedEvent { pos: source });
+
+            // Everyone that can see part of the route
+            let viewers = chunk_system
+                .user_loaded_chunks
+                .iter()
+                .filter(|(_, chunks)| path.iter().any(|pos| chunks.contains(&global_to_local_position(*pos).0)))
+                .map(|(user, _)| *user)
+                .collect::<Vec<_>>();
+
+            let id = *next_item_id;
+            *next_item_id += 1;
+
+            for user in &viewers {
+                send_packet.send(SendPacket(
+                    Protocol::PipeItemSent(PipeItemSent {
+                        id,
+                        item_identifier: item.item.identifier.clone(),
+                        path: path.clone(),
+                        speed: config.pipe_item_speed,
+                    }),
+                    *user,
+                ));
+            }
+
+            items.push(TravellingItem {
+                id,
+                item,
+                path,
+                destination,
+                progress: 0.0,
+                viewers,
+            });
+        }
+    }
+}
+
+/// Whether `item` fits in the inventory at `destination`, once the items already on their way to it arrive
+fn has_space(
+    world_data: &WorldData,
+    items: &[TravellingItem],
+    destination: GlobalBlockPosition,
+    item: &ItemStack,
+) -> bool {
+    let Some(mut inventory) = world_data
+        .get_block_entity(destination)
+        .and_then(|entity| entity.get::<InventoryEntity>()) else {
+        return false;
+    };
+
+    for travelling in items.iter().filter(|travelling| travelling.destination == destination) {
+        if !inventory.push_item(travelling.item.clone()) {
+            return false;
+        }
+    }
+
+    inventory.can_fit(item)
+}
+
+/// Moves items along their pipes, delivering those that arrive. Items that can't be delivered, or
+/// are left in a broken pipe, are dropped into the world. Items reaching chunks that aren't loaded
+/// wait for them to load.
+fn move_items(
+    mut pipes: ResMut<PipeSystem>,
+    mut world_data: ResMut<WorldData>,
+    mut changed_event: EventWriter<BlockEntityChangedEvent>,
+    mut send_packet: EventWriter<SendPacket>,
+    mut spawn_event: EventWriter<SpawnGameObjectRequest>,
+    config: Res<ServerConfig>,
+    time: Res<Time>,
+) {
+    let PipeSystem { networks, items, scanned_chunks, .. } = &mut *pipes;
+    let distance = config.pipe_item_speed * time.delta_seconds();
+
+    items.retain_mut(|item| {
+        // Only chunks whose pipes are in the networks, so a chunk that has just loaded isn't taken for broken pipes
+        let is_loaded = |pos: GlobalBlockPosition| {
+            let chunk = global_to_local_position(pos).0;
+            scanned_chunks.contains(&chunk) && world_data.chunks.contains_key(&chunk)
+        };
+
+        let drop_at = match item.advance(distance, networks, is_loaded) {
+            ItemProgress::Travelling | ItemProgress::Waiting => return true,
+            ItemProgress::Stranded(pos) => Some(pos),
+            ItemProgress::Arrived => {
+                if deliver(&mut world_data, item) {
+                    changed_event.send(BlockEntityChangedEvent { pos: item.destination });
+                    None
+                } else {
+                    // The inventory was filled or removed while the item was on its way
+                    Some(item.position())
+                }
+            }
+        };
+
+        if let Some(pos) = drop_at {
+            spawn_event.send(item_drop_request(item.item.clone(), pos));
+        }
+
+        for user in &item.viewers {
+            send_packet.send(SendPacket(Protocol::PipeItemRemoved(PipeItemRemoved { id: item.id }), *user));
+        }
+
+        false
+    });
+}
+
+/// Puts an item into its destination inventory, returning false if it doesn't fit
+fn deliver(world_data: &mut WorldData, item: &TravellingItem) -> bool {
+    let Some(entity) = world_data.get_block_entity_mut(item.destination) else {
+        return false;
+    };
+    let Some(mut inventory) = entity.get::<InventoryEntity>() else {
+        return false;
+    };
+
+    if !inventory.push_item(item.item.clone()) {
+        return false;
+    }
+
+    entity.set(&inventory);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::pipes::transport::TravellingItem;
+    use crate::game::pipes::PipeSystem;
+    use nalgebra::Vector3;
+    use rc_shared::item::types::{ItemStack, ItemType};
+
+    #[test]
+    fn test_travelling_items_saved() {
+        let dir = std::env::temp_dir().join(format!("rc_pipe_items_test_{}", std::process::id()));
+        let path = dir.join("pipe_items");
+
+        let mut pipes = PipeSystem::default();
+        pipes.items.push(TravellingItem {
+            id: 4,
+            item: ItemStack::new(
+                ItemType {
+                    identifier: String::from("mcv3::StoneItem"),
+                    name: String::from("Stone"),
+                    icon: String::from("stone"),
+                    block_definition_index: None,
+                },
+                3,
+            ),
+            path: (0..5).map(|x| Vector3::new(x, 0, 0)).collect(),
+            destination: Vector3::new(5, 0, 0),
+            progress: 2.5,
+            viewers: vec![],
+        });
+
+        pipes.save_items(&path).unwrap();
+
+        let mut loaded = PipeSystem::default();
+        loaded.load_items(&path).unwrap();
+
+        assert_eq!(loaded.items, pipes.items);
+        // New items don't reuse the ids of those still travelling
+        assert_eq!(loaded.next_item_id, 5);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_saved_travelling_items() {
+        let mut pipes = PipeSystem::default();
+        pipes.load_items(std::env::temp_dir().join("rc_pipe_items_test_missing")).unwrap();
+
+        assert!(pipes.items.is_empty());
+    }
+}
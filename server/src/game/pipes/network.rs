@@ -0,0 +1,283 @@
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::viewable_direction::BLOCK_SIDES;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NetworkId(u64);
+
+/// A group of pipes connected to each other
+#[derive(Debug, Default)]
+pub struct PipeNetwork {
+    pub pipes: HashSet<GlobalBlockPosition>,
+}
+
+/// Every loaded pipe grouped into networks. Networks are kept up to date as pipes are added and
+/// removed, merging when a pipe joins two networks and splitting when one is cut in two.
+#[derive(Debug, Default)]
+pub struct PipeNetworks {
+    networks: HashMap<NetworkId, PipeNetwork>,
+    pipe_networks: HashMap<GlobalBlockPosition, NetworkId>,
+    next_id: u64,
+}
+
+impl PipeNetworks {
+    pub fn contains(&self, pos: GlobalBlockPosition) -> bool {
+        self.pipe_networks.contains_key(&pos)
+    }
+
+    pub fn network_of(&self, pos: GlobalBlockPosition) -> Option<NetworkId> {
+        self.pipe_networks.get(&pos).copied()
+    }
+
+    pub fn get(&self, id: NetworkId) -> Option<&PipeNetwork> {
+        self.networks.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NetworkId, &PipeNetwork)> {
+        self.networks.iter().map(|(id, network)| (*id, network))
+    }
+
+    /// Every pipe in every network
+    pub fn pipes(&self) -> impl Iterator<Item = GlobalBlockPosition> + '_ {
+        self.pipe_networks.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.networks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty()
+    }
+
+    fn new_network(&mut self, pipes: HashSet<GlobalBlockPosition>) -> NetworkId {
+        let id = NetworkId(self.next_id);
+        self.next_id += 1;
+
+        for pipe in &pipes {
+            self.pipe_networks.insert(*pipe, id);
+        }
+        self.networks.insert(id, PipeNetwork { pipes });
+
+        id
+    }
+
+    /// Adds a pipe, joining it to the networks of any pipes next to it
+    pub fn add_pipe(&mut self, pos: GlobalBlockPosition) {
+        if self.contains(pos) {
+            return;
+        }
+
+        let mut neighbours = BLOCK_SIDES
+            .iter()
+            .filter_map(|side| self.network_of(pos + side))
+            .collect::<Vec<NetworkId>>();
+        neighbours.sort_by_key(|id| id.0);
+        neighbours.dedup();
+
+        // Merge everything into the largest network so the fewest pipes move
+        let Some(&target) = neighbours
+            .iter()
+            .max_by_key(|id| self.networks[id].pipes.len()) else {
+            self.new_network(HashSet::from([pos]));
+            return;
+        };
+
+        for id in neighbours {
+            if id == target {
+                continue;
+            }
+
+            let network = self.networks.remove(&id).unwrap();
+            for pipe in &network.pipes {
+                self.pipe_networks.insert(*pipe, target);
+            }
+            self.networks.get_mut(&target).unwrap().pipes.extend(network.pipes);
+        }
+
+        self.networks.get_mut(&target).unwrap().pipes.insert(pos);
+        self.pipe_networks.insert(pos, target);
+    }
+
+    /// Removes pipes, splitting any network they held together
+    pub fn remove_pipes(&mut self, positions: impl IntoIterator<Item = GlobalBlockPosition>) {
+        let mut affected = HashSet::new();
+
+        for pos in positions {
+            let Some(id) = self.pipe_networks.remove(&pos) else {
+                continue;
+            };
+
+            let network = self.networks.get_mut(&id).unwrap();
+            network.pipes.remove(&pos);
+
+            // A pipe with one neighbour in its network can't have been holding it together
+            let neighbours = BLOCK_SIDES
+                .iter()
+                .filter(|side| network.pipes.contains(&(pos + *side)))
+                .count();
+
+            if network.pipes.is_empty() {
+                self.networks.remove(&id);
+                affected.remove(&id);
+            } else if neighbours > 1 {
+                affected.insert(id);
+            }
+        }
+
+        for id in affected {
+            self.split(id);
+        }
+    }
+
+    pub fn remove_pipe(&mut self, pos: GlobalBlockPosition) {
+        self.remove_pipes([pos]);
+    }
+
+    /// Breaks a network into its connected parts, keeping the id for the first
+    fn split(&mut self, id: NetworkId) {
+        let Some(network) = self.networks.remove(&id) else {
+            return;
+        };
+
+        let mut remaining = network.pipes;
+        let mut parts = Vec::new();
+
+        while let Some(&start) = remaining.iter().next() {
+            let part = flood(start, |pos| remaining.contains(&pos));
+            remaining.retain(|pos| !part.contains(pos));
+            parts.push(part);
+        }
+
+        let mut parts = parts.into_iter();
+        if let Some(first) = parts.next() {
+            self.networks.insert(id, PipeNetwork { pipes: first });
+        }
+
+        for part in parts {
+            self.new_network(part);
+        }
+    }
+
+    /// The shortest route through pipes from `from` to `to`, including both ends
+    pub fn path(&self, from: GlobalBlockPosition, to: GlobalBlockPosition) -> Option<Vec<GlobalBlockPosition>> {
+        let network = self.network_of(from)?;
+        if self.network_of(to)? != network {
+            return None;
+        }
+
+        let mut previous = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(pos) = queue.pop_front() {
+            if pos == to {
+                let mut path = vec![to];
+                while *path.last().unwrap() != from {
+                    path.push(previous[path.last().unwrap()]);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for side in &BLOCK_SIDES {
+                let next = pos + side;
+                if self.network_of(next) == Some(network) && !previous.contains_key(&next) {
+                    previous.insert(next, pos);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Every position connected to `start` through positions matching `include`
+fn flood(
+    start: GlobalBlockPosition,
+    include: impl Fn(GlobalBlockPosition) -> bool,
+) -> HashSet<GlobalBlockPosition> {
+    let mut found = HashSet::from([start]);
+    let mut queue = vec![start];
+
+    while let Some(pos) = queue.pop() {
+        for side in &BLOCK_SIDES {
+            let next = pos + side;
+            if include(next) && found.insert(next) {
+                queue.push(next);
+            }
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::pipes::network::PipeNetworks;
+    use nalgebra::Vector3;
+
+    fn line(networks: &mut PipeNetworks, from: i32, to: i32) {
+        for x in from..=to {
+            networks.add_pipe(Vector3::new(x, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_merge_networks() {
+        let mut networks = PipeNetworks::default();
+        line(&mut networks, 0, 3);
+        line(&mut networks, 5, 20);
+        assert_eq!(networks.len(), 2);
+
+        // Bridging the gap joins them
+        networks.add_pipe(Vector3::new(4, 0, 0));
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks.network_of(Vector3::new(0, 0, 0)), networks.network_of(Vector3::new(20, 0, 0)));
+    }
+
+    #[test]
+    fn test_split_networks() {
+        let mut networks = PipeNetworks::default();
+        line(&mut networks, -20, 20);
+
+        // Crosses chunk borders on both sides
+        networks.remove_pipe(Vector3::new(0, 0, 0));
+        assert_eq!(networks.len(), 2);
+        assert_ne!(networks.network_of(Vector3::new(-1, 0, 0)), networks.network_of(Vector3::new(1, 0, 0)));
+        assert_eq!(networks.network_of(Vector3::new(0, 0, 0)), None);
+
+        // Removing an end doesn't split anything
+        networks.remove_pipe(Vector3::new(20, 0, 0));
+        assert_eq!(networks.len(), 2);
+
+        networks.remove_pipes((-20..0).map(|x| Vector3::new(x, 0, 0)));
+        assert_eq!(networks.len(), 1);
+    }
+
+    #[test]
+    fn test_loop_doesnt_split() {
+        let mut networks = PipeNetworks::default();
+        for (x, z) in [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (1, 2), (0, 2), (0, 1)] {
+            networks.add_pipe(Vector3::new(x, 0, z));
+        }
+
+        networks.remove_pipe(Vector3::new(1, 0, 0));
+        assert_eq!(networks.len(), 1);
+    }
+
+    #[test]
+    fn test_path() {
+        let mut networks = PipeNetworks::default();
+        line(&mut networks, 0, 3);
+        networks.add_pipe(Vector3::new(3, 1, 0));
+        networks.add_pipe(Vector3::new(10, 0, 0));
+
+        let path = networks.path(Vector3::new(0, 0, 0), Vector3::new(3, 1, 0)).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path[0], Vector3::new(0, 0, 0));
+        assert_eq!(path[4], Vector3::new(3, 1, 0));
+
+        assert_eq!(networks.path(Vector3::new(0, 0, 0), Vector3::new(10, 0, 0)), None);
+    }
+}
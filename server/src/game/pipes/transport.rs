@@ -0,0 +1,163 @@
+use crate::game::pipes::network::PipeNetworks;
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::constants::UserId;
+use rc_shared::item::types::ItemStack;
+use serde::{Deserialize, Serialize};
+
+/// An item stack on its way through pipes from one inventory to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TravellingItem {
+    pub id: u64,
+    pub item: ItemStack,
+    /// The pipes the item passes through, in order
+    pub path: Vec<GlobalBlockPosition>,
+    /// The inventory next to the last pipe that the item is delivered to
+    pub destination: GlobalBlockPosition,
+    /// How many blocks along `path` the item has travelled
+    pub progress: f32,
+    /// The users told about the item, who need telling when it leaves the pipes
+    #[serde(skip)]
+    pub viewers: Vec<UserId>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ItemProgress {
+    Travelling,
+    /// The item is held before a pipe or destination in a chunk that isn't loaded, until it loads again
+    Waiting,
+    /// The item reached the end of its path
+    Arrived,
+    /// A pipe the item needed was removed, leaving it stuck at this position
+    Stranded(GlobalBlockPosition),
+}
+
+impl TravellingItem {
+    /// The pipe the item is currently in
+    pub fn position(&self) -> GlobalBlockPosition {
+        self.path[self.progress as usize]
+    }
+
+    /// Moves the item `distance` blocks along its path, as far as the chunks `is_loaded` allows
+    pub fn advance(
+        &mut self,
+        distance: f32,
+        networks: &PipeNetworks,
+        is_loaded: impl Fn(GlobalBlockPosition) -> bool,
+    ) -> ItemProgress {
+        let end = (self.path.len() - 1) as f32;
+        let target = (self.progress + distance).min(end);
+
+        // Pipes in unloaded chunks aren't in the networks, so would look removed. Wait just before them instead
+        let from = self.progress as usize;
+        let to = target.ceil() as usize;
+        if let Some(unloaded) = (from..=to).find(|i| !is_loaded(self.path[*i])) {
+            let held = unloaded.saturating_sub(1) as f32;
+            self.progress = target.min(held).max(self.progress);
+            return ItemProgress::Waiting;
+        }
+
+        self.progress = target;
+
+        // The pipe the item is in and the one it's moving into both need to still be there
+        let current = self.progress as usize;
+        let next = (self.progress.ceil() as usize).min(self.path.len() - 1);
+
+        if !networks.contains(self.path[current]) || !networks.contains(self.path[next]) {
+            return ItemProgress::Stranded(self.path[current]);
+        }
+
+        if self.progress >= end && !is_loaded(self.destination) {
+            ItemProgress::Waiting
+        } else if self.progress >= end {
+            ItemProgress::Arrived
+        } else {
+            ItemProgress::Travelling
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::pipes::network::PipeNetworks;
+    use crate::game::pipes::transport::{ItemProgress, TravellingItem};
+    use nalgebra::Vector3;
+    use rc_shared::item::types::{ItemStack, ItemType};
+
+    fn travelling_item(networks: &PipeNetworks, length: i32) -> TravellingItem {
+        TravellingItem {
+            id: 0,
+            item: ItemStack::new(
+                ItemType {
+                    identifier: String::from("mcv3::StoneItem"),
+                    name: String::from("Stone"),
+                    icon: String::from("stone"),
+                    block_definition_index: None,
+                },
+                1,
+            ),
+            path: networks.path(Vector3::new(0, 0, 0), Vector3::new(length - 1, 0, 0)).unwrap(),
+            destination: Vector3::new(length, 0, 0),
+            progress: 0.0,
+            viewers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_item_arrives() {
+        let mut networks = PipeNetworks::default();
+        for x in 0..5 {
+            networks.add_pipe(Vector3::new(x, 0, 0));
+        }
+
+        let mut item = travelling_item(&networks, 5);
+
+        assert_eq!(item.advance(1.5, &networks, |_| true), ItemProgress::Travelling);
+        assert_eq!(item.position(), Vector3::new(1, 0, 0));
+        assert_eq!(item.advance(1.5, &networks, |_| true), ItemProgress::Travelling);
+        assert_eq!(item.advance(10.0, &networks, |_| true), ItemProgress::Arrived);
+        assert_eq!(item.position(), Vector3::new(4, 0, 0));
+    }
+
+    #[test]
+    fn test_item_stranded() {
+        let mut networks = PipeNetworks::default();
+        for x in 0..5 {
+            networks.add_pipe(Vector3::new(x, 0, 0));
+        }
+
+        let mut item = travelling_item(&networks, 5);
+        assert_eq!(item.advance(1.0, &networks, |_| true), ItemProgress::Travelling);
+
+        networks.remove_pipe(Vector3::new(3, 0, 0));
+
+        assert_eq!(item.advance(1.5, &networks, |_| true), ItemProgress::Stranded(Vector3::new(2, 0, 0)));
+    }
+
+    #[test]
+    fn test_item_waits_for_unloaded_chunks() {
+        let mut networks = PipeNetworks::default();
+        for x in 0..20 {
+            networks.add_pipe(Vector3::new(x, 0, 0));
+        }
+
+        let mut item = travelling_item(&networks, 20);
+
+        // The chunk from x = 16 onwards unloads, taking its pipes out of the networks
+        networks.remove_pipes((16..20).map(|x| Vector3::new(x, 0, 0)));
+        let loaded = |loaded_to: i32| move |pos: Vector3<i32>| pos.x < loaded_to;
+
+        assert_eq!(item.advance(10.0, &networks, loaded(16)), ItemProgress::Travelling);
+        assert_eq!(item.advance(10.0, &networks, loaded(16)), ItemProgress::Waiting);
+        assert_eq!(item.position(), Vector3::new(15, 0, 0));
+        assert_eq!(item.advance(10.0, &networks, loaded(16)), ItemProgress::Waiting);
+        assert_eq!(item.position(), Vector3::new(15, 0, 0));
+
+        // Carries on once it loads again, then waits for its destination's chunk
+        for x in 16..20 {
+            networks.add_pipe(Vector3::new(x, 0, 0));
+        }
+        assert_eq!(item.advance(10.0, &networks, loaded(20)), ItemProgress::Waiting);
+        assert_eq!(item.position(), Vector3::new(19, 0, 0));
+        assert_eq!(item.advance(1.0, &networks, loaded(21)), ItemProgress::Arrived);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::game::update::BlockUpdateEvent;
+use crate::game::world::data::WorldData;
+use crate::systems::chunk::ChunkSystem;
+use bevy::prelude::{Event, EventReader, EventWriter, Res, ResMut};
+use rc_networking::protocol::clientbound::block_entity_update::BlockEntityUpdate;
+use rc_networking::protocol::Protocol;
+use rc_networking::types::SendPacket;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::helpers::global_to_local_position;
+
+/// Sends the block entity at `pos` to every client that has its chunk loaded
+#[derive(Event)]
+pub struct BlockEntityChangedEvent {
+    pub pos: GlobalBlockPosition
+}
+
+/// Gives placed blocks the entity their definition declares, and drops the entity of any block
+/// that was replaced
+pub fn update_block_entities(
+    mut world_data: ResMut<WorldData>,
+    mut update_event: EventReader<BlockUpdateEvent>,
+    mut changed_event: EventWriter<BlockEntityChangedEvent>,
+    block_states: Res<BlockStates>,
+) {
+    for event in update_event.read() {
+        let entity = block_states.get_block_from_id(event.block_id).create_entity();
+
+        let current = world_data.get_block_entity(event.pos).map(|entity| entity.identifier.as_str());
+
+        // The same kind of block entity is kept, as the block only changed state
+        if current == entity.as_ref().map(|entity| entity.identifier.as_str()) {
+            continue;
+        }
+
+        world_data.set_block_entity(event.pos, entity);
+        changed_event.send(BlockEntityChangedEvent { pos: event.pos });
+    }
+}
+
+pub fn sync_block_entities(
+    world_data: Res<WorldData>,
+    chunk_system: Res<ChunkSystem>,
+    mut changed_event: EventReader<BlockEntityChangedEvent>,
+    mut send_packet: EventWriter<SendPacket>,
+) {
+    for event in changed_event.read() {
+        let (chunk_pos, _) = global_to_local_position(event.pos);
+        let entity = world_data.get_block_entity(event.pos).cloned();
+
+        for (user, loaded_chunks) in &chunk_system.user_loaded_chunks {
+            if !loaded_chunks.contains(&chunk_pos) {
+                continue;
+            }
+
+            send_packet.send(SendPacket(
+                Protocol::BlockEntityUpdate(BlockEntityUpdate::new(
+                    event.pos.x,
+                    event.pos.y,
+                    event.pos.z,
+                    entity.clone(),
+                )),
+                *user,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::update::block_entities::{update_block_entities, BlockEntityChangedEvent};
+    use crate::game::update::BlockUpdateEvent;
+    use crate::game::world::data::WorldData;
+    use rc_shared::block::test_block_states;
+    use bevy::app::{App, Update};
+    use bevy::prelude::Events;
+    use nalgebra::Vector3;
+    use rc_shared::block::entity::InventoryEntity;
+
+    #[test]
+    fn test_entity_created_and_dropped() {
+        let block_states = test_block_states();
+        let (_, chest) = block_states.get_by_identifier("mcv3::block::Chest").unwrap();
+        let pos = Vector3::new(1, 2, 3);
+
+        let mut world = WorldData::default();
+        world.insert_chunk(ChunkData::blank(Vector3::new(0, 0, 0)));
+
+        let mut app = App::new();
+        app.insert_resource(world)
+            .insert_resource(block_states.clone())
+            .add_event::<BlockUpdateEvent>()
+            .add_event::<BlockEntityChangedEvent>()
+            .add_systems(Update, update_block_entities);
+
+        app.world_mut().send_event(BlockUpdateEvent { pos, block_id: chest.get_id() });
+        app.update();
+
+        let entity = app.world().resource::<WorldData>().get_block_entity(pos).unwrap();
+        assert_eq!(entity.get::<InventoryEntity>().unwrap().slots.len(), 27);
+        assert_eq!(app.world().resource::<Events<BlockEntityChangedEvent>>().len(), 1);
+
+        app.world_mut().send_event(BlockUpdateEvent { pos, block_id: 0 });
+        app.update();
+
+        assert!(app.world().resource::<WorldData>().get_block_entity(pos).is_none());
+    }
+}
@@ -1,12 +1,20 @@
+pub mod block_entities;
+pub mod ticks;
+
+use crate::game::update::block_entities::{sync_block_entities, update_block_entities, BlockEntityChangedEvent};
+use crate::game::update::ticks::poke_blocks;
 use crate::game::world::data::WorldData;
 
 use crate::transport::TransportSystem;
 use bevy::app::App;
-use bevy::prelude::{Event, EventReader, EventWriter, info, Plugin, Res, ResMut, Update};
+use bevy::prelude::{Event, EventReader, EventWriter, info, IntoSystemConfigs, Plugin, Res, ResMut, Update};
 use rc_networking::protocol::Protocol;
 use rc_networking::types::SendPacket;
 use rc_shared::block::BlockStates;
+use rc_shared::block::entity::InventoryEntity;
+use rc_shared::block::properties::SIDE_PROPERTIES;
 use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::light::LightUpdate;
 use rc_shared::viewable_direction::BLOCK_SIDES;
 
 /// Eventually turn this into a modular block update system
@@ -29,7 +37,10 @@ impl Plugin for BlockUpdatePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<BlockUpdateEvent>()
             .add_event::<BlockPokeEvent>()
-            .add_systems(Update, (update_block, do_pipes_temp));
+            .add_event::<BlockEntityChangedEvent>()
+            // Pipes connect to inventories, so need the entities of placed blocks created first
+            .add_systems(Update, (update_block, do_pipes_temp.after(update_block_entities), poke_blocks))
+            .add_systems(Update, (update_block_entities, sync_block_entities).chain());
     }
 }
 
@@ -40,10 +51,9 @@ fn update_block(
     block_states: Res<BlockStates>,
 ) {
     for event in update_event.read() {
-        let block_id = world_data.get_block_id(event.pos).unwrap();
-
         // Update chunk column
         world_data.update_column_pos(event.pos, event.block_id);
+        world_data.light_updates.push(LightUpdate::Block(event.pos));
     }
 }
 
@@ -55,39 +65,40 @@ fn do_pipes_temp(
     block_states: Res<BlockStates>,
 ) {
     for event in update_event.read() {
-        let block_id = world_data.get_block_id(event.pos).unwrap();
-
-        let definition_id = block_states.get_definition_index_by_id(block_id).unwrap();
-        let block_start_id = block_states.get_start_id_by_definition(definition_id).unwrap();
-
-        let block = block_states.get_block_from_id(block_id);
+        let Some(block_id) = world_data.get_block_id(event.pos) else {
+            continue;
+        };
+        let mut block = block_states.get_block_from_id(block_id);
 
         // Pipe check (TEMP)
         if block.get_identifier() != "mcv3::block::Pipe" {
             continue;
         }
 
-        // Get surrounding blocks
-        let mut val = 0;
-        for dir in 0..6 {
-            let block_id = world_data
-                .get_block_id(event.pos + BLOCK_SIDES[dir])
-                .unwrap();
-
-            if block_id >= 9 {
-                // Also include type spawner
-                val |= 0b100000 >> dir;
-            }
+        // Connect to the surrounding pipes, and the inventories they move items between
+        for (side, property) in BLOCK_SIDES.iter().zip(SIDE_PROPERTIES) {
+            let pos = event.pos + side;
+
+            // Leave sides facing unloaded chunks as they were
+            let Some(neighbour) = world_data.get_block_id(pos) else {
+                continue;
+            };
+
+            let connected = block_states.get_block_from_id(neighbour).get_identifier() == "mcv3::block::Pipe"
+                || world_data.get_block_entity(pos).is_some_and(|entity| entity.is::<InventoryEntity>());
+
+            block = block.with_property(property, connected).unwrap();
         }
 
-        world_data.set_block_id(event.pos, val + block_start_id);
+        let block_id = block.get_id();
+        world_data.set_block_id(event.pos, block_id);
 
         // Notify all clients
         for (uid, _) in &clients.clients {
             send_packet.send(SendPacket(
                 Protocol::BlockUpdate(
                     rc_networking::protocol::clientbound::block_update::BlockUpdate::new(
-                        val + 10,
+                        block_id,
                         event.pos.x,
                         event.pos.y,
                         event.pos.z,
@@ -98,3 +109,28 @@ fn do_pipes_temp(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rc_shared::block::test_block_states;
+    use rc_shared::block::properties::PropertyValue;
+
+    #[test]
+    fn test_pipe_properties() {
+        let block_states = test_block_states();
+        let (_, pipe) = block_states.get_by_identifier("mcv3::block::Pipe").unwrap();
+        let start_id = pipe.get_id();
+
+        let pipe = pipe.with_property("up", true).unwrap();
+        assert_eq!(pipe.get_property("up"), Some(PropertyValue::Bool(true)));
+        assert_eq!(pipe.get_property("north"), Some(PropertyValue::Bool(false)));
+
+        // The sides keep the bit layout pipe networks read, with up as the highest bit
+        assert_eq!(pipe.get_id(), start_id + 0b100000);
+        let pipe = pipe.with_property("south", true).unwrap();
+        assert_eq!(pipe.get_id(), start_id + 0b100001);
+        assert_eq!(block_states.get_block_from_id(pipe.get_id()).get_property("south"), Some(PropertyValue::Bool(true)));
+
+        assert!(pipe.with_property("level", 1).is_none());
+    }
+}
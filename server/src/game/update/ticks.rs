@@ -0,0 +1,369 @@
+use crate::game::update::{BlockPokeEvent, BlockUpdateEvent};
+use crate::game::world::data::WorldData;
+use crate::systems::chunk::ChunkSystem;
+use crate::systems::connection::message::{calculate_drops, item_drop_request};
+use crate::systems::game_object::spawn::SpawnGameObjectRequest;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{EventReader, EventWriter, Res, ResMut};
+use nalgebra::Vector3;
+use rand::rngs::ThreadRng;
+use rand::RngCore;
+use rc_networking::protocol::clientbound::block_update::BlockUpdate;
+use rc_networking::protocol::Protocol;
+use rc_networking::types::SendPacket;
+use rc_shared::block::tick::BlockTickContext;
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::helpers::global_to_local_position;
+use rc_shared::item::ItemStates;
+use rc_shared::light::LightLevel;
+use rc_shared::viewable_direction::BLOCK_SIDES;
+use serde::{Deserialize, Serialize};
+
+/// A block tick waiting to fire, saved with the chunk it's in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledTick {
+    /// The position of the block within its chunk
+    pub pos: Vector3<u8>,
+    /// The identifier of the block the tick is for. The tick is dropped if the block has changed
+    /// into something else by the time it fires.
+    pub block: String,
+    /// Server ticks left until the tick fires
+    pub delay: u64,
+}
+
+/// The world as blocks see it while ticking. Block changes are written straight to the world, and
+/// collected so clients and neighbouring blocks can be told about them afterwards.
+pub struct TickWorld<'a> {
+    world_data: &'a mut WorldData,
+    block_states: &'a BlockStates,
+    rng: ThreadRng,
+    changes: TickChanges,
+}
+
+/// The blocks changed while ticking
+#[derive(Default)]
+pub struct TickChanges {
+    /// Blocks that were replaced, with the id they were replaced with
+    pub set: Vec<(GlobalBlockPosition, BlockId)>,
+    /// Blocks that were destroyed, with the id they had, so their loot can be dropped
+    pub destroyed: Vec<(GlobalBlockPosition, BlockId)>,
+}
+
+impl<'a> TickWorld<'a> {
+    pub fn new(world_data: &'a mut WorldData, block_states: &'a BlockStates) -> TickWorld<'a> {
+        TickWorld {
+            world_data,
+            block_states,
+            rng: rand::thread_rng(),
+            changes: TickChanges::default(),
+        }
+    }
+
+    pub fn world_data(&self) -> &WorldData {
+        self.world_data
+    }
+
+    pub fn finish(self) -> TickChanges {
+        self.changes
+    }
+}
+
+impl BlockTickContext for TickWorld<'_> {
+    fn block_states(&self) -> &BlockStates {
+        self.block_states
+    }
+
+    fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<BlockId> {
+        self.world_data.get_block_id(pos)
+    }
+
+    fn set_block(&mut self, pos: GlobalBlockPosition, block_id: BlockId) {
+        if self.world_data.set_block_id(pos, block_id).is_some() {
+            self.changes.set.push((pos, block_id));
+        }
+    }
+
+    fn destroy_block(&mut self, pos: GlobalBlockPosition) {
+        let Some(block_id) = self.world_data.get_block_id(pos) else {
+            return;
+        };
+
+        self.world_data.set_block_id(pos, 0);
+        self.changes.destroyed.push((pos, block_id));
+    }
+
+    fn schedule_tick(&mut self, pos: GlobalBlockPosition, delay: u64) {
+        let Some(block) = self.get_block(pos) else {
+            return;
+        };
+
+        self.world_data.schedule_tick(pos, block.get_identifier(), delay);
+    }
+
+    fn rng(&mut self) -> &mut dyn RngCore {
+        &mut self.rng
+    }
+
+    fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel> {
+        self.world_data.get_light(pos)
+    }
+}
+
+/// Tells clients and neighbouring blocks about the blocks changed by ticking, and drops the loot of
+/// destroyed blocks
+#[derive(SystemParam)]
+pub struct TickChangeWriters<'w> {
+    update_event: EventWriter<'w, BlockUpdateEvent>,
+    poke_event: EventWriter<'w, BlockPokeEvent>,
+    send_packet: EventWriter<'w, SendPacket>,
+    spawn_event: EventWriter<'w, SpawnGameObjectRequest>,
+    chunk_system: Res<'w, ChunkSystem>,
+    item_states: Res<'w, ItemStates>,
+}
+
+impl TickChangeWriters<'_> {
+    pub fn apply(&mut self, changes: TickChanges, block_states: &BlockStates) {
+        for (pos, block_id) in &changes.destroyed {
+            for drop in calculate_drops(block_states, &self.item_states, *block_id) {
+                self.spawn_event.send(item_drop_request(drop, *pos));
+            }
+        }
+
+        let destroyed = changes.destroyed.into_iter().map(|(pos, _)| (pos, 0));
+
+        for (pos, block_id) in changes.set.into_iter().chain(destroyed) {
+            self.update_event.send(BlockUpdateEvent { pos, block_id });
+            for side in &BLOCK_SIDES {
+                self.poke_event.send(BlockPokeEvent { pos: pos + side });
+            }
+
+            let (chunk_pos, _) = global_to_local_position(pos);
+            for (user, loaded_chunks) in &self.chunk_system.user_loaded_chunks {
+                if loaded_chunks.contains(&chunk_pos) {
+                    self.send_packet.send(SendPacket(
+                        Protocol::BlockUpdate(BlockUpdate::new(block_id, pos.x, pos.y, pos.z)),
+                        *user,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Lets blocks react to the blocks around them changing
+pub fn poke_blocks(
+    mut world_data: ResMut<WorldData>,
+    mut poke_event: EventReader<BlockPokeEvent>,
+    mut writers: TickChangeWriters,
+    block_states: Res<BlockStates>,
+) {
+    if poke_event.is_empty() {
+        return;
+    }
+
+    let mut world = TickWorld::new(&mut world_data, &block_states);
+
+    for event in poke_event.read() {
+        if let Some(block) = world.get_block(event.pos) {
+            block.poke(event.pos, &mut world);
+        }
+    }
+
+    writers.apply(world.finish(), &block_states);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::update::ticks::TickWorld;
+    use crate::game::world::data::WorldData;
+    use rc_shared::block::test_block_states;
+    use nalgebra::{Vector2, Vector3};
+    use rc_shared::block::tick::BlockTickContext;
+    use rc_shared::chunk::ChunkDataStorage;
+    use rc_shared::viewable_direction::BLOCK_SIDES;
+
+    fn world() -> WorldData {
+        let mut world = WorldData::default();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let mut chunk = ChunkData::blank(Vector3::new(x, 0, z));
+                chunk.world = ChunkDataStorage::Data(Box::new([[[0; 16]; 16]; 16]));
+                world.insert_chunk(chunk);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn test_scheduled_ticks_count_down() {
+        let block_states = test_block_states();
+        let mut world_data = world();
+        let (_, leaves) = block_states.get_by_identifier("mcv3::block::Leaves").unwrap();
+        let pos = Vector3::new(3, 4, 5);
+
+        world_data.set_block_id(pos, leaves.get_id());
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        world.schedule_tick(pos, 2);
+        // A second tick for the same block is dropped in favour of the first
+        world.schedule_tick(pos, 10);
+        world_data.unsaved_chunks.clear();
+
+        // Counting down alone doesn't need the chunk saving
+        assert!(world_data.take_due_ticks().is_empty());
+        assert!(world_data.unsaved_chunks.is_empty());
+
+        assert_eq!(world_data.take_due_ticks(), vec![(pos, String::from("mcv3::block::Leaves"))]);
+        assert!(world_data.unsaved_chunks.contains(&Vector3::new(0, 0, 0)));
+        assert!(world_data.take_due_ticks().is_empty());
+    }
+
+    #[test]
+    fn test_leaves_decay_without_logs() {
+        let block_states = test_block_states();
+        let mut world_data = world();
+        let (_, leaves) = block_states.get_by_identifier("mcv3::block::Leaves").unwrap();
+        let (_, log) = block_states.get_by_identifier("mcv3::block::WoodLog").unwrap();
+
+        // Held up by a log two leaves away
+        world_data.set_block_id(Vector3::new(0, 5, 0), log.get_id());
+        world_data.set_block_id(Vector3::new(1, 5, 0), leaves.get_id());
+        world_data.set_block_id(Vector3::new(2, 5, 0), leaves.get_id());
+
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        leaves.scheduled_tick(Vector3::new(2, 5, 0), &mut world);
+        assert!(world.finish().destroyed.is_empty());
+
+        world_data.set_block_id(Vector3::new(0, 5, 0), 0);
+
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        leaves.scheduled_tick(Vector3::new(2, 5, 0), &mut world);
+        let changes = world.finish();
+
+        assert_eq!(changes.destroyed, vec![(Vector3::new(2, 5, 0), leaves.get_id())]);
+        assert_eq!(world_data.get_block_id(Vector3::new(2, 5, 0)), Some(0));
+    }
+
+    #[test]
+    fn test_covered_grass_dies() {
+        let block_states = test_block_states();
+        let mut world_data = world();
+        let (_, grass) = block_states.get_by_identifier("mcv3::block::Grass").unwrap();
+        let (_, dirt) = block_states.get_by_identifier("mcv3::block::Dirt").unwrap();
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+        let pos = Vector3::new(-4, 2, 7);
+
+        world_data.set_block_id(pos, grass.get_id());
+        world_data.set_block_id(pos + Vector3::new(0, 1, 0), stone.get_id());
+
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        grass.random_tick(pos, &mut world);
+
+        assert_eq!(world.finish().set, vec![(pos, dirt.get_id())]);
+    }
+
+    #[test]
+    fn test_sapling_grows() {
+        let block_states = test_block_states();
+        let mut world_data = world();
+        let (_, sapling) = block_states.get_by_identifier("mcv3::block::Sapling").unwrap();
+        let (_, log) = block_states.get_by_identifier("mcv3::block::WoodLog").unwrap();
+        let pos = Vector3::new(8, 1, 8);
+
+        world_data.set_block_id(pos, sapling.get_id());
+
+        // Saplings only grow in the light
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        sapling.random_tick(pos, &mut world);
+        assert!(world.finish().set.is_empty());
+
+        world_data.update_column(Vector2::new(0, 0));
+        world_data.update_light(block_states);
+
+        // The first tick only moves it to the next stage
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        sapling.random_tick(pos, &mut world);
+        world.finish();
+
+        let grown = block_states.get_block_from_id(world_data.get_block_id(pos).unwrap());
+        assert_eq!(grown.get_identifier(), "mcv3::block::Sapling");
+        assert_ne!(grown.get_id(), sapling.get_id());
+
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        grown.random_tick(pos, &mut world);
+        world.finish();
+
+        assert_eq!(world_data.get_block_id(pos), Some(log.get_id()));
+        assert_eq!(world_data.get_block_id(pos + Vector3::new(0, 3, 0)), Some(log.get_id()));
+    }
+
+    #[test]
+    fn test_water_flows_and_dries_up() {
+        let block_states = test_block_states();
+        let mut world_data = world();
+        let (_, water) = block_states.get_by_identifier("mcv3::block::Water").unwrap();
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+        let source = Vector3::new(0, 2, 0);
+
+        for x in -10..=10 {
+            for z in -10..=10 {
+                world_data.set_block_id(Vector3::new(x, 1, z), stone.get_id());
+            }
+        }
+        world_data.set_block_id(source, water.get_id());
+
+        let run_ticks = |world_data: &mut WorldData| {
+            let mut world = TickWorld::new(world_data, block_states);
+            world.schedule_tick(source, 1);
+            drop(world);
+
+            for _ in 0..1000 {
+                for (pos, _) in world_data.take_due_ticks() {
+                    let mut world = TickWorld::new(world_data, block_states);
+                    if let Some(block) = world.get_block(pos) {
+                        block.scheduled_tick(pos, &mut world);
+                    }
+
+                    // Poke the neighbours of changed blocks, as the server does
+                    for (changed, _) in world.finish().set {
+                        for side in &BLOCK_SIDES {
+                            let mut world = TickWorld::new(world_data, block_states);
+                            if let Some(block) = world.get_block(changed + side) {
+                                block.poke(changed + side, &mut world);
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        run_ticks(&mut world_data);
+
+        let is_water = |world_data: &WorldData, pos: Vector3<i32>| {
+            world_data
+                .get_block_id(pos)
+                .is_some_and(|id| block_states.get_block_from_id(id).get_identifier() == "mcv3::block::Water")
+        };
+
+        assert!(is_water(&world_data, Vector3::new(7, 2, 0)));
+        assert!(is_water(&world_data, Vector3::new(0, 2, -7)));
+        assert!(!is_water(&world_data, Vector3::new(8, 2, 0)));
+        assert!(!is_water(&world_data, source + Vector3::new(0, 1, 0)));
+
+        // Flowing water is shallower than its source
+        let flowing = world_data.get_block_id(Vector3::new(3, 2, 0)).unwrap();
+        assert_ne!(flowing, water.get_id());
+
+        world_data.set_block_id(source, 0);
+        let mut world = TickWorld::new(&mut world_data, block_states);
+        for side in &BLOCK_SIDES[2..] {
+            world.schedule_tick(source + side, 1);
+        }
+        drop(world);
+        run_ticks(&mut world_data);
+
+        assert!(!is_water(&world_data, Vector3::new(1, 2, 0)));
+        assert!(!is_water(&world_data, Vector3::new(5, 2, 0)));
+    }
+}
@@ -7,7 +7,8 @@ use rc_shared::helpers::global_to_local_position;
 use crate::game::world::data::WorldData;
 
 
-const CHUNK_CHECKING_RANGE: [i32; 21] = [10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, -1, -2, -3, -4, -5, -6, -7, -8, -9, -10]; // (-10..10).rev()
+/// The chunk heights searched from the top down for the surface of a column
+pub const CHUNK_CHECKING_RANGE: [i32; 21] = [10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, -1, -2, -3, -4, -5, -6, -7, -8, -9, -10]; // (-10..10).rev()
 
 impl WorldData {
     // Loops over chunks in a column and populates the data for its ChunkColumnData
@@ -1,22 +1,23 @@
 use crate::game::chunk::ChunkData;
 
 use crate::error::ServerError;
+use crate::game::world::region;
 use crate::game::world::serialized::DeserializedChunkData;
-use rc_shared::helpers::global_to_local_position;
+use crate::game::update::ticks::ScheduledTick;
+use rc_shared::helpers::{global_to_local_position, local_to_global_position};
 use bevy::ecs::entity::Entity;
 use bevy::ecs::prelude::Resource;
 
 use nalgebra::{Vector2, Vector3};
 use rc_shared::constants::GameObjectId;
 
-use std::collections::HashMap;
-use std::fs;
-use std::fs::File;
-use std::io::BufReader;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::AtomicU64;
-use rc_shared::block::BlockId;
-use rc_shared::chunk::{ChunkColumnPosition, ChunkPosition, GlobalBlockPosition};
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::block::entity::BlockEntityData;
+use rc_shared::chunk::{ChunkColumnPosition, ChunkDataStorage, ChunkPosition, ChunkSystemTrait, GlobalBlockPosition};
 use rc_shared::chunk_column::ChunkColumnData;
+use rc_shared::light::LightUpdate;
 
 pub static GAME_OBJECT_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
 
@@ -26,6 +27,10 @@ pub struct WorldData {
     pub chunks_columns: HashMap<ChunkColumnPosition, ChunkColumnData>,
     pub game_objects_mapping: HashMap<GameObjectId, Entity>,
     pub game_objects_chunks: HashMap<ChunkPosition, HashMap<GameObjectId, Entity>>,
+    /// Chunks that have changed since they were last written to disk
+    pub unsaved_chunks: HashSet<ChunkPosition>,
+    /// Changes to the world that it hasn't been relit for yet
+    pub light_updates: Vec<LightUpdate>,
 }
 
 impl Default for WorldData {
@@ -35,6 +40,8 @@ impl Default for WorldData {
             chunks_columns: Default::default(),
             game_objects_mapping: Default::default(),
             game_objects_chunks: Default::default(),
+            unsaved_chunks: Default::default(),
+            light_updates: Default::default(),
         }
     }
 }
@@ -45,6 +52,7 @@ impl WorldData {
         chunk: ChunkData
     ) {
         let column = Vector2::new(chunk.position.x, chunk.position.z);
+        self.light_updates.push(LightUpdate::Chunk(chunk.position));
         self.chunks.insert(chunk.position, chunk);
         self.update_column(column);
     }
@@ -63,6 +71,7 @@ impl WorldData {
             .get_mut(&chunk_pos)
             .unwrap()
             .insert(game_object_id, entity.clone());
+        self.unsaved_chunks.insert(chunk_pos);
 
         self.game_objects_mapping.insert(game_object_id, entity)
     }
@@ -79,10 +88,32 @@ impl WorldData {
         self.game_objects_chunks
             .get_mut(&chunk_pos)
             .and_then(|v| v.remove(&game_object_id));
+        self.unsaved_chunks.insert(chunk_pos);
 
         self.game_objects_mapping.remove(&game_object_id)
     }
 
+    /// Moves a game object into the chunk it has moved to, so both chunks are saved without or with it
+    pub fn move_game_object(
+        &mut self,
+        game_object_id: &GameObjectId,
+        from: Vector3<i32>,
+        to: Vector3<i32>,
+    ) {
+        if from == to {
+            return;
+        }
+
+        if let Some(entity) = self.remove_game_object(game_object_id, from) {
+            self.insert_game_object(*game_object_id, entity, to);
+        }
+    }
+
+    /// The id following the highest id of the loaded game objects
+    pub fn next_game_object_id(&self) -> u64 {
+        self.game_objects_mapping.keys().map(|id| id.0 + 1).max().unwrap_or(0)
+    }
+
     pub fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<u32> {
         let (chunk_pos, local_pos) = global_to_local_position(pos);
 
@@ -96,29 +127,118 @@ impl WorldData {
 
         if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
             chunk.world.set(local_pos, block_id);
+            self.unsaved_chunks.insert(chunk_pos);
             Some(())
         } else {
             None
         }
     }
 
+    pub fn get_block_entity(&self, pos: GlobalBlockPosition) -> Option<&BlockEntityData> {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        self.chunks.get(&chunk_pos)?.block_entities.get(local_pos)
+    }
+
+    /// Send a `BlockEntityChangedEvent` after changing the entity so clients see the change
+    pub fn get_block_entity_mut(&mut self, pos: GlobalBlockPosition) -> Option<&mut BlockEntityData> {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        let entity = self.chunks.get_mut(&chunk_pos)?.block_entities.get_mut(local_pos)?;
+        self.unsaved_chunks.insert(chunk_pos);
+
+        Some(entity)
+    }
+
+    /// Sets or removes the block entity at `pos`, returning the one it replaced. Send a
+    /// `BlockEntityChangedEvent` afterwards so clients see the change.
+    pub fn set_block_entity(
+        &mut self,
+        pos: GlobalBlockPosition,
+        entity: Option<BlockEntityData>,
+    ) -> Option<BlockEntityData> {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        let chunk = self.chunks.get_mut(&chunk_pos)?;
+        self.unsaved_chunks.insert(chunk_pos);
+
+        chunk.block_entities.set(local_pos, entity)
+    }
+
+    /// Ticks the block at `pos` after `delay` server ticks, if it's still `block` by then. A block
+    /// only has one tick scheduled at a time, so scheduling again before it fires does nothing.
+    pub fn schedule_tick(&mut self, pos: GlobalBlockPosition, block: &str, delay: u64) {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+        let local_pos = local_pos.cast::<u8>();
+
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+            return;
+        };
+
+        if chunk.scheduled_ticks.iter().any(|tick| tick.pos == local_pos && tick.block == block) {
+            return;
+        }
+
+        chunk.scheduled_ticks.push(ScheduledTick {
+            pos: local_pos,
+            block: block.to_string(),
+            delay: delay.max(1),
+        });
+        self.unsaved_chunks.insert(chunk_pos);
+    }
+
+    /// Counts every scheduled tick down by one, removing and returning the ticks that are due.
+    /// Chunks are only saved again once one of their ticks fires, so a tick may wait a little longer than
+    /// scheduled if the server stops before then
+    pub fn take_due_ticks(&mut self) -> Vec<(GlobalBlockPosition, String)> {
+        let mut due = Vec::new();
+
+        for (position, chunk) in &mut self.chunks {
+            if chunk.scheduled_ticks.is_empty() {
+                continue;
+            }
+
+            let waiting = chunk.scheduled_ticks.len();
+
+            chunk.scheduled_ticks.retain_mut(|tick| {
+                tick.delay -= 1;
+                if tick.delay > 0 {
+                    return true;
+                }
+
+                due.push((local_to_global_position(*position, tick.pos.cast()), tick.block.clone()));
+                false
+            });
+
+            if chunk.scheduled_ticks.len() != waiting {
+                self.unsaved_chunks.insert(*position);
+            }
+        }
+
+        due
+    }
+
+    /// Reads a saved chunk, translating its blocks into the current block ids
     pub fn try_load_chunk(
         location: ChunkPosition,
+        block_states: &BlockStates,
     ) -> Result<Option<DeserializedChunkData>, ServerError> {
-        let path = format!(
-            "./world/chunks/{:08x}{:08x}{:08x}.chunk",
-            location.x, location.y, location.z
-        );
-        if !fs::exists(&path)? {
-            return Ok(None);
+        let mut chunk = region::read_chunk(location)?;
+
+        if let Some(chunk) = &mut chunk {
+            chunk.remap_blocks(block_states);
         }
 
-        let file = File::open(&path)?;
-        let mut reader = BufReader::new(file);
+        Ok(chunk)
+    }
+}
 
-        let chunk =
-            serde_json::from_reader::<&mut BufReader<File>, DeserializedChunkData>(&mut reader)?;
+impl ChunkSystemTrait for WorldData {
+    fn get_raw_chunk(&self, pos: &ChunkPosition) -> Option<&ChunkDataStorage> {
+        self.chunks.get(pos).map(|chunk| &chunk.world)
+    }
 
-        Ok(Some(chunk))
+    fn get_raw_chunk_mut(&mut self, pos: &ChunkPosition) -> Option<&mut ChunkDataStorage> {
+        self.chunks.get_mut(pos).map(|chunk| &mut chunk.world)
     }
 }
@@ -1,10 +1,62 @@
 use nalgebra::{Quaternion, Vector3};
 use serde::{Deserialize, Serialize};
+use std::fs;
+use rc_shared::constants::UserId;
+use crate::error::ServerError;
 use crate::game::inventory::Inventory;
+use crate::game::world::migration::PLAYER_MIGRATIONS;
+use crate::helpers::write_atomic;
+use serde_json::Value;
+
+pub const PLAYER_DIRECTORY: &str = "./world/players";
 
 #[derive(Serialize, Deserialize)]
 pub struct DeserializedPlayerData {
+    /// The format version the player was read from. Players are always written in the current version.
+    pub version: u32,
     pub position: Vector3<f32>,
     pub rotation: Quaternion<f32>,
     pub inventory: Inventory
-}
\ No newline at end of file
+}
+
+impl DeserializedPlayerData {
+    pub fn new(position: Vector3<f32>, rotation: Quaternion<f32>, inventory: Inventory) -> DeserializedPlayerData {
+        DeserializedPlayerData {
+            version: PLAYER_MIGRATIONS.current_version(),
+            position,
+            rotation,
+            inventory,
+        }
+    }
+
+    /// Reads a player's saved data, upgrading it from older formats
+    pub fn load(user_id: UserId) -> Result<Option<DeserializedPlayerData>, ServerError> {
+        let path = format!("{}/{}", PLAYER_DIRECTORY, user_id.0);
+
+        if !fs::exists(&path)? {
+            return Ok(None);
+        }
+
+        let data = serde_json::from_str::<Value>(&fs::read_to_string(&path)?)?;
+
+        PLAYER_MIGRATIONS.load(data).map(Some)
+    }
+
+    /// Moves a player file that couldn't be read out of the way, so it isn't overwritten when the player is next saved
+    pub fn set_aside(user_id: UserId) -> Result<(), ServerError> {
+        let path = format!("{}/{}", PLAYER_DIRECTORY, user_id.0);
+        fs::rename(&path, format!("{}.corrupt", path))?;
+        Ok(())
+    }
+
+    pub fn save(&self, user_id: UserId) -> Result<(), ServerError> {
+        fs::create_dir_all(PLAYER_DIRECTORY)?;
+
+        write_atomic(
+            format!("{}/{}", PLAYER_DIRECTORY, user_id.0),
+            serde_json::to_string(self)?,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::config::ServerConfig;
+use crate::error::ServerError;
+use crate::helpers::write_atomic;
+use crate::game::world::region::has_saved_chunks;
+use bevy::log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub const LEVEL_PATH: &str = "./world/level";
+
+/// The seed every world was generated with before it could be chosen
+pub const LEGACY_SEED: u32 = 0;
+
+/// Settings fixed when a world is created, so the world generates the same way every time it is loaded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LevelData {
+    pub seed: u32
+}
+
+impl LevelData {
+    /// Reads the level file of the current world, or creates one using the seed from `config`.
+    /// Worlds saved before level files existed were all generated with the same seed, so keep using it for them
+    pub fn load_or_create(config: &ServerConfig) -> Result<LevelData, ServerError> {
+        if fs::exists(LEVEL_PATH)? {
+            let data = fs::read_to_string(LEVEL_PATH)?;
+            return Ok(serde_json::from_str(&data)?);
+        }
+
+        let seed = if has_saved_chunks()? {
+            info!("World has no level file, using the seed it was created with");
+            LEGACY_SEED
+        } else {
+            config.seed.unwrap_or_else(rand::random)
+        };
+
+        let level = LevelData { seed };
+
+        info!("Creating world with seed {}", level.seed);
+
+        if config.save_world {
+            if let Err(err) = level.save() {
+                error!("Error writing level data: {:?}", err);
+            }
+        }
+
+        Ok(level)
+    }
+
+    pub fn save(&self) -> Result<(), ServerError> {
+        fs::create_dir_all("./world")?;
+        write_atomic(LEVEL_PATH, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use crate::game::world::data::WorldData;
+use bevy::prelude::{Res, ResMut};
+use nalgebra::{Vector2, Vector3};
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::helpers::global_to_local_position;
+use rc_shared::light::{update_light, LightLevel, LightWorld};
+use std::mem;
+
+impl LightWorld for WorldData {
+    fn get_block_id(&self, pos: GlobalBlockPosition) -> Option<BlockId> {
+        WorldData::get_block_id(self, pos)
+    }
+
+    fn sky_height(&self, x: i32, z: i32) -> Option<i32> {
+        let (chunk_pos, local_pos) = global_to_local_position(Vector3::new(x, 0, z));
+
+        self.chunks_columns
+            .get(&Vector2::new(chunk_pos.x, chunk_pos.z))?
+            .skylight_level[local_pos.x][local_pos.z]
+    }
+
+    fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel> {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        self.chunks.get(&chunk_pos).map(|chunk| chunk.light.get(local_pos))
+    }
+
+    fn set_light(&mut self, pos: GlobalBlockPosition, light: LightLevel) {
+        let (chunk_pos, local_pos) = global_to_local_position(pos);
+
+        if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+            chunk.light.set(local_pos, light);
+        }
+    }
+}
+
+impl WorldData {
+    /// The light at `pos`, or None if its chunk isn't loaded
+    pub fn get_light(&self, pos: GlobalBlockPosition) -> Option<LightLevel> {
+        LightWorld::get_light(self, pos)
+    }
+
+    /// Relights the world around everything that changed since it was last lit
+    pub fn update_light(&mut self, block_states: &BlockStates) {
+        if self.light_updates.is_empty() {
+            return;
+        }
+
+        let updates = mem::take(&mut self.light_updates);
+        update_light(self, block_states, &updates);
+    }
+}
+
+pub fn light_world(mut world_data: ResMut<WorldData>, block_states: Res<BlockStates>) {
+    world_data.update_light(&block_states);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::world::data::WorldData;
+    use rc_shared::block::test_block_states;
+    use nalgebra::Vector3;
+    use rc_shared::block::BlockStates;
+    use rc_shared::chunk::{ChunkDataStorage, GlobalBlockPosition};
+    use rc_shared::light::{LightLevel, LightUpdate, LightWorld, MAX_SKYLIGHT, SKY_CHANNEL};
+
+    /// A floor of stone at y = 0, with air above it
+    fn world(block_states: &BlockStates) -> WorldData {
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+
+        let mut world = WorldData::default();
+        for x in -1..=1 {
+            for y in 0..=1 {
+                for z in -1..=1 {
+                    let mut data = [[[0; 16]; 16]; 16];
+                    if y == 0 {
+                        for column in data.iter_mut() {
+                            column[0] = [stone.get_id(); 16];
+                        }
+                    }
+
+                    let mut chunk = ChunkData::blank(Vector3::new(x, y, z));
+                    chunk.world = ChunkDataStorage::Data(Box::new(data));
+                    world.insert_chunk(chunk);
+                }
+            }
+        }
+
+        world.update_light(block_states);
+        world
+    }
+
+    fn set_block(world: &mut WorldData, block_states: &BlockStates, pos: GlobalBlockPosition, block_id: u32) {
+        world.set_block_id(pos, block_id);
+        world.update_column_pos(pos, block_id);
+        world.light_updates.push(LightUpdate::Block(pos));
+        world.update_light(block_states);
+    }
+
+    fn block_light(world: &WorldData, pos: GlobalBlockPosition) -> u8 {
+        world.get_light(pos).unwrap().0[0]
+    }
+
+    fn skylight(world: &WorldData, pos: GlobalBlockPosition) -> u8 {
+        world.get_light(pos).unwrap().0[SKY_CHANNEL]
+    }
+
+    #[test]
+    fn test_lamp_lights_and_unlights() {
+        let block_states = test_block_states();
+        let mut world = world(block_states);
+        let (_, lamp) = block_states.get_by_identifier("mcv3::block::Lamp").unwrap();
+        let pos = Vector3::new(14, 3, 14);
+
+        set_block(&mut world, block_states, pos, lamp.get_id());
+        let strength = lamp.draw().emission[3];
+
+        assert_eq!(block_light(&world, pos + Vector3::new(1, 0, 0)), strength - 1);
+        // Across a chunk border
+        assert_eq!(block_light(&world, pos + Vector3::new(5, 0, 0)), strength - 5);
+        assert_eq!(block_light(&world, pos + Vector3::new(2, 2, -3)), strength - 7);
+        // Stone isn't lit
+        assert_eq!(block_light(&world, Vector3::new(14, 0, 14)), 0);
+
+        set_block(&mut world, block_states, pos, 0);
+
+        assert_eq!(block_light(&world, pos), 0);
+        assert_eq!(block_light(&world, pos + Vector3::new(5, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_light_goes_around_walls() {
+        let block_states = test_block_states();
+        let mut world = world(block_states);
+        let (_, lamp) = block_states.get_by_identifier("mcv3::block::Lamp").unwrap();
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+        let strength = lamp.draw().emission[3];
+
+        set_block(&mut world, block_states, Vector3::new(0, 1, 0), lamp.get_id());
+        assert_eq!(block_light(&world, Vector3::new(2, 1, 0)), strength - 2);
+
+        // Light has to go over the wall once it's built
+        set_block(&mut world, block_states, Vector3::new(1, 1, 0), stone.get_id());
+        set_block(&mut world, block_states, Vector3::new(1, 2, 0), stone.get_id());
+        assert_eq!(block_light(&world, Vector3::new(2, 1, 0)), strength - 4);
+
+        set_block(&mut world, block_states, Vector3::new(1, 2, 0), 0);
+        assert_eq!(block_light(&world, Vector3::new(2, 1, 0)), strength - 4);
+        set_block(&mut world, block_states, Vector3::new(1, 1, 0), 0);
+        assert_eq!(block_light(&world, Vector3::new(2, 1, 0)), strength - 2);
+    }
+
+    #[test]
+    fn test_skylight_under_roof() {
+        let block_states = test_block_states();
+        let mut world = world(block_states);
+        let (_, stone) = block_states.get_by_identifier("mcv3::block::Stone").unwrap();
+        let below = Vector3::new(5, 4, 5);
+
+        assert_eq!(skylight(&world, below), MAX_SKYLIGHT);
+
+        for x in 0..=10 {
+            for z in 0..=10 {
+                set_block(&mut world, block_states, Vector3::new(x, 10, z), stone.get_id());
+            }
+        }
+
+        // Light only reaches under the middle of the roof from its sides
+        assert_eq!(skylight(&world, below), MAX_SKYLIGHT - 6);
+        assert_eq!(skylight(&world, Vector3::new(5, 11, 5)), MAX_SKYLIGHT);
+
+        set_block(&mut world, block_states, Vector3::new(5, 10, 5), 0);
+        assert_eq!(skylight(&world, below), MAX_SKYLIGHT);
+        assert_eq!(skylight(&world, Vector3::new(6, 4, 5)), MAX_SKYLIGHT - 1);
+    }
+
+    #[test]
+    fn test_overlapping_lights() {
+        let block_states = test_block_states();
+        let mut world = world(block_states);
+        let (_, lamp) = block_states.get_by_identifier("mcv3::block::Lamp").unwrap();
+        let strength = lamp.draw().emission[3];
+        let kept = Vector3::new(8, 3, 4);
+
+        // Breaking one of two lamps leaves the other's light where they overlapped
+        set_block(&mut world, block_states, Vector3::new(4, 3, 4), lamp.get_id());
+        set_block(&mut world, block_states, kept, lamp.get_id());
+        set_block(&mut world, block_states, Vector3::new(4, 3, 4), 0);
+
+        assert_eq!(block_light(&world, kept), strength);
+        assert_eq!(block_light(&world, Vector3::new(4, 3, 4)), strength - 4);
+        assert_eq!(block_light(&world, Vector3::new(6, 3, 4)), strength - 2);
+
+        // Light brighter than the lamp gives off, as from a brighter source, drowns it out. Once that light is
+        // removed the lamp has to light itself again, as nothing around it is lit to spread light back in
+        let bright = Vector3::new(8, 3, 7);
+        for x in 0..=16 {
+            for y in 1..=10 {
+                for z in 0..=16 {
+                    let pos = Vector3::new(x, y, z);
+                    let level = 30 - (pos - bright).abs().sum() as u8;
+                    if block_light(&world, pos) < level {
+                        let LightLevel([_, g, b, sky]) = world.get_light(pos).unwrap();
+                        world.set_light(pos, LightLevel([level, g, b, sky]));
+                    }
+                }
+            }
+        }
+
+        world.light_updates.push(LightUpdate::Block(bright));
+        world.update_light(block_states);
+
+        assert_eq!(block_light(&world, kept), strength);
+        assert_eq!(block_light(&world, kept + Vector3::new(0, 0, 3)), strength - 3);
+        assert_eq!(block_light(&world, Vector3::new(4, 3, 4)), strength - 4);
+    }
+}
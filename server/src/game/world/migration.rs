@@ -0,0 +1,171 @@
+use crate::error::ServerError;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Upgrades a save from one format version to the next. Steps work on the save's fields rather than
+/// its type, so old formats don't need their own types kept around.
+pub type MigrationStep = fn(&mut Value) -> Result<(), ServerError>;
+
+/// Every upgrade step of a save format, in order, where `steps[n]` upgrades version `n` to `n + 1`.
+/// Changing a save format means adding a step here, which bumps the version new saves are written with.
+pub struct Migrations {
+    pub name: &'static str,
+    pub steps: &'static [MigrationStep],
+}
+
+impl Migrations {
+    /// The version saves are written in
+    pub const fn current_version(&self) -> u32 {
+        self.steps.len() as u32
+    }
+
+    /// Upgrades `value` from `version` to the current version
+    pub fn migrate(&self, value: &mut Value, version: u32) -> Result<(), ServerError> {
+        if version > self.current_version() {
+            return Err(ServerError::UnsupportedVersion {
+                format: self.name,
+                version,
+                supported: self.current_version(),
+            });
+        }
+
+        for step in &self.steps[version as usize..] {
+            step(value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a save of any version up to the current one. The `version` field of the result is left
+    /// as the version that was read, so callers can tell the save needs rewriting.
+    pub fn load<T: DeserializeOwned>(&self, mut value: Value) -> Result<T, ServerError> {
+        let version = read_version(&value);
+
+        if version != self.current_version() {
+            self.migrate(&mut value, version)?;
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Saves from before versions were added have no version field, so they are version 0
+pub fn read_version(value: &Value) -> u32 {
+    value
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Just the version of a save, so it can be checked without decoding the rest
+#[derive(Deserialize)]
+pub struct SaveVersion {
+    #[serde(default)]
+    pub version: u32,
+}
+
+pub const CHUNK_MIGRATIONS: Migrations = Migrations {
+    name: "chunk",
+    steps: &[chunk_add_block_palette, chunk_add_block_entities, chunk_add_scheduled_ticks],
+};
+
+pub const PLAYER_MIGRATIONS: Migrations = Migrations {
+    name: "player",
+    steps: &[player_add_version],
+};
+
+/// Version 0 chunks may have no block palette. Those blocks already use the current ids, which an
+/// empty palette means.
+fn chunk_add_block_palette(chunk: &mut Value) -> Result<(), ServerError> {
+    let Some(chunk) = chunk.as_object_mut() else {
+        return Err(ServerError::Migration(String::from("chunk is not a map")));
+    };
+
+    chunk.entry("block_palette").or_insert_with(|| json!({ "entries": [] }));
+
+    Ok(())
+}
+
+/// Version 1 chunks have per-block metadata in place of block entities, which nothing ever wrote
+fn chunk_add_block_entities(chunk: &mut Value) -> Result<(), ServerError> {
+    let Some(data) = chunk.get_mut("data").and_then(|data| data.as_object_mut()) else {
+        return Err(ServerError::Migration(String::from("chunk has no data")));
+    };
+
+    data.remove("block_metadata");
+    data.entry("block_entities").or_insert_with(|| json!([]));
+
+    Ok(())
+}
+
+/// Version 2 chunks have no scheduled block ticks
+fn chunk_add_scheduled_ticks(chunk: &mut Value) -> Result<(), ServerError> {
+    let Some(data) = chunk.get_mut("data").and_then(|data| data.as_object_mut()) else {
+        return Err(ServerError::Migration(String::from("chunk has no data")));
+    };
+
+    data.entry("scheduled_ticks").or_insert_with(|| json!([]));
+
+    Ok(())
+}
+
+/// Version 0 player files are the same apart from having no version field
+fn player_add_version(player: &mut Value) -> Result<(), ServerError> {
+    let Some(player) = player.as_object_mut() else {
+        return Err(ServerError::Migration(String::from("player is not a map")));
+    };
+
+    player.entry("version").or_insert(json!(0));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::world::deserialized_player::DeserializedPlayerData;
+    use crate::game::world::migration::{read_version, CHUNK_MIGRATIONS, PLAYER_MIGRATIONS};
+    use crate::game::world::serialized::DeserializedChunkData;
+    use nalgebra::Vector3;
+    use serde_json::{json, Value};
+
+    /// A chunk as it was saved before versions were used
+    const CHUNK_V0: &str = include_str!("fixtures/chunk_v0.json");
+    /// A player as it was saved before versions were used
+    const PLAYER_V0: &str = include_str!("fixtures/player_v0.json");
+
+    #[test]
+    fn test_load_chunk_v0() {
+        let value = serde_json::from_str::<Value>(CHUNK_V0).unwrap();
+        assert_eq!(read_version(&value), 0);
+
+        let chunk = CHUNK_MIGRATIONS.load::<DeserializedChunkData>(value).unwrap();
+
+        assert_eq!(chunk.version, 0);
+        assert_eq!(chunk.data.position, Vector3::new(1, -2, 3));
+        assert_eq!(chunk.data.world.get(Vector3::new(0, 0, 0)), 6);
+        assert_eq!(chunk.data.world.get(Vector3::new(0, 1, 0)), 0);
+        assert!(chunk.block_palette.entries.is_empty());
+        assert!(chunk.data.block_entities.is_empty());
+        assert!(chunk.data.scheduled_ticks.is_empty());
+        assert_eq!(chunk.game_objects.len(), 1);
+    }
+
+    #[test]
+    fn test_load_player_v0() {
+        let value = serde_json::from_str::<Value>(PLAYER_V0).unwrap();
+        assert_eq!(read_version(&value), 0);
+
+        let player = PLAYER_MIGRATIONS.load::<DeserializedPlayerData>(value).unwrap();
+
+        assert_eq!(player.version, 0);
+        assert_eq!(player.position, Vector3::new(0.5, 30.0, -4.5));
+    }
+
+    #[test]
+    fn test_future_version() {
+        let mut value = json!({ "version": CHUNK_MIGRATIONS.current_version() + 1 });
+
+        assert!(CHUNK_MIGRATIONS.migrate(&mut value, CHUNK_MIGRATIONS.current_version() + 1).is_err());
+    }
+}
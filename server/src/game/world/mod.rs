@@ -1,22 +1,32 @@
-use crate::game::game_object::GameObject;
 use crate::game::world::data::WorldData;
 
 use crate::{AppExit, ServerConfig};
 use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
 use nalgebra::Vector3;
-use rc_shared::game_objects::{GameObjectType, ItemDropGameObjectData, PlayerGameObjectData};
+use rc_shared::block::BlockStates;
+use std::time::Duration;
 use crate::game::generation::ChunkGenerationConfig;
-use crate::game::inventory::Inventory;
+use crate::game::pipes::{PipeSystem, PIPE_ITEMS_PATH};
 use crate::game::world::column::propagate_chunk_columns;
+use crate::game::world::light::light_world;
+use crate::game::world::saving::GameObjectSaveQuery;
 
 pub mod data;
-mod saving;
+pub mod saving;
 pub mod serialized;
 pub mod deserialized_player;
 pub mod column;
+pub mod region;
+pub mod level;
+pub mod migration;
+pub mod light;
 
 pub static WORLD_SPAWN_LOCATION: Vector3<f32> = Vector3::new(0.0, 20.0, 0.0);
 
+/// How many times saving on shutdown is attempted before giving up
+const SHUTDOWN_SAVE_ATTEMPTS: u32 = 3;
+
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
@@ -24,15 +34,26 @@ impl Plugin for WorldPlugin {
         app.add_systems(Update, save_world)
             .add_systems(Startup, load_spawn_chunks)
             .add_systems(Update, propagate_chunk_columns)
+            .add_systems(Update, light_world)
             .insert_resource(WorldData::default());
+
+        let autosave_interval = app.world().resource::<ServerConfig>().autosave_interval;
+        if autosave_interval > 0 {
+            app.add_systems(
+                Update,
+                autosave_world.run_if(on_timer(Duration::from_secs(autosave_interval)))
+            );
+        }
     }
 }
 
 fn save_world(
-    world: Res<WorldData>,
+    mut world: ResMut<WorldData>,
     config: Res<ServerConfig>,
     bevy_shutdown: EventReader<AppExit>,
-    query: Query<(&GameObject, &GameObjectType, &crate::game::transform::Transform, Option<&Inventory>, Option<&ItemDropGameObjectData>, Option<&PlayerGameObjectData>)>
+    query: GameObjectSaveQuery,
+    block_states: Res<BlockStates>,
+    pipes: Res<PipeSystem>
 ) {
     if bevy_shutdown.is_empty() {
         return;
@@ -45,16 +66,66 @@ fn save_world(
 
     info!("Saving world...");
 
-    world.save_world(&config, &query);
+    for attempt in 1..=SHUTDOWN_SAVE_ATTEMPTS {
+        let world_saved = world.save_world(&query, &block_states);
+        let pipes_saved = save_pipe_items(&pipes);
+
+        if world_saved && pipes_saved {
+            info!("Saved world.");
+            return;
+        }
+
+        warn!("Failed to save world (attempt {}/{})", attempt, SHUTDOWN_SAVE_ATTEMPTS);
+    }
+
+    error!("Giving up saving world, some changes have been lost");
+}
+
+/// Saves chunks that have changed since the last save, so a crash loses at most one interval of changes
+fn autosave_world(
+    mut world: ResMut<WorldData>,
+    config: Res<ServerConfig>,
+    query: GameObjectSaveQuery,
+    block_states: Res<BlockStates>,
+    pipes: Res<PipeSystem>
+) {
+    if !config.save_world {
+        return;
+    }
+
+    let unsaved = world.unsaved_chunks.len();
+
+    let world_saved = world.autosave(&query, &block_states);
+    let pipes_saved = save_pipe_items(&pipes);
 
-    info!("Saved world.");
+    if world_saved && pipes_saved {
+        debug!("Autosaved {} changed chunks", unsaved);
+    } else {
+        warn!("Autosave failed, will retry next interval");
+    }
 }
 
 fn load_spawn_chunks(
     mut command: Commands,
     mut world: ResMut<WorldData>,
     config: Res<ServerConfig>,
-    res_config: Res<ChunkGenerationConfig>
+    res_config: Res<ChunkGenerationConfig>,
+    block_states: Res<BlockStates>,
+    mut pipes: ResMut<PipeSystem>
 ) {
-    world.load_spawn_chunks(&mut command, &config, &res_config);
+    world.load_spawn_chunks(&mut command, &config, &res_config, &block_states);
+
+    if let Err(err) = pipes.load_items(PIPE_ITEMS_PATH) {
+        error!("Error reading items travelling through pipes: {:?}", err);
+    }
+}
+
+/// Items in pipes have been taken out of their inventories, so are saved every time the chunks are
+fn save_pipe_items(pipes: &PipeSystem) -> bool {
+    if let Err(err) = pipes.save_items(PIPE_ITEMS_PATH) {
+        error!("Error writing items travelling through pipes, will retry next save: {:?}", err);
+        return false;
+    }
+
+    true
 }
@@ -0,0 +1,557 @@
+use crate::error::ServerError;
+use crate::game::world::serialized::DeserializedChunkData;
+use bevy::log::{error, info, warn};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use nalgebra::Vector3;
+use rc_shared::chunk::ChunkPosition;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::game::world::migration::{SaveVersion, CHUNK_MIGRATIONS};
+use serde_json::Value;
+
+/// Width and depth of a region in chunk columns
+pub const REGION_SIZE: i32 = 32;
+/// Height of a region in chunks
+pub const REGION_HEIGHT: i32 = 8;
+
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_HEIGHT * REGION_SIZE) as usize;
+
+/// Region files are allocated in fixed size sectors so chunks can be rewritten in place
+const SECTOR_SIZE: u64 = 4096;
+
+/// Each header entry is a u32 sector offset followed by a u32 byte length
+const HEADER_ENTRY_SIZE: u64 = 8;
+const HEADER_SECTORS: u32 = ((REGION_CHUNKS as u64 * HEADER_ENTRY_SIZE) / SECTOR_SIZE) as u32;
+
+pub const REGION_DIRECTORY: &str = "./world/regions";
+const LEGACY_CHUNK_DIRECTORY: &str = "./world/chunks";
+const MIGRATED_CHUNK_DIRECTORY: &str = "./world/chunks_migrated";
+
+pub type RegionPosition = Vector3<i32>;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct RegionEntry {
+    /// Offset into the file in sectors, zero when the chunk is not stored
+    sector: u32,
+    /// Length of the compressed chunk data in bytes
+    length: u32,
+}
+
+impl RegionEntry {
+    fn sectors(&self) -> u32 {
+        sectors_for(self.length as u64)
+    }
+
+    fn is_present(&self) -> bool {
+        self.sector != 0
+    }
+}
+
+fn sectors_for(length: u64) -> u32 {
+    length.div_ceil(SECTOR_SIZE) as u32
+}
+
+/// Splits a chunk position into the region that holds it and its index within that region's header
+pub fn region_position(chunk: ChunkPosition) -> (RegionPosition, usize) {
+    let region = Vector3::new(
+        chunk.x.div_euclid(REGION_SIZE),
+        chunk.y.div_euclid(REGION_HEIGHT),
+        chunk.z.div_euclid(REGION_SIZE),
+    );
+
+    let local = Vector3::new(
+        chunk.x.rem_euclid(REGION_SIZE) as usize,
+        chunk.y.rem_euclid(REGION_HEIGHT) as usize,
+        chunk.z.rem_euclid(REGION_SIZE) as usize,
+    );
+
+    let index = (local.y * REGION_SIZE as usize + local.z) * REGION_SIZE as usize + local.x;
+
+    (region, index)
+}
+
+pub fn region_path(region: RegionPosition) -> String {
+    format!("{}/r.{}.{}.{}.region", REGION_DIRECTORY, region.x, region.y, region.z)
+}
+
+/// A container holding up to `REGION_SIZE` x `REGION_HEIGHT` x `REGION_SIZE` chunks in a single file.
+/// The file starts with a fixed size header of per chunk offsets, followed by the zlib compressed
+/// MessagePack encoded chunks.
+pub struct RegionFile {
+    file: File,
+    header: Vec<RegionEntry>,
+    /// Which sectors of the file are currently occupied
+    used_sectors: Vec<bool>,
+}
+
+impl RegionFile {
+    /// Opens the region file for reading and writing, creating it if it doesn't exist
+    pub fn open(region: RegionPosition) -> Result<RegionFile, ServerError> {
+        create_dir_all(REGION_DIRECTORY)?;
+
+        RegionFile::open_path(region_path(region))
+    }
+
+    pub fn open_path(path: impl AsRef<Path>) -> Result<RegionFile, ServerError> {
+        let path = path.as_ref();
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let header_length = HEADER_SECTORS as u64 * SECTOR_SIZE;
+
+        if file.metadata()?.len() < header_length {
+            // New file, write blank header
+            file.set_len(header_length)?;
+        }
+
+        let mut header_data = vec![0u8; header_length as usize];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut header_data)?;
+
+        let header = header_data
+            .chunks_exact(HEADER_ENTRY_SIZE as usize)
+            .map(parse_entry)
+            .collect::<Vec<RegionEntry>>();
+
+        let file_sectors = sectors_for(file.metadata()?.len()) as usize;
+        let mut used_sectors = vec![false; file_sectors];
+
+        used_sectors[..HEADER_SECTORS as usize].fill(true);
+
+        for entry in header.iter().filter(|entry| entry.is_present()) {
+            let start = entry.sector as usize;
+            let end = start + entry.sectors() as usize;
+
+            if end > used_sectors.len() {
+                return Err(ServerError::CorruptRegion(format!(
+                    "Chunk at sector {} extends past end of region {}",
+                    entry.sector, path.display()
+                )));
+            }
+
+            used_sectors[start..end].fill(true);
+        }
+
+        Ok(RegionFile {
+            file,
+            header,
+            used_sectors,
+        })
+    }
+
+    pub fn read_chunk(&mut self, position: ChunkPosition) -> Result<Option<DeserializedChunkData>, ServerError> {
+        let (_, index) = region_position(position);
+        let entry = self.header[index];
+
+        if !entry.is_present() {
+            return Ok(None);
+        }
+
+        read_entry(&mut self.file, entry).map(Some)
+    }
+
+    /// Writes a chunk into the region, replacing any existing copy
+    pub fn write_chunk(&mut self, position: ChunkPosition, chunk: &DeserializedChunkData) -> Result<(), ServerError> {
+        let entry = self.stage_chunk(chunk)?;
+        self.commit_chunk(position, entry)
+    }
+
+    /// Writes a chunk into free sectors, leaving any existing copy untouched.
+    /// The chunk isn't visible until `commit_chunk` points the header at it.
+    fn stage_chunk(&mut self, chunk: &DeserializedChunkData) -> Result<RegionEntry, ServerError> {
+        let data = encode_chunk(chunk)?;
+        let sectors = sectors_for(data.len() as u64);
+        let sector = self.allocate(sectors);
+
+        self.file.seek(SeekFrom::Start(sector as u64 * SECTOR_SIZE))?;
+        self.file.write_all(&data)?;
+
+        // Pad the final sector so the file is always a whole number of sectors
+        let padding = (sectors as u64 * SECTOR_SIZE) - data.len() as u64;
+        self.file.write_all(&vec![0; padding as usize])?;
+
+        Ok(RegionEntry {
+            sector,
+            length: data.len() as u32,
+        })
+    }
+
+    /// Points the header at a staged chunk, releasing the sectors of the copy it replaces
+    fn commit_chunk(&mut self, position: ChunkPosition, entry: RegionEntry) -> Result<(), ServerError> {
+        let (_, index) = region_position(position);
+        let existing = self.header[index];
+
+        self.file.seek(SeekFrom::Start(index as u64 * HEADER_ENTRY_SIZE))?;
+        self.file.write_all(&serialize_entry(entry))?;
+
+        self.header[index] = entry;
+
+        if existing.is_present() {
+            let start = existing.sector as usize;
+            let end = start + existing.sectors() as usize;
+            self.used_sectors[start..end].fill(false);
+        }
+
+        Ok(())
+    }
+
+    /// Flushes all writes to disk
+    pub fn sync(&self) -> Result<(), ServerError> {
+        self.file.sync_all()?;
+        Ok(())
+    }
+
+    /// Finds the first run of free sectors that fits, or extends the file
+    fn allocate(&mut self, sectors: u32) -> u32 {
+        let sectors = sectors as usize;
+        let mut run_start = 0;
+        let mut run_length = 0;
+
+        for (i, used) in self.used_sectors.iter().enumerate() {
+            if *used {
+                run_length = 0;
+                continue;
+            }
+
+            if run_length == 0 {
+                run_start = i;
+            }
+
+            run_length += 1;
+
+            if run_length == sectors {
+                self.used_sectors[run_start..run_start + sectors].fill(true);
+                return run_start as u32;
+            }
+        }
+
+        // Reuse any free sectors at the end of the file before extending it
+        if run_length == 0 {
+            run_start = self.used_sectors.len();
+        }
+
+        self.used_sectors.resize(run_start + sectors, false);
+        self.used_sectors[run_start..run_start + sectors].fill(true);
+
+        run_start as u32
+    }
+}
+
+/// Whether chunks have been saved for the current world, either in region files or the old chunk layout
+pub fn has_saved_chunks() -> Result<bool, ServerError> {
+    for directory in [REGION_DIRECTORY, LEGACY_CHUNK_DIRECTORY] {
+        if fs::exists(directory)? && fs::read_dir(directory)?.next().is_some() {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Reads a single chunk without loading the entire region header
+pub fn read_chunk(position: ChunkPosition) -> Result<Option<DeserializedChunkData>, ServerError> {
+    let (region, index) = region_position(position);
+    let path = region_path(region);
+
+    if !fs::exists(&path)? {
+        return Ok(None);
+    }
+
+    let mut file = File::open(&path)?;
+
+    let mut entry_data = [0u8; HEADER_ENTRY_SIZE as usize];
+    file.seek(SeekFrom::Start(index as u64 * HEADER_ENTRY_SIZE))?;
+    file.read_exact(&mut entry_data)?;
+
+    let entry = parse_entry(&entry_data);
+
+    if !entry.is_present() {
+        return Ok(None);
+    }
+
+    read_entry(&mut file, entry).map(Some)
+}
+
+/// Groups chunks by region so each region file is only opened once.
+/// New copies of the chunks are written to free sectors and synced before the header is updated to
+/// point at them, so a crash part way through leaves every chunk either as it was or fully saved.
+pub fn write_chunks(
+    chunks: impl Iterator<Item = (ChunkPosition, DeserializedChunkData)>,
+) -> Result<(), ServerError> {
+    let mut regions: HashMap<RegionPosition, Vec<(ChunkPosition, DeserializedChunkData)>> = HashMap::new();
+
+    for (position, chunk) in chunks {
+        let (region, _) = region_position(position);
+        regions.entry(region).or_default().push((position, chunk));
+    }
+
+    create_dir_all(REGION_DIRECTORY)?;
+
+    for (region, chunks) in regions {
+        let path = region_path(region);
+        let mut file = RegionFile::open_path(&path)?;
+
+        let mut staged = Vec::with_capacity(chunks.len());
+
+        for (position, chunk) in chunks {
+            staged.push((position, file.stage_chunk(&chunk)?));
+        }
+
+        file.sync()?;
+
+        for (position, entry) in staged {
+            file.commit_chunk(position, entry)?;
+        }
+
+        file.sync()?;
+    }
+
+    Ok(())
+}
+
+/// Moves chunks from the old one JSON file per chunk layout into region files.
+/// The old directory is renamed once complete so this only ever runs once.
+pub fn migrate_legacy_chunks() -> Result<(), ServerError> {
+    if !fs::exists(LEGACY_CHUNK_DIRECTORY)? {
+        return Ok(());
+    }
+
+    info!("Migrating chunks from {} to region files", LEGACY_CHUNK_DIRECTORY);
+
+    let mut regions: HashMap<RegionPosition, RegionFile> = HashMap::new();
+    let mut migrated = 0;
+
+    for entry in fs::read_dir(LEGACY_CHUNK_DIRECTORY)? {
+        let path = entry?.path();
+
+        let Some(position) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(parse_legacy_chunk_name) else {
+            warn!("Skipping unrecognised file {:?} during chunk migration", path);
+            continue;
+        };
+
+        let chunk = match File::open(&path)
+            .map_err(ServerError::from)
+            .and_then(|file| Ok(serde_json::from_reader::<_, Value>(BufReader::new(file))?))
+            .and_then(|chunk| CHUNK_MIGRATIONS.load::<DeserializedChunkData>(chunk))
+        {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                error!("Failed to read legacy chunk {:?}: {:?}", path, err);
+                continue;
+            }
+        };
+
+        let (region, _) = region_position(position);
+
+        let file = match regions.entry(region) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(RegionFile::open(region)?),
+        };
+
+        file.write_chunk(position, &chunk)?;
+        migrated += 1;
+    }
+
+    // Keep the old files around in case something went wrong, but stop them being migrated again
+    fs::rename(LEGACY_CHUNK_DIRECTORY, MIGRATED_CHUNK_DIRECTORY)?;
+
+    info!("Migrated {} chunks into {} regions", migrated, regions.len());
+
+    Ok(())
+}
+
+/// Legacy chunk files are named by their position as three 8 character hex numbers
+fn parse_legacy_chunk_name(name: &str) -> Option<ChunkPosition> {
+    let name = name.strip_suffix(".chunk")?;
+
+    if name.len() != 24 || !name.is_ascii() {
+        return None;
+    }
+
+    let x = u32::from_str_radix(&name[0..8], 16).ok()? as i32;
+    let y = u32::from_str_radix(&name[8..16], 16).ok()? as i32;
+    let z = u32::from_str_radix(&name[16..24], 16).ok()? as i32;
+
+    Some(Vector3::new(x, y, z))
+}
+
+fn encode_chunk(chunk: &DeserializedChunkData) -> Result<Vec<u8>, ServerError> {
+    let data = rmp_serde::to_vec_named(chunk)?;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&data)?;
+
+    Ok(encoder.finish()?)
+}
+
+fn read_entry(file: &mut File, entry: RegionEntry) -> Result<DeserializedChunkData, ServerError> {
+    let mut data = vec![0u8; entry.length as usize];
+    file.seek(SeekFrom::Start(entry.sector as u64 * SECTOR_SIZE))?;
+    file.read_exact(&mut data)?;
+
+    let mut decoded = vec![];
+    ZlibDecoder::new(data.as_slice()).read_to_end(&mut decoded)?;
+
+    decode_chunk(&decoded)
+}
+
+/// Reads a MessagePack encoded chunk of any version, upgrading it to the current format
+fn decode_chunk(data: &[u8]) -> Result<DeserializedChunkData, ServerError> {
+    // Chunks in the current format are read directly, as going through migrations is much slower
+    let SaveVersion { version } = rmp_serde::from_slice(data)?;
+
+    if version == CHUNK_MIGRATIONS.current_version() {
+        Ok(rmp_serde::from_slice(data)?)
+    } else {
+        CHUNK_MIGRATIONS.load(rmp_serde::from_slice::<Value>(data)?)
+    }
+}
+
+fn parse_entry(data: &[u8]) -> RegionEntry {
+    RegionEntry {
+        sector: u32::from_le_bytes(data[0..4].try_into().unwrap()),
+        length: u32::from_le_bytes(data[4..8].try_into().unwrap()),
+    }
+}
+
+fn serialize_entry(entry: RegionEntry) -> [u8; HEADER_ENTRY_SIZE as usize] {
+    let mut data = [0u8; HEADER_ENTRY_SIZE as usize];
+    data[0..4].copy_from_slice(&entry.sector.to_le_bytes());
+    data[4..8].copy_from_slice(&entry.length.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::world::migration::CHUNK_MIGRATIONS;
+    use crate::game::world::region::{decode_chunk, parse_legacy_chunk_name, region_position, RegionFile, REGION_SIZE};
+    use crate::game::world::serialized::DeserializedChunkData;
+    use nalgebra::Vector3;
+    use rc_shared::block::entity::{BlockEntityData, InventoryEntity};
+    use rc_shared::chunk::ChunkDataStorage;
+    use rc_shared::item::types::{ItemStack, ItemType};
+
+    fn chunk(position: Vector3<i32>, block: u32) -> DeserializedChunkData {
+        let mut data = ChunkData::blank(position);
+        data.world = ChunkDataStorage::Data(Box::new([[[block; 16]; 16]; 16]));
+
+        DeserializedChunkData {
+            version: CHUNK_MIGRATIONS.current_version(),
+            data,
+            game_objects: vec![],
+            block_palette: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_region_position() {
+        assert_eq!(region_position(Vector3::new(0, 0, 0)), (Vector3::new(0, 0, 0), 0));
+        assert_eq!(region_position(Vector3::new(1, 0, 0)), (Vector3::new(0, 0, 0), 1));
+        assert_eq!(region_position(Vector3::new(-1, -1, -1)).0, Vector3::new(-1, -1, -1));
+        assert_eq!(region_position(Vector3::new(REGION_SIZE, 0, 0)).0, Vector3::new(1, 0, 0));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("rc_region_test_{}.region", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let a = chunk(Vector3::new(0, 0, 0), 1);
+        let b = chunk(Vector3::new(3, 2, -1), 2);
+
+        {
+            let mut region = RegionFile::open_path(path).unwrap();
+            region.write_chunk(a.data.position, &a).unwrap();
+            region.write_chunk(b.data.position, &b).unwrap();
+        }
+
+        let mut region = RegionFile::open_path(path).unwrap();
+        assert_eq!(region.read_chunk(a.data.position).unwrap(), Some(a.clone()));
+        assert_eq!(region.read_chunk(b.data.position).unwrap(), Some(b.clone()));
+        assert_eq!(region.read_chunk(Vector3::new(1, 1, 1)).unwrap(), None);
+
+        // A staged chunk shouldn't replace the saved copy until it's committed
+        let index = region_position(a.data.position).1;
+        let sector = region.header[index].sector;
+        let updated = chunk(a.data.position, 3);
+        let entry = region.stage_chunk(&updated).unwrap();
+
+        assert_ne!(entry.sector, sector);
+        assert_eq!(region.read_chunk(a.data.position).unwrap(), Some(a.clone()));
+        assert_eq!(RegionFile::open_path(path).unwrap().read_chunk(a.data.position).unwrap(), Some(a.clone()));
+
+        region.commit_chunk(a.data.position, entry).unwrap();
+
+        assert_eq!(region.read_chunk(a.data.position).unwrap(), Some(updated));
+        assert_eq!(region.read_chunk(b.data.position).unwrap(), Some(b.clone()));
+
+        // The old copy's sectors are free for the next chunk written
+        let c = chunk(Vector3::new(1, 1, 1), 4);
+        region.write_chunk(c.data.position, &c).unwrap();
+
+        assert_eq!(region.header[region_position(c.data.position).1].sector, sector);
+        assert_eq!(region.read_chunk(c.data.position).unwrap(), Some(c));
+        assert_eq!(region.read_chunk(b.data.position).unwrap(), Some(b));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_legacy_chunk_name() {
+        assert_eq!(
+            parse_legacy_chunk_name("00000001ffffffff00000003.chunk"),
+            Some(Vector3::new(1, -1, 3))
+        );
+        assert_eq!(parse_legacy_chunk_name("game_objects"), None);
+    }
+
+    #[test]
+    fn test_decode_old_chunk() {
+        let old = serde_json::from_str::<serde_json::Value>(include_str!("fixtures/chunk_v0.json")).unwrap();
+
+        let decoded = decode_chunk(&rmp_serde::to_vec_named(&old).unwrap()).unwrap();
+        assert!(decoded.is_outdated());
+        assert_eq!(decoded.data.world.get(Vector3::new(0, 0, 0)), 6);
+
+        let current = chunk(Vector3::new(0, 0, 0), 6);
+        assert_eq!(decode_chunk(&rmp_serde::to_vec_named(&current).unwrap()).unwrap(), current);
+    }
+
+    #[test]
+    fn test_block_entities_saved() {
+        let mut saved = chunk(Vector3::new(0, 0, 0), 76);
+        let mut inventory = InventoryEntity::new(2);
+        let item = ItemType {
+            identifier: String::from("mcv3::StoneItem"),
+            name: String::from("Stone"),
+            icon: String::from("stone"),
+            block_definition_index: None,
+        };
+        inventory.slots[1] = Some(ItemStack::new(item, 3));
+        saved.data.block_entities.set(Vector3::new(4, 5, 6), Some(BlockEntityData::new(&inventory)));
+
+        let encoded = rmp_serde::to_vec_named(&saved).unwrap();
+        assert_eq!(decode_chunk(&encoded).unwrap(), saved);
+
+        // Migrations read chunks through JSON values, which need string keys
+        let value = rmp_serde::from_slice::<serde_json::Value>(&encoded).unwrap();
+        let migrated = CHUNK_MIGRATIONS.load::<DeserializedChunkData>(value).unwrap();
+        let entity = migrated.data.block_entities.get(Vector3::new(4, 5, 6)).unwrap();
+        assert_eq!(entity.get::<InventoryEntity>(), Some(inventory));
+    }
+}
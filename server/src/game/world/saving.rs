@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use crate::game::chunk::ChunkData;
 use crate::game::game_object::GameObject;
 use crate::game::transform::Transform;
@@ -5,39 +6,54 @@ use crate::game::world::data::GAME_OBJECT_ID_COUNTER;
 use crate::game::world::serialized::DeserializedChunkData;
 use crate::ServerConfig;
 use crate::WorldData;
-use bevy::log::{error, info};
+use bevy::log::{error, info, warn};
 use bevy::prelude::{Commands, Query};
 use nalgebra::Vector3;
 use std::collections::HashMap;
 use std::fs;
-use std::fs::{create_dir_all, File};
-use std::io::BufWriter;
-use std::str::FromStr;
+use std::fs::create_dir_all;
 use std::sync::atomic::Ordering;
 use rc_shared::game_objects::{DebugGameObjectData, GameObjectData, GameObjectType, ItemDropGameObjectData, PlayerGameObjectData};
 use crate::config::WorldType;
 use crate::game::generation::ChunkGenerationConfig;
 use crate::game::inventory::Inventory;
 use crate::game::world::deserialized_player::DeserializedPlayerData;
+use crate::game::world::region;
+use crate::helpers::write_atomic;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::ChunkPosition;
+
+const GAME_OBJECT_COUNTER_PATH: &str = "./world/game_objects";
+
+/// Everything needed to save the game objects and players in the world
+pub type GameObjectSaveQuery<'w, 's> = Query<'w, 's, (
+    &'static GameObject,
+    &'static GameObjectType,
+    &'static Transform,
+    Option<&'static Inventory>,
+    Option<&'static ItemDropGameObjectData>,
+    Option<&'static PlayerGameObjectData>
+)>;
 
 impl WorldData {
     pub fn load_spawn_chunks(
         &mut self,
         command: &mut Commands,
         config: &ServerConfig,
-        res_config: &ChunkGenerationConfig
+        res_config: &ChunkGenerationConfig,
+        block_states: &BlockStates
     ) {
+        if let Err(err) = region::migrate_legacy_chunks() {
+            error!("Error migrating legacy chunks: {:?}", err);
+        }
+
         // Load spawn area
         for x in -3..=3 {
             for y in 0..=5 {
                 for z in -3..=3 {
                     let pos = Vector3::new(x, y, z);
 
-                    let DeserializedChunkData {
-                        version,
-                        data,
-                        game_objects,
-                    } = match Self::try_load_chunk(pos) {
+                    let chunk = match Self::try_load_chunk(pos, block_states) {
                         Ok(Some(chunk)) => Some(chunk),
                         Ok(None) => None,
                         Err(err) => {
@@ -50,18 +66,21 @@ impl WorldData {
                             WorldType::Regular => ChunkData::generate(pos, res_config),
                             WorldType::Canvas => ChunkData::generate_canvas(pos)
                         };
-                        DeserializedChunkData {
-                            version: 0,
-                            data,
-                            game_objects: vec![],
-                        }
+                        self.unsaved_chunks.insert(pos);
+                        DeserializedChunkData::new(data, vec![], block_states)
                     });
 
-                    assert_eq!(version, 0);
+                    // Rewrite chunks from older formats in the current one
+                    if chunk.is_outdated() {
+                        self.unsaved_chunks.insert(pos);
+                    }
+
+                    self.insert_chunk(chunk.data);
 
-                    self.insert_chunk(data);
+                    // Loading the chunk's game objects doesn't change it
+                    let unsaved = self.unsaved_chunks.contains(&pos);
 
-                    for (id, game_object, transform, data) in game_objects {
+                    for (id, game_object, transform, data) in chunk.game_objects {
                         let mut entity_commands = command.spawn(transform);
 
                         entity_commands.insert(game_object);
@@ -75,36 +94,78 @@ impl WorldData {
                         let entity = entity_commands.id();
                         self.insert_game_object(id, entity, Vector3::new(x, y, z));
                     }
+
+                    if !unsaved {
+                        self.unsaved_chunks.remove(&pos);
+                    }
                 }
             }
         }
 
-        // Load sequential object id counter
-        if fs::exists("./world/game_objects").unwrap() {
-            let entites = u64::from_str(&fs::read_to_string("./world/game_objects").unwrap())
-                .expect("'/world/game_objects' file corrupted");
+        // Load sequential object id counter, falling back to counting on from the loaded objects
+        let counter = match load_game_object_counter() {
+            Ok(Some(counter)) => counter,
+            Ok(None) => self.next_game_object_id(),
+            Err(err) => {
+                error!("Error reading game object counter, counting on from the loaded objects: {:?}", err);
+                self.next_game_object_id()
+            }
+        };
 
-            GAME_OBJECT_ID_COUNTER.store(entites, Ordering::SeqCst);
-        }
+        GAME_OBJECT_ID_COUNTER.store(counter, Ordering::SeqCst);
 
         info!("Loaded spawn chunks");
     }
 
-    pub fn save_world(
-        &self,
-        config: &ServerConfig,
-        query: &Query<(&GameObject, &GameObjectType, &Transform, Option<&Inventory>, Option<&ItemDropGameObjectData>, Option<&PlayerGameObjectData>)>
-    ) {
-        create_dir_all("./world/chunks").unwrap();
-        create_dir_all("./world/players").unwrap();
+    /// Writes every loaded chunk, the game object counter and all players.
+    /// Each part is attempted even if an earlier one fails, returning false if anything went wrong.
+    pub fn save_world(&mut self, query: &GameObjectSaveQuery, block_states: &BlockStates) -> bool {
+        let chunks = self.chunks.keys().copied().collect::<Vec<ChunkPosition>>();
+
+        let chunks_saved = self.save_chunks(chunks, query, block_states);
+        let counter_saved = save_game_object_counter();
+        let players_saved = save_players(query);
+
+        chunks_saved && counter_saved && players_saved
+    }
+
+    /// Writes the chunks changed since they were last saved, along with anything else that could have
+    /// changed since. Chunks that fail to save stay unsaved, so they are retried next time.
+    pub fn autosave(&mut self, query: &GameObjectSaveQuery, block_states: &BlockStates) -> bool {
+        let chunks = self
+            .unsaved_chunks
+            .iter()
+            .filter(|pos| self.chunks.contains_key(pos))
+            .copied()
+            .collect::<Vec<ChunkPosition>>();
+
+        let chunks_saved = self.save_chunks(chunks, query, block_states);
+        let counter_saved = save_game_object_counter();
+        let players_saved = save_players(query);
+
+        chunks_saved && counter_saved && players_saved
+    }
+
+    fn save_chunks(
+        &mut self,
+        positions: impl IntoIterator<Item = ChunkPosition>,
+        query: &GameObjectSaveQuery,
+        block_states: &BlockStates
+    ) -> bool {
+        let mut chunks = Vec::new();
+
+        for pos in positions {
+            let Some(chunk) = self.chunks.get(&pos) else {
+                continue
+            };
 
-        // Write chunks
-        for (pos, chunk) in &self.chunks {
             let mut game_objects = vec![];
 
-            for (id, entity) in self.game_objects_chunks.get(pos).unwrap_or(&HashMap::new()) {
-                let (game_object, game_object_type, transform, _, item_drop, _)
-                    = query.get(*entity).unwrap();
+            for (id, entity) in self.game_objects_chunks.get(&pos).unwrap_or(&HashMap::new()) {
+                let Ok((game_object, game_object_type, transform, _, item_drop, _)) = query.get(*entity) else {
+                    warn!("Game object {:?} in chunk {:?} has no entity, not saving it", id, pos);
+                    continue
+                };
 
                 let data = match game_object_type {
                     GameObjectType::Debug => GameObjectData::Debug,
@@ -116,50 +177,70 @@ impl WorldData {
                 game_objects.push((*id, game_object.clone(), *transform, data));
             }
 
-            let data = DeserializedChunkData {
-                version: 0,
-                data: chunk.clone(),
-                game_objects,
-            };
+            let data = DeserializedChunkData::new(chunk.clone(), game_objects, block_states);
 
-            let file = File::create(format!(
-                "./world/chunks/{:08x}{:08x}{:08x}.chunk",
-                pos.x, pos.y, pos.z
-            ))
-            .unwrap();
+            chunks.push((pos, data));
+        }
 
-            let mut writer = BufWriter::new(file);
+        let positions = chunks.iter().map(|(pos, _)| *pos).collect::<Vec<ChunkPosition>>();
 
-            serde_json::to_writer(&mut writer, &data).unwrap();
+        if let Err(err) = region::write_chunks(chunks.into_iter()) {
+            error!("Error writing chunk data, will retry next save: {:?}", err);
+            return false;
         }
 
-        fs::write(
-            "./world/game_objects",
-            GAME_OBJECT_ID_COUNTER.load(Ordering::SeqCst).to_string(),
-        )
-        .unwrap();
+        for pos in positions {
+            self.unsaved_chunks.remove(&pos);
+        }
 
-        for (game_object, game_object_type, transform, inventory, _, player_data) in query.iter() {
+        true
+    }
+}
 
-            let Some(inventory) = inventory else {
-                continue
-            };
+/// Reads the id the next game object will be given, if the world has saved one
+fn load_game_object_counter() -> Result<Option<u64>, ServerError> {
+    if !fs::exists(GAME_OBJECT_COUNTER_PATH)? {
+        return Ok(None);
+    }
 
-            let Some(player_data) = player_data else {
-                continue
-            };
+    let data = fs::read_to_string(GAME_OBJECT_COUNTER_PATH)?;
+    Ok(Some(serde_json::from_str(&data)?))
+}
 
-            let data = DeserializedPlayerData {
-                position: transform.position,
-                rotation: transform.rotation,
-                inventory: inventory.clone()
-            };
+fn save_game_object_counter() -> bool {
+    let result = create_dir_all("./world").and_then(|_| write_atomic(
+        GAME_OBJECT_COUNTER_PATH,
+        GAME_OBJECT_ID_COUNTER.load(Ordering::SeqCst).to_string(),
+    ));
 
-            fs::write(
-                format!("./world/players/{}", player_data.user_id.0),
-                serde_json::to_string(&data).unwrap(),
-            )
-            .unwrap();
+    if let Err(err) = result {
+        error!("Error writing game object counter, will retry next save: {:?}", err);
+        return false;
+    }
+
+    true
+}
+
+fn save_players(query: &GameObjectSaveQuery) -> bool {
+    let mut saved = true;
+
+    for (_, _, transform, inventory, _, player_data) in query.iter() {
+
+        let Some(inventory) = inventory else {
+            continue
+        };
+
+        let Some(player_data) = player_data else {
+            continue
+        };
+
+        let data = DeserializedPlayerData::new(transform.position, transform.rotation, inventory.clone());
+
+        if let Err(err) = data.save(player_data.user_id) {
+            error!("Error writing player {:?}, will retry next save: {:?}", player_data.user_id, err);
+            saved = false;
         }
     }
+
+    saved
 }
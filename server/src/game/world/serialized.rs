@@ -1,13 +1,89 @@
 use crate::game::chunk::ChunkData;
 use crate::game::game_object::GameObject;
 use crate::game::transform::Transform;
+use crate::game::world::migration::CHUNK_MIGRATIONS;
+use rc_shared::block::palette::{BlockIdRemap, BlockPalette};
+use rc_shared::block::BlockStates;
 use rc_shared::constants::GameObjectId;
 use serde::{Deserialize, Serialize};
 use rc_shared::game_objects::GameObjectData;
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct DeserializedChunkData {
+    /// The format version the chunk was read from. Chunks are always written in the current version.
     pub version: u32,
     pub data: ChunkData,
     pub game_objects: Vec<(GameObjectId, GameObject, Transform, GameObjectData)>,
+    /// The ids the blocks in `data` had when it was saved. Chunks saved before this existed have none,
+    /// and are assumed to use the current ids.
+    pub block_palette: BlockPalette,
+}
+
+impl DeserializedChunkData {
+    pub fn new(
+        data: ChunkData,
+        game_objects: Vec<(GameObjectId, GameObject, Transform, GameObjectData)>,
+        block_states: &BlockStates,
+    ) -> DeserializedChunkData {
+        DeserializedChunkData {
+            version: CHUNK_MIGRATIONS.current_version(),
+            block_palette: block_states.palette_for(data.world.block_ids()),
+            data,
+            game_objects,
+        }
+    }
+
+    /// Whether the chunk was read from an older format, so should be saved again in the current one
+    pub fn is_outdated(&self) -> bool {
+        self.version < CHUNK_MIGRATIONS.current_version()
+    }
+
+    /// Translates the saved block ids into the ids of the current block list, so adding or removing
+    /// blocks doesn't corrupt existing worlds
+    pub fn remap_blocks(&mut self, block_states: &BlockStates) {
+        if self.block_palette.entries.is_empty() {
+            return;
+        }
+
+        let remap = BlockIdRemap::new(&self.block_palette, &block_states.palette());
+        self.data.world.remap(&remap);
+        self.block_palette = block_states.palette_for(self.data.world.block_ids());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::chunk::ChunkData;
+    use crate::game::world::serialized::DeserializedChunkData;
+    use rc_shared::block::test_block_states;
+    use nalgebra::Vector3;
+    use rc_shared::block::palette::BlockPaletteEntry;
+    use rc_shared::chunk::ChunkDataStorage;
+
+    #[test]
+    fn test_remap_saved_blocks() {
+        let block_states = test_block_states();
+
+        let mut data = ChunkData::blank(Vector3::new(0, 0, 0));
+        data.world = ChunkDataStorage::Data(Box::new([[[6; 16]; 16]; 16]));
+        data.world.set(Vector3::new(0, 0, 0), 8);
+
+        let mut chunk = DeserializedChunkData::new(data, vec![], block_states);
+        assert_eq!(chunk.block_palette.entries.len(), 2);
+
+        // Saved with the current ids, so nothing changes
+        let saved = chunk.clone();
+        chunk.remap_blocks(block_states);
+        assert_eq!(chunk, saved);
+
+        // Saved when stone and sand had each other's ids
+        chunk.block_palette.entries = vec![
+            BlockPaletteEntry { identifier: String::from("mcv3::block::Stone"), start_id: 8, variants: 1 },
+            BlockPaletteEntry { identifier: String::from("mcv3::block::Sand"), start_id: 6, variants: 1 },
+        ];
+        chunk.remap_blocks(block_states);
+
+        assert_eq!(chunk.data.world.get(Vector3::new(0, 0, 0)), 6);
+        assert_eq!(chunk.data.world.get(Vector3::new(1, 0, 0)), 8);
+    }
 }
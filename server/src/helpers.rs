@@ -0,0 +1,39 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+/// Writes `contents` to a temporary file then renames it over `path`, so a crash part way through
+/// leaves either the old or the new file rather than a partially written one
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut temp = path.as_ref().as_os_str().to_owned();
+    temp.push(".tmp");
+
+    let mut file = File::create(&temp)?;
+    file.write_all(contents.as_ref())?;
+    file.sync_all()?;
+
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::write_atomic;
+    use std::fs;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join(format!("rc_atomic_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("counter");
+
+        write_atomic(&path, "1").unwrap();
+        write_atomic(&path, "2").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+        assert!(!fs::exists(dir.join("counter.tmp")).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
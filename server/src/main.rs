@@ -20,14 +20,14 @@ use crate::game::world::WorldPlugin;
 use crate::systems::chunk::ChunkPlugin;
 use crate::systems::connection::ConnectionPlugin;
 use crate::systems::game_object::GameObjectPlugin;
-use crate::systems::tick::tick;
+use crate::systems::tick::{advance_tick, tick, ServerTick};
 use crate::transport::{TransportPlugin, TransportSystem};
-use bevy::app::{App, AppExit, ScheduleRunnerPlugin, Startup};
+use bevy::app::{App, AppExit, PreStartup, ScheduleRunnerPlugin, Startup};
 use bevy::log::{info, Level, LogPlugin};
-use bevy::prelude::{default, AssetPlugin, AssetServer, EventWriter, PluginGroup, PreUpdate, Res, ResMut, Update, Time, Fixed};
+use bevy::prelude::{default, AssetPlugin, AssetServer, EventWriter, IntoSystemConfigs, PluginGroup, PreUpdate, Res, ResMut, Update, Time, Fixed, FixedPreUpdate, FixedUpdate};
 use bevy::MinimalPlugins;
 use crate::events::join::PlayerSpawnEvent;
-use crate::game::pipes::generate_links;
+use crate::game::pipes::PipePlugin;
 use rc_networking::types::{ReceivePacket, SendPacket};
 use rc_shared::block::{BlockStates, BlockStatesPlugin};
 use rc_shared::item::{ItemStates, ItemStatesPlugin};
@@ -37,8 +37,10 @@ use rc_networking::protocol::Protocol;
 use rc_shared::atlas::{TEXTURE_ATLAS, TextureAtlas};
 use rc_shared::{config as config_macro, PHYSICS_SYNC_RATE_SECONDS};
 use crate::game::commands::CommandsPlugin;
+use crate::game::movement::MovementPlugin;
 use crate::game::entity::EntityPlugin;
 use crate::game::join_message::{join_message, leave_message};
+use crate::game::generation::prepare_generation;
 use crate::systems::chat::broadcast_chat;
 
 #[macro_use]
@@ -83,19 +85,23 @@ fn main() {
         .add_plugins(ConnectionPlugin)
         .add_plugins(EntityPlugin)
         .add_plugins(CommandsPlugin)
+        .add_plugins(MovementPlugin)
         .add_plugins(BlockStatesPlugin)
-        .add_systems(Startup, load_block_states)
+        // Block states are needed before any chunks are generated
+        .add_systems(PreStartup, (load_block_states, prepare_generation).chain())
         .add_plugins(ItemStatesPlugin)
         .add_plugins(BlockUpdatePlugin)
+        .add_plugins(PipePlugin)
         .add_event::<ReceivePacket>()
         .add_event::<SendPacket>()
         .add_event::<AuthorizationEvent>()
         .add_event::<PlayerSpawnEvent>()
         // Gameplay Loop on Tick
-        .add_systems(Update, tick)
+        .init_resource::<ServerTick>()
+        .add_systems(FixedPreUpdate, advance_tick)
+        .add_systems(FixedUpdate, tick)
         .add_systems(PreUpdate, detect_shutdowns)
         .add_systems(Startup, create_states)
-        .add_systems(Update, generate_links)
         .add_systems(Update, propagate_inventories)
         .add_systems(Update, (broadcast_chat, join_message, leave_message))
         // Run App
@@ -1,4 +1,6 @@
 mod dirty;
+mod summary;
+mod unload;
 
 use crate::game::chunk::ChunkData;
 use crate::{TransportSystem, WorldData};
@@ -10,36 +12,51 @@ use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
 use rc_shared::constants::UserId;
 use rc_networking::events::disconnect::NetworkDisconnectionEvent;
+use rc_networking::protocol::clientbound::block_entity_update::BlockEntityUpdate;
 use rc_networking::protocol::clientbound::chunk_update::FullChunkUpdate;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::{ReceivePacket, SendPacket};
 use std::collections::{HashMap, HashSet};
 use rc_networking::protocol::clientbound::chunk_column_update::ChunkColumnUpdate;
+use rc_shared::block::BlockStates;
 use rc_shared::chunk::ChunkPosition;
+use rc_shared::helpers::local_to_global_position;
 use crate::config::{ServerConfig, WorldType};
 use crate::game::generation::ChunkGenerationConfig;
 use crate::systems::chunk::dirty::sync_dirty_chunks;
+use crate::systems::chunk::summary::{send_chunk_summaries, SummaryRequests};
+use crate::systems::chunk::unload::{receive_unload_requests, unload_unused_chunks};
+use bevy::time::common_conditions::on_timer;
+use std::time::Duration;
 
 const MAX_OUTSTANDING_CHUNK_REQUESTS: usize = 80;
 const CHUNKS_GENERATED_PER_TICK: usize = 40;
+/// How often chunks no user has loaded are saved and evicted
+const CHUNK_UNLOAD_INTERVAL: Duration = Duration::from_secs(10);
 
 pub struct ChunkPlugin;
 
 impl Plugin for ChunkPlugin {
     fn build(&self, app: &mut App) {
+        let generation_config = ChunkGenerationConfig::load(app.world().resource::<ServerConfig>());
+
         app.insert_resource(ChunkSystem {
             user_loaded_chunks: Default::default(),
             generating_chunks: Default::default(),
             requesting_chunks: Default::default(),
             chunk_outstanding_requests: Default::default(),
-            user_loaded_columns: Default::default()
+            user_loaded_columns: Default::default(),
+            requesting_summaries: Default::default()
         })
         .add_systems(Update, handle_disconnections)
         .add_systems(Update, get_chunk_requests)
         .add_systems(Update, request_chunks)
         .add_systems(Update, generate_chunks)
+        .add_systems(Update, send_chunk_summaries)
         .add_systems(Update, sync_dirty_chunks)
-        .insert_resource(ChunkGenerationConfig::default());
+        .add_systems(Update, receive_unload_requests)
+        .add_systems(Update, unload_unused_chunks.run_if(on_timer(CHUNK_UNLOAD_INTERVAL)))
+        .insert_resource(generation_config);
     }
 }
 
@@ -51,6 +68,8 @@ pub struct ChunkSystem {
     pub requesting_chunks: HashMap<UserId, Vec<ChunkPosition>>,
     // How many chunk requests have been send and are waiting acknowledgement
     pub chunk_outstanding_requests: HashMap<UserId, usize>,
+    /// Chunk columns users want summaries of, and at what scale
+    pub requesting_summaries: HashMap<UserId, SummaryRequests>,
 }
 
 /// Handles chunk requests coming in, and chunk data going out
@@ -68,15 +87,17 @@ pub fn request_chunks(
         chunk_outstanding_requests,
         generating_chunks,
         user_loaded_chunks,
-        user_loaded_columns
+        user_loaded_columns,
+        ..
     } = &mut *system;
 
     // Remove packets received
     for packet in receive_packets.read() {
         if let Protocol::AcknowledgeChunk(data) = packet.0 {
-            // Remove one
-            *chunk_outstanding_requests.get_mut(&packet.1).unwrap() =
-                (chunk_outstanding_requests.get(&packet.1).unwrap() - 1).max(0);
+            // Remove one, unless the user has since disconnected
+            if let Some(outstanding) = chunk_outstanding_requests.get_mut(&packet.1) {
+                *outstanding = outstanding.saturating_sub(1);
+            }
         }
     }
 
@@ -126,6 +147,16 @@ pub fn request_chunks(
                     *user,
                 ));
 
+                // Send its block entities
+                for (local, entity) in chunk.block_entities.iter() {
+                    let pos = local_to_global_position(chunk.position, local);
+
+                    send_packets.send(SendPacket(
+                        Protocol::BlockEntityUpdate(BlockEntityUpdate::new(pos.x, pos.y, pos.z, Some(entity.clone()))),
+                        *user,
+                    ));
+                }
+
                 user_loaded_chunks.get_mut(user).unwrap().insert(chunk.position);
 
                 // Send chunk column
@@ -166,7 +197,8 @@ pub fn generate_chunks(
     mut system: ResMut<ChunkSystem>,
     mut world: ResMut<WorldData>,
     config: Res<ServerConfig>,
-    gen_config: Res<ChunkGenerationConfig>
+    gen_config: Res<ChunkGenerationConfig>,
+    block_states: Res<BlockStates>
 ) {
     // Generate X chunks per loop
     let chunks_per_loop = system
@@ -188,8 +220,12 @@ pub fn generate_chunks(
     let chunks = iterator
         .map(|pos| {
             // Try load chunk, or generate
-            match WorldData::try_load_chunk(*pos) {
-                Ok(Some(chunk)) => Some(chunk.data),
+            match WorldData::try_load_chunk(*pos, &block_states) {
+                // Chunks from older formats are rewritten in the current one
+                Ok(Some(chunk)) => {
+                    let outdated = chunk.is_outdated();
+                    Some((chunk.data, outdated))
+                }
                 Ok(None) => None,
                 Err(err) => {
                     error!("Error reading chunk data: {:?}", err);
@@ -197,15 +233,19 @@ pub fn generate_chunks(
                 }
             }.unwrap_or_else(|| {
                 // Generate the chunk
-                match config.world_type {
+                let chunk = match config.world_type {
                     WorldType::Regular => ChunkData::generate(*pos, &gen_config),
                     WorldType::Canvas => ChunkData::generate_canvas(*pos)
-                }
+                };
+                (chunk, true)
             })
         })
-        .collect::<Vec<ChunkData>>();
+        .collect::<Vec<(ChunkData, bool)>>();
 
-    for chunk in chunks {
+    for (chunk, unsaved) in chunks {
+        if unsaved {
+            world.unsaved_chunks.insert(chunk.position);
+        }
         world.insert_chunk(chunk);
     }
 }
@@ -238,5 +278,9 @@ fn handle_disconnections(
             disconnection.client
         );
         system.requesting_chunks.remove(&disconnection.client);
+        system.user_loaded_chunks.remove(&disconnection.client);
+        system.user_loaded_columns.remove(&disconnection.client);
+        system.chunk_outstanding_requests.remove(&disconnection.client);
+        system.requesting_summaries.remove(&disconnection.client);
     }
 }
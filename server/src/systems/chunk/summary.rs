@@ -0,0 +1,304 @@
+use std::collections::{HashMap, VecDeque};
+use bevy::prelude::{error, trace, EventReader, EventWriter, Query, Res, ResMut};
+use nalgebra::{Vector2, Vector3};
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rc_networking::protocol::clientbound::chunk_summary_update::ChunkSummaryUpdate;
+use rc_networking::protocol::Protocol;
+use rc_networking::types::{ReceivePacket, SendPacket};
+use rc_shared::block::{BlockId, BlockStates};
+use rc_shared::chunk::{ChunkDataStorage, ChunkPosition};
+use rc_shared::chunk_summary::ChunkSummary;
+use rc_shared::constants::UserId;
+use rc_shared::helpers::global_f32_to_local_position;
+use rc_shared::CHUNK_SIZE;
+use crate::config::{ServerConfig, WorldType};
+use crate::game::chunk::ChunkData;
+use crate::game::generation::{ChunkGenerationConfig, ChunkSurface};
+use crate::game::transform::Transform;
+use crate::game::world::column::update::CHUNK_CHECKING_RANGE;
+use crate::game::world::data::WorldData;
+use crate::systems::chunk::ChunkSystem;
+use crate::transport::TransportSystem;
+
+const SUMMARIES_GENERATED_PER_TICK: usize = 64;
+/// The most chunk columns each user can be waiting on summaries of
+const MAX_QUEUED_SUMMARIES: usize = 8192;
+/// How many chunk columns away from a player summaries are sent, a little past how far the client draws them
+const MAX_SUMMARY_DISTANCE: i32 = 64;
+
+/// Chunk columns a user wants summaries of in the order they asked, each only once at the latest scale asked for
+#[derive(Default)]
+pub struct SummaryRequests {
+    order: VecDeque<Vector2<i32>>,
+    scales: HashMap<Vector2<i32>, u8>,
+}
+
+impl SummaryRequests {
+    /// Queues a column, or changes the scale of one already queued. Returns false if the queue is full
+    pub fn push(&mut self, column: Vector2<i32>, scale: u8) -> bool {
+        if let Some(queued) = self.scales.get_mut(&column) {
+            *queued = scale;
+            return true;
+        }
+
+        if self.scales.len() >= MAX_QUEUED_SUMMARIES {
+            return false;
+        }
+
+        self.order.push_back(column);
+        self.scales.insert(column, scale);
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<(Vector2<i32>, u8)> {
+        let column = self.order.pop_front()?;
+        let scale = self.scales.remove(&column).unwrap();
+        Some((column, scale))
+    }
+
+    pub fn remove(&mut self, column: Vector2<i32>) {
+        if self.scales.remove(&column).is_some() {
+            self.order.retain(|queued| *queued != column);
+        }
+    }
+
+    pub fn retain(&mut self, keep: impl Fn(Vector2<i32>) -> bool) {
+        self.order.retain(|column| keep(*column));
+        self.scales.retain(|column, _| keep(*column));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Answers requests for summaries of distant chunk columns, nearest first as the client sends them
+#[allow(clippy::too_many_arguments)]
+pub fn send_chunk_summaries(
+    mut system: ResMut<ChunkSystem>,
+    mut receive_packets: EventReader<ReceivePacket>,
+    mut send_packets: EventWriter<SendPacket>,
+    world: Res<WorldData>,
+    transport: Res<TransportSystem>,
+    transforms: Query<&Transform>,
+    block_states: Res<BlockStates>,
+    config: Res<ServerConfig>,
+    gen_config: Res<ChunkGenerationConfig>,
+) {
+    // Players we don't know the position of yet can't be checked, but are still limited to a full queue
+    let player_column = |user: &UserId| {
+        let game_object_id = transport.clients.get(user)?.game_object_id?;
+        let transform = transforms.get(world.get_game_object(&game_object_id)?).ok()?;
+        let (chunk, _) = global_f32_to_local_position(transform.position);
+        Some(Vector2::new(chunk.x, chunk.z))
+    };
+    let in_range = |player: Option<Vector2<i32>>, column: Vector2<i32>| {
+        player.is_none_or(|player| {
+            let offset = column - player;
+            offset.x.abs() <= MAX_SUMMARY_DISTANCE && offset.y.abs() <= MAX_SUMMARY_DISTANCE
+        })
+    };
+
+    for packet in receive_packets.read() {
+        match packet.0 {
+            Protocol::RequestChunkSummary(request) => {
+                let column = Vector2::new(request.x, request.z);
+
+                if !in_range(player_column(&packet.1), column) {
+                    trace!("Ignoring summary request for out of range column {:?} from {:?}", column, packet.1);
+                    continue;
+                }
+
+                let requests = system.requesting_summaries.entry(packet.1).or_default();
+
+                if !requests.push(column, request.scale) {
+                    trace!("Ignoring summary request for {:?} from {:?} with a full queue", column, packet.1);
+                }
+            }
+            Protocol::UnloadChunkSummary(request) => {
+                if let Some(requests) = system.requesting_summaries.get_mut(&packet.1) {
+                    requests.remove(Vector2::new(request.x, request.z));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut requests = Vec::new();
+    for (user, columns) in system.requesting_summaries.iter_mut() {
+        // Players may have moved on since asking
+        let player = player_column(user);
+        columns.retain(|column| in_range(player, column));
+
+        while requests.len() < SUMMARIES_GENERATED_PER_TICK {
+            let Some((position, scale)) = columns.pop() else {
+                break;
+            };
+            requests.push((*user, position, scale));
+        }
+    }
+    system.requesting_summaries.retain(|_, columns| !columns.is_empty());
+
+    if requests.is_empty() {
+        return;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let iterator = requests.into_par_iter();
+    #[cfg(target_arch = "wasm32")]
+    let iterator = requests.into_iter();
+
+    let summaries = iterator
+        .map(|(user, position, scale)| {
+            let load_saved = |chunk_position| match WorldData::try_load_chunk(chunk_position, &block_states) {
+                Ok(chunk) => chunk.map(|chunk| chunk.data.world),
+                Err(err) => {
+                    error!("Error reading chunk {:?} for summary: {:?}", chunk_position, err);
+                    None
+                }
+            };
+
+            let surface = column_surface(position, &world, load_saved, || match config.world_type {
+                WorldType::Regular => ChunkData::generate_surface(position, &gen_config),
+                WorldType::Canvas => ChunkData::generate_canvas_surface(),
+            });
+
+            (user, position, ChunkSummary::from_surface(scale, |x, z| surface[x][z]))
+        })
+        .collect::<Vec<(_, Vector2<i32>, ChunkSummary)>>();
+
+    for (user, position, summary) in summaries {
+        send_packets.send(SendPacket(
+            Protocol::ChunkSummaryUpdate(ChunkSummaryUpdate::new(position, summary)),
+            user,
+        ));
+    }
+}
+
+/// Finds the surface of a chunk column from its loaded chunks and those `load_saved` reads, so changes made to the
+/// world show from afar. Block columns whose top is in a chunk that hasn't been generated yet use the surface from
+/// `generate` instead.
+fn column_surface(
+    position: Vector2<i32>,
+    world: &WorldData,
+    load_saved: impl Fn(ChunkPosition) -> Option<ChunkDataStorage>,
+    generate: impl Fn() -> ChunkSurface,
+) -> ChunkSurface {
+    let mut surface: [[Option<(i32, BlockId)>; CHUNK_SIZE]; CHUNK_SIZE] = [[None; CHUNK_SIZE]; CHUNK_SIZE];
+    let mut generated = None;
+
+    for chunk_y in CHUNK_CHECKING_RANGE {
+        let chunk_position = Vector3::new(position.x, chunk_y, position.y);
+        let bottom = chunk_y * CHUNK_SIZE as i32;
+
+        let saved: Option<ChunkDataStorage>;
+        let chunk = match world.chunks.get(&chunk_position) {
+            Some(chunk) => Some(&chunk.world),
+            None => {
+                saved = load_saved(chunk_position);
+                saved.as_ref()
+            }
+        };
+
+        for (x, row) in surface.iter_mut().enumerate() {
+            for (z, top) in row.iter_mut().enumerate() {
+                if top.is_some() {
+                    continue;
+                }
+
+                *top = match chunk {
+                    Some(chunk) => (0..CHUNK_SIZE)
+                        .rev()
+                        .map(|y| (y, chunk.get(Vector3::new(x, y, z))))
+                        .find(|(_, block)| *block != 0)
+                        .map(|(y, block)| (bottom + y as i32, block)),
+                    None => {
+                        let (height, block) = generated.get_or_insert_with(&generate)[x][z];
+                        (height >= bottom).then_some((height, block))
+                    }
+                };
+            }
+        }
+
+        if surface.iter().flatten().all(Option::is_some) {
+            break;
+        }
+    }
+
+    let mut result = [[(0, 0); CHUNK_SIZE]; CHUNK_SIZE];
+
+    for (x, row) in result.iter_mut().enumerate() {
+        for (z, top) in row.iter_mut().enumerate() {
+            *top = surface[x][z].unwrap_or_else(|| generated.get_or_insert_with(&generate)[x][z]);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Vector2, Vector3};
+    use rc_shared::CHUNK_SIZE;
+    use crate::game::chunk::ChunkData;
+    use crate::game::world::data::WorldData;
+    use rc_shared::block::test_block_states;
+    use crate::systems::chunk::summary::{column_surface, SummaryRequests, MAX_QUEUED_SUMMARIES};
+
+    #[test]
+    fn test_summary_requests() {
+        let mut requests = SummaryRequests::default();
+
+        assert!(requests.push(Vector2::new(0, 0), 4));
+        assert!(requests.push(Vector2::new(1, 0), 4));
+
+        // Asking again changes the scale without queueing the column twice
+        assert!(requests.push(Vector2::new(0, 0), 2));
+        requests.remove(Vector2::new(1, 0));
+
+        assert_eq!(requests.pop(), Some((Vector2::new(0, 0), 2)));
+        assert_eq!(requests.pop(), None);
+
+        for x in 0..MAX_QUEUED_SUMMARIES as i32 {
+            assert!(requests.push(Vector2::new(x, 0), 1));
+        }
+        assert!(!requests.push(Vector2::new(-1, 0), 1));
+    }
+
+    #[test]
+    fn test_column_surface_uses_world() {
+        let block_states = test_block_states();
+        let stone = block_states.get_by_identifier("mcv3::block::Stone").unwrap().1.get_id();
+        let sand = block_states.get_by_identifier("mcv3::block::Sand").unwrap().1.get_id();
+
+        let position = Vector2::new(2, 3);
+
+        let mut chunk = ChunkData::blank(Vector3::new(position.x, 1, position.y));
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE / 2 {
+                chunk.world.set(Vector3::new(x, 3, z), stone);
+            }
+        }
+
+        let mut world_data = WorldData::default();
+        world_data.chunks.insert(chunk.position, chunk);
+
+        // The chunk below is only saved, with sand covering half of it
+        let mut saved = ChunkData::blank(Vector3::new(position.x, 0, position.y));
+        for x in 0..CHUNK_SIZE / 2 {
+            for z in 0..CHUNK_SIZE {
+                saved.world.set(Vector3::new(x, 2, z), sand);
+            }
+        }
+        let load_saved = |chunk_position: Vector3<i32>| (chunk_position == saved.position).then(|| saved.world.clone());
+
+        let surface = column_surface(position, &world_data, load_saved, || [[(5, 7); CHUNK_SIZE]; CHUNK_SIZE]);
+
+        assert_eq!(surface[0][0], (CHUNK_SIZE as i32 + 3, stone));
+        // Block columns left empty fall through to the saved chunk below
+        assert_eq!(surface[0][CHUNK_SIZE - 1], (2, sand));
+        // Then to the generated surface where nothing has been saved
+        assert_eq!(surface[CHUNK_SIZE - 1][CHUNK_SIZE - 1], (5, 7));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+use bevy::prelude::{error, trace, EventReader, Res, ResMut};
+use nalgebra::{Vector2, Vector3};
+use rc_networking::protocol::Protocol;
+use rc_networking::types::ReceivePacket;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::ChunkPosition;
+use rc_shared::helpers::global_f32_to_local_position;
+use crate::config::ServerConfig;
+use crate::game::world::data::WorldData;
+use crate::game::world::region;
+use crate::game::world::serialized::DeserializedChunkData;
+use crate::game::world::WORLD_SPAWN_LOCATION;
+use crate::systems::chunk::ChunkSystem;
+
+/// Forgets chunks that clients tell us they have unloaded
+pub fn receive_unload_requests(
+    mut request: EventReader<ReceivePacket>,
+    mut system: ResMut<ChunkSystem>,
+) {
+    let ChunkSystem {
+        user_loaded_chunks,
+        user_loaded_columns,
+        requesting_chunks,
+        ..
+    } = &mut *system;
+
+    for packet in request.read() {
+        let Protocol::UnloadChunk(request) = packet.0 else {
+            continue
+        };
+
+        let pos = Vector3::new(request.x, request.y, request.z);
+
+        if let Some(requesting) = requesting_chunks.get_mut(&packet.1) {
+            requesting.retain(|chunk| *chunk != pos);
+        }
+
+        let Some(loaded_chunks) = user_loaded_chunks.get_mut(&packet.1) else {
+            continue
+        };
+
+        loaded_chunks.remove(&pos);
+
+        // Columns are sent alongside the first chunk in them, so forget it once none are left
+        let column_loaded = loaded_chunks
+            .iter()
+            .any(|chunk| chunk.x == pos.x && chunk.z == pos.z);
+
+        if !column_loaded {
+            if let Some(columns) = user_loaded_columns.get_mut(&packet.1) {
+                columns.remove(&Vector2::new(pos.x, pos.z));
+            }
+        }
+    }
+}
+
+/// Saves then evicts chunks that no user has loaded
+pub fn unload_unused_chunks(
+    mut world: ResMut<WorldData>,
+    system: Res<ChunkSystem>,
+    config: Res<ServerConfig>,
+    block_states: Res<BlockStates>,
+) {
+    let held_chunks = system
+        .user_loaded_chunks
+        .values()
+        .flatten()
+        .chain(system.requesting_chunks.values().flatten())
+        .chain(system.generating_chunks.iter())
+        .collect::<HashSet<&ChunkPosition>>();
+
+    let (spawn_chunk, _) = global_f32_to_local_position(WORLD_SPAWN_LOCATION);
+
+    let unused_chunks = world
+        .chunks
+        .keys()
+        .filter(|pos| !held_chunks.contains(pos))
+        .filter(|pos| (*pos - spawn_chunk).cast::<f32>().magnitude() > config.spawn_keep_alive as f32)
+        // Game objects are only loaded with spawn chunks, so keep chunks containing them
+        .filter(|pos| world.game_objects_chunks.get(pos).is_none_or(|objects| objects.is_empty()))
+        // Evicted chunks are read back from disk, so without saving any changes to them would be lost
+        .filter(|pos| config.save_world || !world.unsaved_chunks.contains(pos))
+        .copied()
+        .collect::<Vec<ChunkPosition>>();
+
+    if unused_chunks.is_empty() {
+        return;
+    }
+
+    // Chunks that haven't changed since they were last saved are already on disk
+    let chunks = unused_chunks
+        .iter()
+        .filter(|pos| world.unsaved_chunks.contains(pos))
+        .map(|pos| {
+            (*pos, DeserializedChunkData::new(world.chunks.get(pos).unwrap().clone(), vec![], &block_states))
+        })
+        .collect::<Vec<_>>();
+
+    // Keep the chunks in memory if they couldn't be saved, so they are tried again next time
+    if let Err(err) = region::write_chunks(chunks.into_iter()) {
+        error!("Error writing unloaded chunks: {:?}", err);
+        return;
+    }
+
+    for pos in &unused_chunks {
+        world.chunks.remove(pos);
+        world.unsaved_chunks.remove(pos);
+    }
+
+    // Remove columns that no longer have any chunks in them
+    let loaded_columns = world
+        .chunks
+        .keys()
+        .map(|pos| Vector2::new(pos.x, pos.z))
+        .collect::<HashSet<Vector2<i32>>>();
+    world.chunks_columns.retain(|pos, _| loaded_columns.contains(pos));
+
+    trace!("Unloaded {} chunks", unused_chunks.len());
+}
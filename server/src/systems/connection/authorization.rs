@@ -1,12 +1,11 @@
 use std::collections::HashSet;
-use std::fs;
 use crate::game::inventory::Inventory;
 use crate::game::transform::Transform;
-use bevy::ecs::change_detection::ResMut;
+use bevy::ecs::change_detection::{Res, ResMut};
 use bevy::ecs::event::EventReader;
 use bevy::ecs::prelude::{Commands, EventWriter};
 use bevy::ecs::system::Query;
-use bevy::log::info;
+use bevy::log::{error, info};
 use nalgebra::Vector3;
 use rc_shared::game_objects::{GameObjectData, PlayerGameObjectData};
 use std::sync::atomic::Ordering;
@@ -17,7 +16,9 @@ use crate::systems::chunk::ChunkSystem;
 use crate::{TransportSystem, WorldData};
 use rc_shared::constants::GameObjectId;
 use crate::systems::game_object::spawn::SpawnGameObjectRequest;
+use rc_networking::protocol::Protocol;
 use rc_networking::types::SendPacket;
+use rc_shared::block::BlockStates;
 use crate::events::join::PlayerSpawnEvent;
 use crate::game::world::deserialized_player::DeserializedPlayerData;
 use crate::game::world::WORLD_SPAWN_LOCATION;
@@ -26,21 +27,31 @@ pub fn authorization_event(
     mut event_reader: EventReader<AuthorizationEvent>,
     global: ResMut<WorldData>,
     mut transport: ResMut<TransportSystem>,
-    send_packet: EventWriter<SendPacket>,
+    mut send_packet: EventWriter<SendPacket>,
     mut commands: Commands,
     transforms: Query<&Transform>,
     mut chunk_system: ResMut<ChunkSystem>,
     mut spawn_game_object: EventWriter<SpawnGameObjectRequest>,
-    mut player_spawn_event: EventWriter<PlayerSpawnEvent>
+    mut player_spawn_event: EventWriter<PlayerSpawnEvent>,
+    block_states: Res<BlockStates>
 ) {
     for client in event_reader.read() {
+        // Block ids are assigned when the server starts, so tell the client which ids we use before sending any blocks
+        send_packet.send(SendPacket(Protocol::BlockPalette(block_states.palette()), client.user_id));
+
         // Load player data
-        let path = format!("./world/players/{}", client.user_id.0);
-        let (transform, mut inventory) = if fs::exists(&path).unwrap() {
-            let player_data = serde_json::from_str::<DeserializedPlayerData>(&
-                fs::read_to_string(&path).unwrap()
-            ).unwrap();
+        let player_data = match DeserializedPlayerData::load(client.user_id) {
+            Ok(player_data) => player_data,
+            Err(err) => {
+                error!("Error reading player data of {:?}, spawning them fresh: {:?}", client.user_id, err);
+                if let Err(err) = DeserializedPlayerData::set_aside(client.user_id) {
+                    error!("Error moving player data of {:?} aside: {:?}", client.user_id, err);
+                }
+                None
+            }
+        };
 
+        let (transform, mut inventory) = if let Some(player_data) = player_data {
             let mut transform = Transform::from_translation(player_data.position);
 
             transform.rotation = player_data.rotation;
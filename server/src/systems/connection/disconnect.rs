@@ -1,9 +1,8 @@
-use std::fs;
 use crate::{TransportSystem, WorldData};
 use bevy::ecs::event::EventReader;
 use bevy::ecs::prelude::{Commands, EventWriter};
 use bevy::ecs::system::ResMut;
-use bevy::prelude::{Query, Res, warn};
+use bevy::prelude::{error, Query, Res, warn};
 use rc_networking::events::disconnect::NetworkDisconnectionEvent;
 use rc_networking::protocol::clientbound::despawn_game_object::DespawnGameObject;
 use crate::game::world::deserialized_player::DeserializedPlayerData;
@@ -43,21 +42,12 @@ pub fn disconnection_event(
 
             if config.save_world {
 
-                // TODO: Move to more centralized place
                 // Save player data
-                let data = DeserializedPlayerData {
-                    position: transform.position,
-                    rotation: transform.rotation,
-                    inventory: inventory.clone()
-                };
+                let data = DeserializedPlayerData::new(transform.position, transform.rotation, inventory.clone());
 
-                fs::create_dir_all("./world/players").unwrap();
-
-                fs::write(
-                    format!("./world/players/{}", entry.user_id.0),
-                    serde_json::to_string(&data).unwrap(),
-                )
-                .unwrap();
+                if let Err(err) = data.save(entry.user_id) {
+                    error!("Error saving player {:?} on disconnect: {:?}", entry.user_id, err);
+                }
             }
 
             // Delete game_object
@@ -1,18 +1,21 @@
 use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 use crate::game::transform::Transform;
 use crate::game::update::{BlockPokeEvent, BlockUpdateEvent};
 use crate::game::world::data::GAME_OBJECT_ID_COUNTER;
-use rc_shared::helpers::global_to_local_position;
+use rc_shared::helpers::{global_f32_to_local_position, global_to_local_position};
 use crate::systems::game_object::spawn::SpawnGameObjectRequest;
 use crate::{TransportSystem, WorldData};
 use bevy::ecs::prelude::*;
 use nalgebra::{Quaternion, Vector3, Vector4};
 use rand::Rng;
 use rc_networking::protocol::clientbound::block_update::BlockUpdate;
+use rc_networking::protocol::clientbound::game_object_moved::GameObjectMoved;
 use rc_networking::protocol::Protocol;
 use rc_networking::types::{ReceivePacket, SendPacket};
 use rc_shared::block::BlockStates;
+use rc_shared::block::entity::InventoryEntity;
 use rc_shared::constants::GameObjectId;
 use rc_shared::game_objects::{GameObjectData, ItemDropGameObjectData};
 use rc_shared::item::types::ItemStack;
@@ -22,13 +25,20 @@ use bevy::log::warn;
 use bevy::prelude::trace;
 use crate::game::entity::{DirtyPosition, DirtyRotation};
 use crate::game::inventory::Inventory;
+use crate::game::interaction::{validate_destroy, validate_place};
+use crate::game::movement::MovementValidator;
+use crate::systems::tick::ServerTick;
+use crate::systems::chunk::ChunkSystem;
+use rc_shared::chunk::GlobalBlockPosition;
+use rc_shared::constants::UserId;
+use rc_shared::game_objects::PlayerGameObjectData;
 
 pub fn receive_message_event(
     mut event_reader: EventReader<ReceivePacket>,
     mut event_writer: EventWriter<SendPacket>,
     mut global: ResMut<WorldData>,
     system: Res<TransportSystem>,
-    mut transforms: Query<&mut Transform>,
+    mut transforms: Query<(&mut Transform, Option<&PlayerGameObjectData>)>,
     mut block_update_writer: EventWriter<BlockUpdateEvent>,
     mut block_poke_writer: EventWriter<BlockPokeEvent>,
     mut ew: EventWriter<SpawnGameObjectRequest>,
@@ -36,6 +46,9 @@ pub fn receive_message_event(
     item_states: Res<ItemStates>,
     mut commands: Commands,
     mut inventory: Query<&mut Inventory>,
+    chunk_system: Res<ChunkSystem>,
+    mut movement: ResMut<MovementValidator>,
+    tick: Res<ServerTick>,
 ) {
     for event in event_reader.read() {
         match &event.0 {
@@ -46,9 +59,43 @@ pub fn receive_message_event(
                 };
 
                 if let Some(val) = global.get_game_object(&entity) {
+                    let mut transform = transforms.get_mut(val).unwrap().0;
+                    let position = Vector3::new(packet.x, packet.y, packet.z);
+
+                    if let Err(reason) = movement.validate(
+                        event.1,
+                        &global,
+                        &block_states,
+                        transform.position,
+                        position,
+                        Instant::now(),
+                    ) {
+                        warn!(
+                            "Rejected move from player {:?} to {:?}: {:?} ({} violations)",
+                            event.1, position, reason, movement.violations(&event.1)
+                        );
+
+                        // Snap the player back to where the server thinks they are
+                        event_writer.send(SendPacket(
+                            Protocol::GameObjectMoved(GameObjectMoved {
+                                entity,
+                                tick: tick.0,
+                                x: transform.position.x,
+                                y: transform.position.y,
+                                z: transform.position.z,
+                            }),
+                            event.1,
+                        ));
+                        continue;
+                    }
+
                     // Move player in ecs
-                    transforms.get_mut(val.clone()).unwrap().position =
-                        Vector3::new(packet.x, packet.y, packet.z);
+                    global.move_game_object(
+                        &entity,
+                        global_f32_to_local_position(transform.position).0,
+                        global_f32_to_local_position(position).0,
+                    );
+                    transform.position = position;
                     commands.entity(val.clone()).insert(DirtyPosition);
                     commands.entity(val).insert(DirtyRotation);
                 } else {
@@ -63,7 +110,7 @@ pub fn receive_message_event(
 
                 if let Some(val) = global.get_game_object(&entity) {
                     // Move player in ecs
-                    transforms.get_mut(val).unwrap().rotation =
+                    transforms.get_mut(val).unwrap().0.rotation =
                         Quaternion::from_vector(Vector4::new(packet.x, packet.y, packet.z, packet.w));
                     commands.entity(val.clone()).insert(DirtyPosition);
                     commands.entity(val).insert(DirtyRotation);
@@ -86,16 +133,36 @@ pub fn receive_message_event(
                 let test = global.get_game_object(&game_object_id).unwrap();
                 let mut inventory = inventory.get_mut(test).unwrap();
 
+                let position = Vector3::new(packet.x, packet.y, packet.z);
+                let player_transform = *transforms.get(test).unwrap().0;
+
+                if let Err(rejection) = validate_place(
+                    &global,
+                    &block_states,
+                    &player_transform,
+                    chunk_system.user_loaded_chunks.get(&event.1),
+                    position,
+                    transforms.iter().filter(|(_, player)| player.is_some()).map(|(transform, _)| transform)
+                ) {
+                    warn!("Rejected block placement by {:?} at {:?}: {:?}", event.1, position, rejection);
+                    reject_block_action(&global, &mut event_writer, event.1, position);
+
+                    // Resend the inventory as the client will have already taken the block
+                    inventory.dirty = true;
+                    continue;
+                }
+
                 let block = inventory.take_selected_block();
 
                 let Some(block_definition_index) = block else {
                     warn!("Client tried to place unplacable block");
+                    reject_block_action(&global, &mut event_writer, event.1, position);
+                    inventory.dirty = true;
                     continue
                 };
 
                 let block_id = block_states.get_start_id_by_definition(block_definition_index).unwrap();
 
-                // TODO: Don't trust user input
                 let packet = BlockUpdate::new(block_id, packet.x, packet.y, packet.z);
 
                 for (client, _) in &system.clients {
@@ -118,12 +185,17 @@ pub fn receive_message_event(
                     warn!("{:?} attempted to place block in unloaded chunk. Skipping {:?}", event.1, chunk_loc);
                     continue;
                 };
+                global.unsaved_chunks.insert(chunk_loc);
 
                 // Trigger block update for all surrounding blocks
                 block_update_writer.send(BlockUpdateEvent {
                     pos: Vector3::new(packet.x, packet.y, packet.z),
                     block_id
                 });
+                // The placed block is poked too, so blocks like water start flowing straight away
+                block_poke_writer.send(BlockPokeEvent {
+                    pos: Vector3::new(packet.x, packet.y, packet.z),
+                });
                 for side in &BLOCK_SIDES {
                     block_poke_writer.send(BlockPokeEvent {
                         pos: Vector3::new(packet.x, packet.y, packet.z) + side,
@@ -131,7 +203,26 @@ pub fn receive_message_event(
                 }
             }
             Protocol::DestroyBlock(packet) => {
-                // TODO: Don't trust user input
+                let Some(game_object_id) = system.clients.get(&event.1).unwrap().game_object_id else {
+                    continue
+                };
+                let entity = global.get_game_object(&game_object_id).unwrap();
+
+                let position = Vector3::new(packet.x, packet.y, packet.z);
+                let player_transform = transforms.get(entity).unwrap().0;
+
+                if let Err(rejection) = validate_destroy(
+                    &global,
+                    &block_states,
+                    player_transform,
+                    chunk_system.user_loaded_chunks.get(&event.1),
+                    position
+                ) {
+                    warn!("Rejected block break by {:?} at {:?}: {:?}", event.1, position, rejection);
+                    reject_block_action(&global, &mut event_writer, event.1, position);
+                    continue;
+                }
+
                 let packet = BlockUpdate::new(0, packet.x, packet.y, packet.z);
 
                 for (client, _) in &system.clients {
@@ -154,6 +245,7 @@ pub fn receive_message_event(
                     warn!("{:?} attempted to break block in unloaded chunk. Skipping {:?}", event.1, chunk_loc);
                     continue;
                 };
+                global.unsaved_chunks.insert(chunk_loc);
 
                 // Trigger block update for all surrounding blocks
                 block_update_writer.send(BlockUpdateEvent {
@@ -168,22 +260,19 @@ pub fn receive_message_event(
 
                 // Spawn block drops after destroying
                 if packet.id == 0 {
-                    let drops = calculate_drops(&block_states, &item_states, old_block_id);
+                    let mut drops = calculate_drops(&block_states, &item_states, old_block_id);
+
+                    // Anything the block was holding drops with it
+                    if let Some(inventory) = global
+                        .get_block_entity(position)
+                        .and_then(|entity| entity.get::<InventoryEntity>())
+                    {
+                        drops.extend(inventory.slots.into_iter().flatten());
+                    }
 
                     for drop in drops {
                         trace!("Spawning item drop with item {:?}", drop);
-                        ew.send(SpawnGameObjectRequest {
-                            transform: Transform::from_translation(Vector3::new(
-                                packet.x as f32 + 0.5,
-                                packet.y as f32 + 0.5,
-                                packet.z as f32 + 0.5,
-                            )),
-                            data: GameObjectData::ItemDrop(ItemDropGameObjectData {
-                                item_stack: drop,
-                            }),
-                            id: GameObjectId(GAME_OBJECT_ID_COUNTER.fetch_add(1, Ordering::SeqCst)),
-                            entity: None,
-                        });
+                        ew.send(item_drop_request(drop, position));
                     }
                 }
             }
@@ -192,7 +281,34 @@ pub fn receive_message_event(
     }
 }
 
-fn calculate_drops(
+/// Sends the server's copy of a block back to a client so any change they predicted is rolled back
+fn reject_block_action(
+    world: &WorldData,
+    event_writer: &mut EventWriter<SendPacket>,
+    user: UserId,
+    pos: GlobalBlockPosition,
+) {
+    let Some(block_id) = world.get_block_id(pos) else {
+        return
+    };
+
+    event_writer.send(SendPacket(
+        Protocol::BlockUpdate(BlockUpdate::new(block_id, pos.x, pos.y, pos.z)),
+        user,
+    ));
+}
+
+/// Spawns an item stack in the middle of the block at `pos`
+pub(crate) fn item_drop_request(item_stack: ItemStack, pos: GlobalBlockPosition) -> SpawnGameObjectRequest {
+    SpawnGameObjectRequest {
+        transform: Transform::from_translation(pos.cast::<f32>() + Vector3::new(0.5, 0.5, 0.5)),
+        data: GameObjectData::ItemDrop(ItemDropGameObjectData { item_stack }),
+        id: GameObjectId(GAME_OBJECT_ID_COUNTER.fetch_add(1, Ordering::SeqCst)),
+        entity: None,
+    }
+}
+
+pub(crate) fn calculate_drops(
     block_states: &BlockStates,
     item_states: &ItemStates,
     block_id: u32,
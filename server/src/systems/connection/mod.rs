@@ -7,7 +7,7 @@ use rc_shared::constants::{GameObjectId, UserId};
 
 mod authorization;
 mod disconnect;
-mod message;
+pub(crate) mod message;
 
 pub struct ConnectionPlugin;
 
@@ -6,6 +6,8 @@ use crate::game::game_object::GameObject;
 use rc_shared::helpers::global_to_local_position;
 use crate::systems::chunk::ChunkSystem;
 use crate::transport::TransportSystem;
+use crate::WorldData;
+use rc_shared::helpers::global_f32_to_local_position;
 
 #[derive(Component)]
 pub struct DespawnGameObject;
@@ -14,6 +16,7 @@ pub fn despawn_game_objects(
     query: Query<(Entity, &crate::game::transform::Transform, &GameObject), With<DespawnGameObject>>,
     chunk_system: Res<ChunkSystem>,
     system: Res<TransportSystem>,
+    mut world: ResMut<WorldData>,
     mut commands: Commands,
     mut send_packet: EventWriter<SendPacket>
 ) {
@@ -30,7 +33,7 @@ pub fn despawn_game_objects(
 
         for (user_id, user) in &system.clients {
             // If player has chunk loaded
-            if !chunk_system.user_loaded_chunks.get(user_id).unwrap().contains(&chunk_pos) {
+            if !chunk_system.user_loaded_chunks.get(user_id).is_some_and(|chunks| chunks.contains(&chunk_pos)) {
                 continue
             }
 
@@ -46,6 +49,10 @@ pub fn despawn_game_objects(
             );
         }
 
+        // Found the same way as when it was spawned, so it's taken out of the chunk it was put in
+        let (object_chunk, _) = global_f32_to_local_position(transform.position);
+        world.remove_game_object(&game_object.id, object_chunk);
+
         commands.entity(entity).despawn_recursive();
     }
 }
\ No newline at end of file
@@ -1,5 +1,65 @@
+use crate::config::ServerConfig;
+use crate::game::update::ticks::{TickChangeWriters, TickWorld};
+use crate::game::world::data::WorldData;
+use bevy::prelude::{Res, ResMut, Resource};
+use nalgebra::Vector3;
+use rand::Rng;
+use rc_shared::block::tick::BlockTickContext;
+use rc_shared::block::BlockStates;
+use rc_shared::chunk::{ChunkDataStorage, ChunkPosition};
+use rc_shared::helpers::local_to_global_position;
+use rc_shared::CHUNK_SIZE;
 
+/// The number of fixed updates the server has run, stamped on packets so clients can order them in time
+#[derive(Resource, Default, Debug, Copy, Clone)]
+pub struct ServerTick(pub u64);
 
-pub fn tick() {
-    // All game logic should happen here, on a tick event
-}
\ No newline at end of file
+/// Runs the block ticks that are due, then random ticks a few blocks in every loaded chunk
+pub fn tick(
+    mut world_data: ResMut<WorldData>,
+    mut writers: TickChangeWriters,
+    block_states: Res<BlockStates>,
+    config: Res<ServerConfig>,
+) {
+    let due = world_data.take_due_ticks();
+    let mut world = TickWorld::new(&mut world_data, &block_states);
+
+    for (pos, identifier) in due {
+        // The block was replaced since the tick was scheduled
+        let Some(block) = world.get_block(pos).filter(|block| block.get_identifier() == identifier) else {
+            continue;
+        };
+
+        block.scheduled_tick(pos, &mut world);
+    }
+
+    let chunks = world
+        .world_data()
+        .chunks
+        .iter()
+        .filter(|(_, chunk)| !matches!(chunk.world, ChunkDataStorage::Empty))
+        .map(|(position, _)| *position)
+        .collect::<Vec<ChunkPosition>>();
+
+    for chunk in chunks {
+        for _ in 0..config.random_tick_speed {
+            let rng = world.rng();
+            let local = Vector3::new(
+                rng.gen_range(0..CHUNK_SIZE),
+                rng.gen_range(0..CHUNK_SIZE),
+                rng.gen_range(0..CHUNK_SIZE),
+            );
+            let pos = local_to_global_position(chunk, local);
+
+            if let Some(block) = world.get_block(pos).filter(|block| block.has_random_ticks()) {
+                block.random_tick(pos, &mut world);
+            }
+        }
+    }
+
+    writers.apply(world.finish(), &block_states);
+}
+
+pub fn advance_tick(mut tick: ResMut<ServerTick>) {
+    tick.0 += 1;
+}